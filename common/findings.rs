@@ -0,0 +1,354 @@
+// Findings: suspected bugs, reduced to signatures so repeats of one bug are counted instead of
+// stored again, and kept in a SQLite file that executors write and the server reads.
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// The store shared by every executor started from the same directory.
+pub const FINDINGS_DB: &str = "findings.db";
+
+/// Schema changes in the order they were made; a store's `user_version` counts those applied.
+/// Only ever append to this list. The first one also adopts stores made before versioning.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS findings (
+        signature TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        code TEXT NOT NULL,
+        template TEXT NOT NULL,
+        stmt_kind TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        first_seed INTEGER NOT NULL,
+        first_seen TEXT NOT NULL,
+        last_seen TEXT NOT NULL,
+        sql TEXT NOT NULL,
+        detail TEXT NOT NULL,
+        reproducer TEXT NOT NULL,
+        schema TEXT NOT NULL
+    );",
+];
+
+/// Words that are followed by the name of a schema object in SQLite's error messages.
+const NAME_KEYWORDS: &[&str] = &["table", "column", "view", "index", "trigger", "named", "function", "collation"];
+
+/// A suspected bug, kept with everything needed to replay it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Finding {
+    /// `error`, `panic`, `differential`, `crash`, `hang` or the name of the oracle that found it
+    pub kind: String,
+    pub seed: u64,
+    pub sql: String,
+    /// For errors the error code and message, otherwise what differed
+    pub detail: String,
+    /// Statements that lead to the finding, ending with `sql` for errors
+    pub history: Vec<String>,
+    /// CREATE statements of the schema objects when the finding was made; empty if not captured.
+    #[serde(default)]
+    pub schema: Vec<String>,
+    pub timestamp: String,
+}
+
+impl Finding {
+    pub fn new(kind: &str, seed: u64, sql: &str, detail: String, history: Vec<String>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            seed,
+            sql: sql.to_string(),
+            detail,
+            history,
+            schema: vec![],
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn with_schema(mut self, schema: Vec<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn signature(&self) -> Signature {
+        Signature::new(&self.kind, &self.sql, &self.detail)
+    }
+}
+
+/// What makes two findings the same bug: where names, numbers and literals in the message
+/// are left out, the rest has to match.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signature {
+    pub kind: String,
+    /// SQLite's result code for errors, empty otherwise
+    pub code: String,
+    pub template: String,
+    /// Leading keywords of the statement, e.g. `CREATE VIEW`
+    pub stmt_kind: String,
+}
+
+impl Signature {
+    pub fn new(kind: &str, sql: &str, detail: &str) -> Self {
+        let (code, message) = match kind {
+            "error" => detail.split_once(": ").unwrap_or(("", detail)),
+            // Value differences go on to quote the first differing rows
+            "differential" => ("", detail.split("; ").next().unwrap_or_default()),
+            // The first line says how the executor ended and where, without varying parts; the
+            // executor's last output follows
+            "crash" | "hang" => {
+                let first = detail.lines().next().unwrap_or_default();
+                let (exit, cause) = first.split_once(": ").unwrap_or((first, ""));
+                return Self {
+                    kind: kind.to_string(),
+                    code: exit.to_string(),
+                    template: cause.to_string(),
+                    stmt_kind: String::new(),
+                };
+            }
+            // Oracle details go on to quote the rewritten queries
+            _ => ("", detail.split(':').next().unwrap_or_default()),
+        };
+        Self {
+            kind: kind.to_string(),
+            code: code.to_string(),
+            template: message_template(message),
+            stmt_kind: statement_kind(sql),
+        }
+    }
+
+    /// The signature as one string, the key findings are stored under.
+    pub fn key(&self) -> String {
+        format!("{}|{}|{}|{}", self.kind, self.code, self.template, self.stmt_kind)
+    }
+}
+
+/// Replaces the parts of a message that vary between occurrences of the same bug with `?`:
+/// numbers, quoted text, qualified names and object names.
+pub fn message_template(message: &str) -> String {
+    let mut words: Vec<String> = vec![];
+    let mut after_keyword = false;
+    for word in message.split_whitespace() {
+        let name = word.trim_end_matches([':', ',', ';']);
+        let suffix = &word[name.len()..];
+        let variable = after_keyword
+            || name.contains(|c: char| c.is_ascii_digit() || matches!(c, '\'' | '"' | '.' | '[' | '`'));
+        words.push(if variable { format!("?{}", suffix) } else { word.to_string() });
+        after_keyword = NAME_KEYWORDS.contains(&name.to_lowercase().as_str());
+    }
+    words.join(" ")
+}
+
+/// `SELECT`, `INSERT`, ... or two words for `CREATE`, `DROP` and `ALTER`, e.g. `DROP INDEX`.
+pub fn statement_kind(sql: &str) -> String {
+    let words: Vec<String> = sql
+        .split_whitespace()
+        .take(4)
+        .map(|word| word.trim_end_matches([';', '(']).to_uppercase())
+        .collect();
+    match words.first().map(String::as_str) {
+        None => String::new(),
+        Some("WITH") => "SELECT".to_string(),
+        Some(verb @ ("CREATE" | "DROP" | "ALTER")) => {
+            let object = words[1..]
+                .iter()
+                .find(|word| !matches!(word.as_str(), "UNIQUE" | "TEMP" | "TEMPORARY" | "VIRTUAL"));
+            match object {
+                Some(object) => format!("{} {}", verb, object),
+                None => verb.to_string(),
+            }
+        }
+        Some(verb) => verb.to_string(),
+    }
+}
+
+/// A deduplicated finding as stored.
+#[derive(Serialize, Debug, Clone)]
+pub struct StoredFinding {
+    pub signature: String,
+    pub kind: String,
+    pub code: String,
+    pub template: String,
+    pub stmt_kind: String,
+    /// How many times it was found
+    pub count: u64,
+    pub first_seed: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub sql: String,
+    pub detail: String,
+    /// The shortest statement sequence recorded for it
+    pub reproducer: Vec<String>,
+    pub schema: Vec<String>,
+}
+
+pub struct FindingStore {
+    conn: Connection,
+}
+
+impl FindingStore {
+    /// Opens or creates the store at `path`; several processes may have it open at once.
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::migrated(conn)
+    }
+
+    fn migrated(mut conn: Connection) -> rusqlite::Result<Self> {
+        // Executors open the store at the same time, so the version is read under the write lock
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let applied: usize = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", version + 1)?;
+        }
+        tx.commit()?;
+        Ok(Self { conn })
+    }
+
+    /// Stores `finding`, or counts it if its signature is already stored; a shorter history
+    /// replaces the stored reproducer. Returns whether the signature is new.
+    pub fn record(&self, finding: &Finding) -> rusqlite::Result<bool> {
+        let signature = finding.signature();
+        let key = signature.key();
+        let new = !self.contains(&key)?;
+        let to_json = |statements: &[String]| serde_json::to_string(statements).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "INSERT INTO findings (signature, kind, code, template, stmt_kind, count, first_seed, first_seen, last_seen,
+                                   sql, detail, reproducer, schema)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT (signature) DO UPDATE SET
+                count = count + 1,
+                last_seen = excluded.last_seen,
+                reproducer = CASE WHEN json_array_length(excluded.reproducer) < json_array_length(reproducer)
+                                  THEN excluded.reproducer ELSE reproducer END",
+            params![
+                key,
+                signature.kind,
+                signature.code,
+                signature.template,
+                signature.stmt_kind,
+                finding.seed as i64,
+                finding.timestamp,
+                finding.sql,
+                finding.detail,
+                to_json(&finding.history),
+                to_json(&finding.schema),
+            ],
+        )?;
+        Ok(new)
+    }
+
+    /// Counts another occurrence of an already stored signature.
+    pub fn bump(&self, key: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE findings SET count = count + 1, last_seen = ?2 WHERE signature = ?1",
+            params![key, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn contains(&self, key: &str) -> rusqlite::Result<bool> {
+        self.conn
+            .query_row("SELECT 1 FROM findings WHERE signature = ?1", [key], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    /// Every stored finding, most frequent first.
+    pub fn list(&self) -> rusqlite::Result<Vec<StoredFinding>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT signature, kind, code, template, stmt_kind, count, first_seed, first_seen, last_seen,
+                    sql, detail, reproducer, schema
+             FROM findings ORDER BY count DESC, first_seen",
+        )?;
+        let from_json = |json: String| serde_json::from_str(&json).unwrap_or_default();
+        stmt.query_map([], |row| {
+            Ok(StoredFinding {
+                signature: row.get(0)?,
+                kind: row.get(1)?,
+                code: row.get(2)?,
+                template: row.get(3)?,
+                stmt_kind: row.get(4)?,
+                count: row.get::<_, i64>(5)? as u64,
+                first_seed: row.get::<_, i64>(6)? as u64,
+                first_seen: row.get(7)?,
+                last_seen: row.get(8)?,
+                sql: row.get(9)?,
+                detail: row.get(10)?,
+                reproducer: from_json(row.get(11)?),
+                schema: from_json(row.get(12)?),
+            })
+        })?
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_ignore_names_and_numbers() {
+        let a = Signature::new("error", "UPDATE tbl_42 SET col2 = 1;", "Unknown: no such column: tbl_42.rowid");
+        let b = Signature::new("error", "update t SET c = 2", "Unknown: no such column: t.rowid");
+        assert_eq!(a, b);
+        assert_eq!(a.template, "no such column: ?");
+        assert_eq!(
+            message_template("error in view view_31: no such table: main.view_16"),
+            "error in view ?: no such table: ?"
+        );
+        assert_eq!(statement_kind("CREATE UNIQUE INDEX i ON t (a);"), "CREATE INDEX");
+        assert_ne!(a, Signature::new("error", "DELETE FROM t;", "Unknown: no such column: t.rowid"));
+        assert_eq!(
+            Signature::new("tlp", "SELECT 1", "TLP multiset mismatch: query returned 3 rows, partitions returned 2 rows").template,
+            "TLP multiset mismatch"
+        );
+
+        let crash = |location: &str, output: &str| {
+            let detail = format!("exited with code 101: {}\n{}", location, output);
+            Signature::new("crash", "", &detail)
+        };
+        assert_eq!(crash("src/a.rs:10:5", "seed 1"), crash("src/a.rs:10:5", "seed 2"));
+        assert_ne!(crash("src/a.rs:10:5", "seed 1"), crash("src/b.rs:20:9", "seed 1"));
+    }
+
+    #[test]
+    fn test_store_counts_repeats_and_keeps_shortest_reproducer() {
+        let store = FindingStore::open(Path::new(":memory:")).unwrap();
+        let long = Finding::new(
+            "error",
+            3,
+            "SELECT x FROM t;",
+            "Unknown: no such column: x".to_string(),
+            vec!["CREATE TABLE t (a);".to_string(), "INSERT INTO t VALUES (1);".to_string(), "SELECT x FROM t;".to_string()],
+        );
+        let short = Finding::new(
+            "error",
+            9,
+            "SELECT y FROM t;",
+            "Unknown: no such column: y".to_string(),
+            vec!["SELECT y FROM t;".to_string()],
+        );
+        assert!(store.record(&long).unwrap());
+        assert!(!store.record(&short).unwrap());
+        store.bump(&long.signature().key()).unwrap();
+
+        let stored = store.list().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].count, 3);
+        assert_eq!(stored[0].first_seed, 3);
+        assert_eq!(stored[0].reproducer, vec!["SELECT y FROM t;"]);
+    }
+
+    #[test]
+    fn test_migrations_adopt_unversioned_store_and_apply_once() {
+        // A store from before versioning: the table exists but user_version is 0
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        let version = |store: &FindingStore| -> usize {
+            store.conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+        };
+        let store = FindingStore::migrated(conn).unwrap();
+        assert_eq!(version(&store), MIGRATIONS.len());
+        let store = FindingStore::migrated(store.conn).unwrap();
+        assert_eq!(version(&store), MIGRATIONS.len());
+        assert!(store.list().unwrap().is_empty());
+    }
+}
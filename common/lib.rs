@@ -1,3 +1,4 @@
+pub mod findings;
 pub mod logger;
 pub mod profile;
 pub mod rand_by_seed;
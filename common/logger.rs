@@ -4,9 +4,18 @@ use std::path::Path;
 use std::time::SystemTime;
 
 pub fn init() {
+    init_with_console(std::io::stdout());
+}
+
+/// Like [`init`], but console output goes to stderr, leaving stdout for a command's own output.
+pub fn init_stderr() {
+    init_with_console(std::io::stderr());
+}
+
+fn init_with_console(console: impl Into<fern::Output>) {
     fern::Dispatch::new()
         .level(log::LevelFilter::Info)
-        .chain(std::io::stdout())
+        .chain(console)
         .format(|out, message, record| {
             out.finish(format_args!(
                 "[{}][{}][{}][{}] {}",
@@ -2,8 +2,9 @@ use log::info;
 use serde::{Deserialize, Serialize};
 use sqlsmith_rs_drivers::DRIVER_KIND;
 use std::fs;
+use std::path::Path;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Profile {
     pub driver: Option<DRIVER_KIND>,
     pub count: Option<usize>,
@@ -12,6 +13,28 @@ pub struct Profile {
     pub stmt_prob: Option<StmtProb>,
     pub debug: Option<DebugOptions>,
     pub seed: Option<u64>, // Added seed field
+    pub differential: Option<bool>, // Run SQLite and Limbo side by side and compare outcomes
+    pub subquery_depth: Option<usize>, // Max nesting of subqueries, CTEs and derived tables; oracle queries never nest
+    pub oracle: Option<OracleKind>, // Logic bug oracle checked against generated SELECTs
+    pub plan_guided: Option<bool>, // Favour SELECTs and schemas that produce unseen query plans
+    pub reduce: Option<bool>, // Reduce failing statement sequences before recording them
+    pub record: Option<bool>, // Write a replay log per executor thread to replays/
+    pub executor_timeout: Option<u64>, // Wall-clock seconds before an executor is killed as hung
+    pub restart_on_crash: Option<bool>, // Start a crashed or hung executor again with the next seed
+}
+
+/// Logic bug oracles, which rewrite a generated query into equivalent ones and compare results.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OracleKind {
+    /// Ternary logic partitioning: `WHERE p`, `WHERE NOT p` and `WHERE p IS NULL` cover the query
+    Tlp,
+    /// Non-optimizing reference engine construction: counting `p` per row instead of filtering
+    NoRec,
+    /// Pivoted query synthesis: a predicate rectified to hold on a random row must return that row
+    Pqs,
+    /// Optimization toggling: a query must return the same rows with SQLite's optimizer disabled
+    NoOpt,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)] // 添加 Clone
@@ -31,6 +54,46 @@ pub struct StmtProb {
     pub CREATE_TRIGGER: u64,
     pub DROP_TRIGGER: u64, // 新增 DropTrigger 字段
     pub DATE_FUNC: u64, // 新增 DATE_FUNC 字段
+    // Schema changes; missing from older profiles, where they default to 0 (disabled)
+    #[serde(default)]
+    pub CREATE_TABLE: u64,
+    #[serde(default)]
+    pub CREATE_INDEX: u64,
+    #[serde(default)]
+    pub CREATE_VIEW: u64,
+    #[serde(default)]
+    pub ALTER_TABLE: u64,
+    #[serde(default)]
+    pub DROP_TABLE: u64,
+    #[serde(default)]
+    pub DROP_INDEX: u64,
+    #[serde(default)]
+    pub DROP_VIEW: u64,
+}
+
+impl StmtProb {
+    /// The weight of a statement kind by its field name, e.g. `SELECT`.
+    pub fn weight_mut(&mut self, kind: &str) -> Option<&mut u64> {
+        Some(match kind {
+            "DELETE" => &mut self.DELETE,
+            "SELECT" => &mut self.SELECT,
+            "INSERT" => &mut self.INSERT,
+            "UPDATE" => &mut self.UPDATE,
+            "VACUUM" => &mut self.VACUUM,
+            "PRAGMA" => &mut self.PRAGMA,
+            "CREATE_TRIGGER" => &mut self.CREATE_TRIGGER,
+            "DROP_TRIGGER" => &mut self.DROP_TRIGGER,
+            "DATE_FUNC" => &mut self.DATE_FUNC,
+            "CREATE_TABLE" => &mut self.CREATE_TABLE,
+            "CREATE_INDEX" => &mut self.CREATE_INDEX,
+            "CREATE_VIEW" => &mut self.CREATE_VIEW,
+            "ALTER_TABLE" => &mut self.ALTER_TABLE,
+            "DROP_TABLE" => &mut self.DROP_TABLE,
+            "DROP_INDEX" => &mut self.DROP_INDEX,
+            "DROP_VIEW" => &mut self.DROP_VIEW,
+            _ => return None,
+        })
+    }
 }
 
 pub fn read_profile() -> Profile {
@@ -55,12 +118,21 @@ pub fn read_profile() -> Profile {
         CREATE_TRIGGER: 10,
         DROP_TRIGGER: 10, // 新增 DropTrigger 默认值
         DATE_FUNC: 20, // 新增 DATE_FUNC 默认值
+        CREATE_TABLE: 5,
+        CREATE_INDEX: 10,
+        CREATE_VIEW: 5,
+        ALTER_TABLE: 5,
+        DROP_TABLE: 2,
+        DROP_INDEX: 5,
+        DROP_VIEW: 3,
     });
     let debug = Some(DebugOptions {
         show_success_sql: false,
         show_failed_sql: true,
     });
     let seed = Some(0);
+    let differential = Some(false);
+    let subquery_depth = Some(2);
 
     let profile = Profile {
         driver,
@@ -70,6 +142,14 @@ pub fn read_profile() -> Profile {
         stmt_prob,
         debug,
         seed,
+        differential,
+        subquery_depth,
+        oracle: None,
+        plan_guided: None,
+        reduce: None,
+        record: None,
+        executor_timeout: None,
+        restart_on_crash: None,
     };
 
     if let Ok(json_str) = serde_json::to_string_pretty(&profile) {
@@ -81,6 +161,12 @@ pub fn read_profile() -> Profile {
     profile
 }
 
+/// Reads the profile at `path`; unlike [`read_profile`], a missing or invalid file is an error.
+pub fn read_profile_from(path: &Path) -> Result<Profile, std::io::Error> {
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
 pub fn write_profile(profile: &Profile) -> Result<(), std::io::Error> {
     let json_str = serde_json::to_string_pretty(profile)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
@@ -107,6 +193,30 @@ impl Profile {
         if let Some(seed) = self.seed {
             items.push(format!("seed={}", seed)); // Added seed to print
         }
+        if let Some(differential) = self.differential {
+            items.push(format!("differential={}", differential));
+        }
+        if let Some(subquery_depth) = self.subquery_depth {
+            items.push(format!("subquery_depth={}", subquery_depth));
+        }
+        if let Some(oracle) = self.oracle {
+            items.push(format!("oracle={:?}", oracle));
+        }
+        if let Some(plan_guided) = self.plan_guided {
+            items.push(format!("plan_guided={}", plan_guided));
+        }
+        if let Some(reduce) = self.reduce {
+            items.push(format!("reduce={}", reduce));
+        }
+        if let Some(record) = self.record {
+            items.push(format!("record={}", record));
+        }
+        if let Some(executor_timeout) = self.executor_timeout {
+            items.push(format!("executor_timeout={}s", executor_timeout));
+        }
+        if let Some(restart_on_crash) = self.restart_on_crash {
+            items.push(format!("restart_on_crash={}", restart_on_crash));
+        }
         if let Some(stmt_prob) = &self.stmt_prob {
             items.push(format!("SELECT={}", stmt_prob.SELECT));
             items.push(format!("INSERT={}", stmt_prob.INSERT));
@@ -117,6 +227,13 @@ impl Profile {
             items.push(format!("CREATE_TRIGGER={}", stmt_prob.CREATE_TRIGGER));
             items.push(format!("DROP_TRIGGER={}", stmt_prob.DROP_TRIGGER)); // 新增 DropTrigger 打印
             items.push(format!("DATE_FUNC={}", stmt_prob.DATE_FUNC)); // 新增 DATE_FUNC 打印
+            items.push(format!("CREATE_TABLE={}", stmt_prob.CREATE_TABLE));
+            items.push(format!("CREATE_INDEX={}", stmt_prob.CREATE_INDEX));
+            items.push(format!("CREATE_VIEW={}", stmt_prob.CREATE_VIEW));
+            items.push(format!("ALTER_TABLE={}", stmt_prob.ALTER_TABLE));
+            items.push(format!("DROP_TABLE={}", stmt_prob.DROP_TABLE));
+            items.push(format!("DROP_INDEX={}", stmt_prob.DROP_INDEX));
+            items.push(format!("DROP_VIEW={}", stmt_prob.DROP_VIEW));
         }
         if let Some(debug) = &self.debug {
             items.push(format!("show_success_sql={}", debug.show_success_sql));
@@ -4,33 +4,142 @@ use serde::{Deserialize, Serialize};
 pub mod limbo_in_mem;
 pub mod sqlite_in_mem; // <-- 添加这一行
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DRIVER_KIND {
     SQLITE_IN_MEM,
     LIMBO_IN_MEM, // 新增 LIMBO 类型
 }
 
-pub trait DatabaseDriver {
-    /// The associated type for the database connection object.
-    type Connection;
+/// A single column value of a result row, independent of the driver that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// Name, declared columns and primary key of a table, as reported by the driver.
+#[derive(Debug, Clone)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<(String, String)>, // (列名, 类型)
+    pub primary_key: Vec<String>,
+    /// Declared WITHOUT ROWID, so it has no `rowid` column; always false for views
+    pub without_rowid: bool,
+}
 
+/// Driver-agnostic database handle; object safe so engines and generators can use `&dyn DatabaseDriver`.
+pub trait DatabaseDriver {
+    fn kind(&self) -> DRIVER_KIND;
     fn exec(&self, sql: &str) -> Result<usize>;
     fn query(&self, sql: &str) -> Result<usize>;
-    fn get_connection(&self) -> &Self::Connection;
-    fn get_connection_mut(&mut self) -> &mut Self::Connection;
+    /// Runs `sql` and returns every result row in the order produced by the engine.
+    fn query_rows(&self, sql: &str) -> Result<Vec<Vec<SqlValue>>>;
+
+    /// Turns the query optimizer's optional rewrites on or off for statements prepared
+    /// afterwards. Only engines with such a switch support it.
+    fn set_optimizations(&self, enabled: bool) -> Result<()> {
+        anyhow::bail!("{:?} cannot toggle optimizations (enabled: {})", self.kind(), enabled)
+    }
+
+    /// Lists user tables with their columns, read through `sqlite_schema` and `PRAGMA table_info`.
+    fn get_tables(&self) -> Result<Vec<TableSchema>> {
+        let rows = self.query_rows("SELECT name, sql FROM sqlite_schema WHERE type='table' AND name NOT LIKE 'sqlite_%';")?;
+        rows.into_iter()
+            .filter_map(|row| match row.as_slice() {
+                [SqlValue::Text(name), sql] => Some((name.clone(), sql.clone())),
+                _ => None,
+            })
+            .map(|(name, sql)| {
+                let mut table = self.get_relation_schema(name)?;
+                table.without_rowid = matches!(sql, SqlValue::Text(sql) if declares_without_rowid(&sql));
+                Ok(table)
+            })
+            .collect()
+    }
+
+    /// Lists views with their columns. Views that no longer resolve, e.g. because a table
+    /// they read was dropped, are skipped.
+    fn get_views(&self) -> Result<Vec<TableSchema>> {
+        Ok(self
+            .get_object_names("view")?
+            .into_iter()
+            .filter_map(|name| self.get_relation_schema(name).ok())
+            .collect())
+    }
+
+    /// Names of the user objects of `object_type` ("table", "index", "view" or "trigger"),
+    /// leaving out SQLite's internal ones such as automatic indexes.
+    fn get_object_names(&self, object_type: &str) -> Result<Vec<String>> {
+        let rows = self.query_rows(&format!(
+            "SELECT name FROM sqlite_schema WHERE type='{}' AND name NOT LIKE 'sqlite_%';",
+            object_type
+        ))?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| match row.into_iter().next() {
+                Some(SqlValue::Text(name)) => Some(name),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Columns and primary key of the table or view `name`.
+    fn get_relation_schema(&self, name: String) -> Result<TableSchema> {
+        let mut columns = Vec::new();
+        let mut primary_key = Vec::new();
+        // table_info columns: cid, name, type, notnull, dflt_value, pk
+        for col in self.query_rows(&format!("PRAGMA table_info({});", name))? {
+            let text = |idx: usize| match col.get(idx) {
+                Some(SqlValue::Text(t)) => t.clone(),
+                _ => String::new(),
+            };
+            let (col_name, col_type) = (text(1), text(2));
+            if let Some(SqlValue::Integer(pk)) = col.get(5)
+                && *pk > 0
+            {
+                primary_key.push(col_name.clone());
+            }
+            columns.push((col_name, col_type));
+        }
+        Ok(TableSchema {
+            name,
+            columns,
+            primary_key,
+            without_rowid: false,
+        })
+    }
+}
+
+/// Whether the CREATE TABLE statement `sql` ends in the WITHOUT ROWID table option.
+fn declares_without_rowid(sql: &str) -> bool {
+    let words: Vec<String> = sql.split_whitespace().map(|word| word.to_uppercase()).collect();
+    words.windows(2).any(|pair| pair[0].ends_with("WITHOUT") && pair[1].trim_end_matches([',', ';']) == "ROWID")
+}
+
+/// Returns true if `sql` is expected to produce a result set rather than an affected-row count.
+pub fn returns_rows(sql: &str) -> bool {
+    let lower_sql = sql.trim_start().to_lowercase();
+    lower_sql.starts_with("select")
+        || lower_sql.starts_with("with")
+        || lower_sql.starts_with("values")
+        || lower_sql.starts_with("pragma")
+        || lower_sql.contains(" returning ")
 }
 
 /// 通用接口：根据 DRIVER_KIND 创建驱动和连接
-pub fn new_conn(
-    kind: DRIVER_KIND,
-) -> Result<Box<dyn DatabaseDriver<Connection = rusqlite::Connection>>> {
+pub fn new_conn(kind: DRIVER_KIND) -> Result<Box<dyn DatabaseDriver>> {
     match kind {
         DRIVER_KIND::SQLITE_IN_MEM => {
             let driver = sqlite_in_mem::SqliteDriver::new()?;
             Ok(Box::new(driver))
         }
         DRIVER_KIND::LIMBO_IN_MEM => {
-            anyhow::bail!("LIMBO driver is not implemented")
+            let rt = tokio::runtime::Runtime::new()?;
+            let driver = rt.block_on(limbo_in_mem::LimboDriver::new())?;
+            Ok(Box::new(driver))
         }
     }
 }
@@ -1,6 +1,6 @@
-use super::DatabaseDriver;
+use super::{DRIVER_KIND, DatabaseDriver, SqlValue, returns_rows};
 use anyhow::Result;
-use limbo::{Builder, Connection};
+use limbo::{Builder, Connection, Value};
 use log::info;
 use std::fs;
 use std::path::Path;
@@ -11,43 +11,78 @@ pub struct LimboDriver {
 
 impl LimboDriver {
     pub async fn new() -> Result<Self> {
+        Self::with_schema(Path::new("assets/limbo/tpcc-create-table.sql")).await
+    }
+
+    /// Creates an in-memory Limbo database initialized from the given schema file.
+    pub async fn with_schema(sql_file_path: &Path) -> Result<Self> {
         let db = Builder::new_local(":memory:").build().await?;
         let conn = db.connect()?;
         let driver = Self { conn };
 
         // Initialize the database
         info!("Initializing Limbo database...");
-        driver.init().await?;
+        driver.init(sql_file_path).await?;
 
         // TODO: Add verify logic similar to SQLite if needed
 
         Ok(driver)
     }
 
-    async fn init(&self) -> Result<()> {
-        info!("(Limbo) Executing init SQL from assets/limbo/tpcc-create-table.sql...");
-        let sql_file_path = Path::new("assets/limbo/tpcc-create-table.sql");
+    async fn init(&self, sql_file_path: &Path) -> Result<()> {
+        info!("(Limbo) Executing init SQL from {:?}...", sql_file_path);
         let sql_content = fs::read_to_string(sql_file_path)
             .map_err(|e| anyhow::anyhow!("Failed to read SQL file: {:?}: {}", sql_file_path, e))?;
 
-        self.conn
-            .execute(&sql_content, ())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to execute Limbo init SQL batch: {}", e))?;
+        // Limbo only runs the first statement of a batch, so feed them one by one
+        for stmt in split_statements(&sql_content) {
+            if stmt.to_uppercase().starts_with("PRAGMA") {
+                info!("(Limbo) Skipping unsupported init statement: {}", stmt);
+                continue;
+            }
+            self.conn
+                .execute(&stmt, ())
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to execute Limbo init SQL: {}: {}", stmt, e))?;
+        }
         info!("(Limbo) TPC-C tables created successfully.");
         Ok(())
     }
 }
 
+/// Splits a schema script into single statements, dropping `--` comments.
+fn split_statements(sql: &str) -> Vec<String> {
+    let without_comments = sql
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    without_comments
+        .split(';')
+        .map(str::trim)
+        .filter(|stmt| !stmt.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 impl DatabaseDriver for LimboDriver {
-    // Use actual connection type instead of `()`
-    type Connection = limbo::Connection;
+    fn kind(&self) -> DRIVER_KIND {
+        DRIVER_KIND::LIMBO_IN_MEM
+    }
 
     fn exec(&self, sql: &str) -> Result<usize> {
+        if returns_rows(sql) {
+            return self.query(sql);
+        }
         let rt = tokio::runtime::Runtime::new()?;
         rt.block_on(async {
-            let result = self.conn.execute(sql, ()).await?;
-            Ok(result as usize)
+            // `execute` reports a step status rather than the number of changed rows
+            self.conn.execute(sql, ()).await?;
+            let mut rows = self.conn.query("SELECT changes()", ()).await?;
+            match rows.next().await? {
+                Some(row) => Ok(row.get_value(0)?.as_integer().copied().unwrap_or(0) as usize),
+                None => Ok(0),
+            }
         })
     }
 
@@ -63,11 +98,25 @@ impl DatabaseDriver for LimboDriver {
         })
     }
 
-    // Implement connection accessors with the correct type
-    fn get_connection(&self) -> &Self::Connection {
-        &self.conn
-    }
-    fn get_connection_mut(&mut self) -> &mut Self::Connection {
-        &mut self.conn
+    fn query_rows(&self, sql: &str) -> Result<Vec<Vec<SqlValue>>> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(async {
+            let mut rows = self.conn.query(sql, ()).await?;
+            let mut result = Vec::new();
+            while let Some(row) = rows.next().await? {
+                let mut values = Vec::with_capacity(row.column_count());
+                for i in 0..row.column_count() {
+                    values.push(match row.get_value(i)? {
+                        Value::Null => SqlValue::Null,
+                        Value::Integer(v) => SqlValue::Integer(v),
+                        Value::Real(v) => SqlValue::Real(v),
+                        Value::Text(v) => SqlValue::Text(v),
+                        Value::Blob(v) => SqlValue::Blob(v),
+                    });
+                }
+                result.push(values);
+            }
+            Ok(result)
+        })
     }
 }
@@ -1,9 +1,10 @@
 // src/drivers/sqlite.rs
 
-use super::DatabaseDriver;
+use super::{DRIVER_KIND, DatabaseDriver, SqlValue, returns_rows};
 use anyhow::Result;
-use log::info;
+use log::debug;
 use rusqlite::Connection;
+use rusqlite::types::ValueRef;
 use std::fs;
 use std::path::Path;
 
@@ -18,8 +19,8 @@ impl SqliteDriver {
         let conn = Connection::open_in_memory()?;
         let driver = Self { conn };
 
-        // 初始化数据库
-        info!("Initializing SQLite in-memory database...");
+        // 初始化数据库; the reducer opens one per replay, so this is logged at debug level
+        debug!("Initializing SQLite in-memory database...");
         driver.init()?;
 
         // 验证初始化结果
@@ -31,7 +32,7 @@ impl SqliteDriver {
     }
 
     fn init(&self) -> Result<()> {
-        info!("(SQLite) Executing init SQL from assets/sqlite/tpcc-create-table.sql...");
+        debug!("(SQLite) Executing init SQL from assets/sqlite/tpcc-create-table.sql...");
         let sql_file_path = Path::new("assets/sqlite/tpcc-create-table.sql");
         let sql_content = fs::read_to_string(sql_file_path)
             .map_err(|e| anyhow::anyhow!("Failed to read SQL file: {:?}: {}", sql_file_path, e))?;
@@ -39,7 +40,7 @@ impl SqliteDriver {
         self.conn
             .execute_batch(&sql_content)
             .map_err(|e| anyhow::anyhow!("Failed to execute SQLite init SQL batch: {}", e))?;
-        info!("(SQLite) TPC-C tables created successfully.");
+        debug!("(SQLite) TPC-C tables created successfully.");
         Ok(())
     }
 
@@ -80,23 +81,23 @@ impl SqliteDriver {
     }
 }
 
+impl SqliteDriver {
+    /// The underlying rusqlite handle, for SQLite-only features.
+    pub fn get_connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
 impl DatabaseDriver for SqliteDriver {
-    type Connection = Connection;
+    fn kind(&self) -> DRIVER_KIND {
+        DRIVER_KIND::SQLITE_IN_MEM
+    }
 
     fn exec(&self, sql: &str) -> Result<usize> {
-        // 检查是否为查询语句，简单通过常见关键字判断
-        let lower_sql = sql.to_lowercase();
-        if lower_sql.starts_with("select") || lower_sql.starts_with("pragma") {
-            let mut stmt = self.conn.prepare(sql)?;
-            let mut rows = stmt.query([])?;
-            let mut count = 0;
-            while let Some(_) = rows.next()? {
-                count += 1;
-            }
-            Ok(count)
-        } else {
-            Ok(self.conn.execute(sql, [])?)
+        if returns_rows(sql) {
+            return self.query(sql);
         }
+        Ok(self.conn.execute(sql, [])?)
     }
 
     fn query(&self, sql: &str) -> Result<usize> {
@@ -109,11 +110,40 @@ impl DatabaseDriver for SqliteDriver {
         Ok(count)
     }
 
-    fn get_connection(&self) -> &Self::Connection {
-        &self.conn
+    fn query_rows(&self, sql: &str) -> Result<Vec<Vec<SqlValue>>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let column_count = stmt.column_count();
+        let mut rows = stmt.query([])?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut values = Vec::with_capacity(column_count);
+            for i in 0..column_count {
+                values.push(match row.get_ref(i)? {
+                    ValueRef::Null => SqlValue::Null,
+                    ValueRef::Integer(v) => SqlValue::Integer(v),
+                    ValueRef::Real(v) => SqlValue::Real(v),
+                    ValueRef::Text(v) => SqlValue::Text(String::from_utf8_lossy(v).into_owned()),
+                    ValueRef::Blob(v) => SqlValue::Blob(v.to_vec()),
+                });
+            }
+            result.push(values);
+        }
+        Ok(result)
     }
 
-    fn get_connection_mut(&mut self) -> &mut Self::Connection {
-        &mut self.conn
+    fn set_optimizations(&self, enabled: bool) -> Result<()> {
+        // The argument is the mask of optimizations to disable; all bits set is SQLITE_AllOpts
+        let disabled: std::os::raw::c_int = if enabled { 0 } else { -1 };
+        let rc = unsafe {
+            rusqlite::ffi::sqlite3_test_control(
+                rusqlite::ffi::SQLITE_TESTCTRL_OPTIMIZATIONS,
+                self.conn.handle(),
+                disabled,
+            )
+        };
+        if rc != rusqlite::ffi::SQLITE_OK {
+            anyhow::bail!("sqlite3_test_control(SQLITE_TESTCTRL_OPTIMIZATIONS) failed with code {}", rc);
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,272 @@
+// Command line of the executor: a subcommand plus options that override profile.json. Run
+// without arguments, as the fork server does, it generates and executes statements.
+use sqlsmith_rs_common::profile::{DebugOptions, OracleKind, Profile};
+use sqlsmith_rs_drivers::DRIVER_KIND;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Generate and execute statements
+    Run,
+    /// Re-execute a replay log and compare outcomes
+    Replay(String),
+    /// Reduce a replay log to the statements its last error needs
+    Reduce(String),
+    /// Print generated statements without executing them
+    Gen,
+}
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("run", "Generate and execute statements (default)"),
+    ("replay <FILE>", "Re-execute a replay log and check every outcome matches"),
+    ("reduce <FILE>", "Reduce a replay log to the statements its last error needs"),
+    ("gen", "Print generated statements without executing them"),
+];
+
+/// Long options as (name, value, help); a `[=BOOL]` value may be left out to mean true.
+const OPTIONS: &[(&str, &str, &str)] = &[
+    ("profile", "<PATH>", "Profile to read instead of ./profile.json"),
+    ("driver", "<sqlite|limbo>", "Database driver"),
+    ("seed", "<N>", "Base seed; thread n uses seed + n. Overrides EXEC_PARAM_SEED"),
+    ("count", "<N>", "Statements per thread"),
+    ("threads", "<N>", "Threads per executor"),
+    ("executors", "<N>", "Executor processes started by the server"),
+    ("executor-timeout", "<SECS>", "Wall-clock seconds before the server kills an executor as hung"),
+    ("restart-on-crash", "[=BOOL]", "Have the server start a crashed or hung executor again with the next seed"),
+    ("subquery-depth", "<N>", "Max nesting of subqueries, CTEs and derived tables"),
+    ("oracle", "<tlp|norec|pqs|noopt|none>", "Logic bug oracle checked against generated SELECTs"),
+    ("differential", "[=BOOL]", "Run SQLite and Limbo side by side and compare outcomes"),
+    ("plan-guided", "[=BOOL]", "Favour SELECTs and schemas that produce unseen query plans"),
+    ("reduce", "[=BOOL]", "Reduce failing statement sequences before recording them"),
+    ("record", "[=BOOL]", "Write a replay log per thread to replays/"),
+    ("show-success-sql", "[=BOOL]", "Log every statement that succeeds"),
+    ("show-failed-sql", "[=BOOL]", "Log every statement that fails unexpectedly"),
+    ("prob", "<KIND=N>", "Weight of a statement kind, e.g. SELECT=0; repeatable"),
+];
+
+/// A profile field set on the command line.
+#[derive(Debug, Clone, PartialEq)]
+enum Override {
+    Driver(DRIVER_KIND),
+    Seed(u64),
+    Count(usize),
+    Threads(usize),
+    Executors(usize),
+    ExecutorTimeout(u64),
+    RestartOnCrash(bool),
+    SubqueryDepth(usize),
+    Oracle(Option<OracleKind>),
+    Differential(bool),
+    PlanGuided(bool),
+    Reduce(bool),
+    Record(bool),
+    ShowSuccessSql(bool),
+    ShowFailedSql(bool),
+    Prob(String, u64),
+}
+
+#[derive(Debug)]
+pub struct Cli {
+    pub command: Command,
+    /// Profile to read; profile.json in the working directory when not given
+    pub profile: Option<String>,
+    overrides: Vec<Override>,
+}
+
+/// What the command line asks for.
+#[derive(Debug)]
+pub enum Parsed {
+    Cli(Cli),
+    Help,
+    Version,
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Parsed, String> {
+    let mut args = args.into_iter();
+    let mut cli = Cli {
+        command: Command::Run,
+        profile: None,
+        overrides: vec![],
+    };
+    let mut positional = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "-V" | "--version" => return Ok(Parsed::Version),
+            _ => {}
+        }
+        let Some(option) = arg.strip_prefix("--") else {
+            positional.push(arg);
+            continue;
+        };
+        let (name, inline) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (option, None),
+        };
+        let Some((_, hint, _)) = OPTIONS.iter().find(|(n, ..)| *n == name) else {
+            return Err(format!("unknown option --{}", name));
+        };
+        let value = match inline {
+            Some(value) => value,
+            None if hint.starts_with('[') => "true".to_string(),
+            None => args.next().ok_or_else(|| format!("--{} needs a value {}", name, hint))?,
+        };
+        if name == "profile" {
+            cli.profile = Some(value);
+        } else {
+            cli.overrides.push(parse_override(name, &value)?);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    cli.command = match positional.next().as_deref() {
+        None | Some("run") => Command::Run,
+        Some("gen") => Command::Gen,
+        Some(command @ ("replay" | "reduce")) => {
+            let file = positional.next().ok_or_else(|| format!("{} needs a FILE", command))?;
+            if command == "replay" { Command::Replay(file) } else { Command::Reduce(file) }
+        }
+        Some(other) => return Err(format!("unknown command {}", other)),
+    };
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument {}", extra));
+    }
+    Ok(Parsed::Cli(cli))
+}
+
+fn parse_override(name: &str, value: &str) -> Result<Override, String> {
+    let number = || value.parse::<usize>().map_err(|_| format!("--{} expects a number, got {}", name, value));
+    let flag = || value.parse::<bool>().map_err(|_| format!("--{} expects true or false, got {}", name, value));
+    Ok(match name {
+        "driver" => Override::Driver(match value.to_lowercase().as_str() {
+            "sqlite" | "sqlite_in_mem" => DRIVER_KIND::SQLITE_IN_MEM,
+            "limbo" | "limbo_in_mem" => DRIVER_KIND::LIMBO_IN_MEM,
+            _ => return Err(format!("unknown driver {}", value)),
+        }),
+        "seed" => Override::Seed(value.parse().map_err(|_| format!("--seed expects a number, got {}", value))?),
+        "count" => Override::Count(number()?),
+        "threads" => Override::Threads(number()?),
+        "executors" => Override::Executors(number()?),
+        "executor-timeout" => Override::ExecutorTimeout(number()? as u64),
+        "restart-on-crash" => Override::RestartOnCrash(flag()?),
+        "subquery-depth" => Override::SubqueryDepth(number()?),
+        "oracle" => Override::Oracle(match value.to_lowercase().as_str() {
+            "none" => None,
+            "tlp" => Some(OracleKind::Tlp),
+            "norec" => Some(OracleKind::NoRec),
+            "pqs" => Some(OracleKind::Pqs),
+            "noopt" => Some(OracleKind::NoOpt),
+            _ => return Err(format!("unknown oracle {}", value)),
+        }),
+        "differential" => Override::Differential(flag()?),
+        "plan-guided" => Override::PlanGuided(flag()?),
+        "reduce" => Override::Reduce(flag()?),
+        "record" => Override::Record(flag()?),
+        "show-success-sql" => Override::ShowSuccessSql(flag()?),
+        "show-failed-sql" => Override::ShowFailedSql(flag()?),
+        "prob" => {
+            let (kind, weight) = value.split_once('=').ok_or_else(|| format!("--prob expects KIND=N, got {}", value))?;
+            let weight = weight.parse().map_err(|_| format!("--prob expects a number after =, got {}", weight))?;
+            Override::Prob(kind.to_uppercase(), weight)
+        }
+        _ => unreachable!("option --{} is listed but not handled", name),
+    })
+}
+
+impl Cli {
+    /// Writes the command line's settings over those read from the profile.
+    pub fn apply(&self, profile: &mut Profile) -> Result<(), String> {
+        for o in &self.overrides {
+            match o {
+                Override::Driver(driver) => profile.driver = Some(*driver),
+                Override::Seed(seed) => profile.seed = Some(*seed),
+                Override::Count(count) => profile.count = Some(*count),
+                Override::Threads(threads) => profile.thread_per_exec = Some(*threads),
+                Override::Executors(executors) => profile.executor_count = Some(*executors),
+                Override::ExecutorTimeout(secs) => profile.executor_timeout = Some(*secs),
+                Override::RestartOnCrash(on) => profile.restart_on_crash = Some(*on),
+                Override::SubqueryDepth(depth) => profile.subquery_depth = Some(*depth),
+                Override::Oracle(oracle) => profile.oracle = *oracle,
+                Override::Differential(on) => profile.differential = Some(*on),
+                Override::PlanGuided(on) => profile.plan_guided = Some(*on),
+                Override::Reduce(on) => profile.reduce = Some(*on),
+                Override::Record(on) => profile.record = Some(*on),
+                Override::ShowSuccessSql(on) => debug_options(profile).show_success_sql = *on,
+                Override::ShowFailedSql(on) => debug_options(profile).show_failed_sql = *on,
+                Override::Prob(kind, weight) => {
+                    let prob = profile
+                        .stmt_prob
+                        .as_mut()
+                        .ok_or("--prob needs a profile with stmt_prob to change")?;
+                    *prob.weight_mut(kind).ok_or_else(|| format!("unknown statement kind {}", kind))? = *weight;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The seed given with `--seed`, which wins over `EXEC_PARAM_SEED`.
+    pub fn seed(&self) -> Option<u64> {
+        self.overrides.iter().rev().find_map(|o| match o {
+            Override::Seed(seed) => Some(*seed),
+            _ => None,
+        })
+    }
+}
+
+fn debug_options(profile: &mut Profile) -> &mut DebugOptions {
+    profile.debug.get_or_insert(DebugOptions {
+        show_success_sql: false,
+        show_failed_sql: false,
+    })
+}
+
+pub fn usage() -> String {
+    let mut out = String::from("Usage: executor [OPTIONS] [COMMAND]\n\nCommands:\n");
+    for (command, help) in COMMANDS {
+        out.push_str(&format!("  {:<16} {}\n", command, help));
+    }
+    out.push_str("\nOptions:\n");
+    for (name, hint, help) in OPTIONS {
+        let option = match hint.strip_prefix('[') {
+            Some(_) => format!("--{}{}", name, hint),
+            None => format!("--{} {}", name, hint),
+        };
+        out.push_str(&format!("  {:<40} {}\n", option, help));
+    }
+    out.push_str(&format!("  {:<40} {}\n", "-h, --help", "Print this help"));
+    out.push_str(&format!("  {:<40} {}\n", "-V, --version", "Print the version"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Parsed, String> {
+        parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands_and_overrides() {
+        let Ok(Parsed::Cli(cli)) = parse_args(&["--seed", "7", "replay", "log.jsonl", "--reduce", "--record=false"]) else {
+            panic!("expected a command line");
+        };
+        assert_eq!(cli.command, Command::Replay("log.jsonl".to_string()));
+        assert_eq!(cli.seed(), Some(7));
+        assert_eq!(
+            cli.overrides,
+            vec![Override::Seed(7), Override::Reduce(true), Override::Record(false)]
+        );
+
+        let Ok(Parsed::Cli(cli)) = parse_args(&["--executor-timeout", "30", "--restart-on-crash"]) else {
+            panic!("expected a command line");
+        };
+        assert_eq!(cli.overrides, vec![Override::ExecutorTimeout(30), Override::RestartOnCrash(true)]);
+
+        assert!(matches!(parse_args(&["--version"]), Ok(Parsed::Version)));
+        assert!(parse_args(&["replay"]).is_err());
+        assert!(parse_args(&["--count"]).is_err());
+        assert!(parse_args(&["--bogus"]).is_err());
+        assert!(parse_args(&["--oracle", "xyz"]).is_err());
+    }
+}
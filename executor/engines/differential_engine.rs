@@ -0,0 +1,323 @@
+use crate::findings::Recorder;
+use crate::oracles::{row_key, same_rows};
+use log::info;
+use sqlsmith_rs_common::findings::{Finding, Signature, statement_kind};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::limbo_in_mem::LimboDriver;
+use sqlsmith_rs_drivers::sqlite_in_mem::SqliteDriver;
+use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver, SqlValue, returns_rows};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::Path;
+
+use super::generate_sql_by_prob;
+use crate::generators::GeneratorOptions;
+use crate::{reducer, replay};
+
+/// Both sides are built from the SQLite flavour of the TPC-C schema so they start identical.
+const SHARED_SCHEMA: &str = "assets/sqlite/tpcc-create-table.sql";
+
+/// Runs one statement stream against SQLite and Limbo and reports any divergence.
+pub struct DifferentialEngine {
+    pub rng: LcgRng,
+    pub run_count: usize,
+    pub thread_per_exec: usize,
+    pub stmt_prob: Option<sqlsmith_rs_common::profile::StmtProb>,
+    pub gen_options: GeneratorOptions,
+    pub debug: Option<sqlsmith_rs_common::profile::DebugOptions>,
+    /// Profile hash to start per-thread replay logs of the SQLite side with; None when not recording
+    pub record: Option<String>,
+}
+
+/// What a single driver produced for a statement.
+#[derive(Debug)]
+enum Outcome {
+    Rows(Vec<Vec<SqlValue>>),
+    Affected(usize),
+    Error(String),
+    Panic(String),
+}
+
+impl DifferentialEngine {
+    pub fn new_limbo_driver() -> anyhow::Result<LimboDriver> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(LimboDriver::with_schema(Path::new(SHARED_SCHEMA)))
+    }
+}
+
+fn run_on(driver: &dyn DatabaseDriver, sql: &str) -> Outcome {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        if returns_rows(sql) {
+            driver.query_rows(sql).map(Outcome::Rows)
+        } else {
+            driver.exec(sql).map(Outcome::Affected)
+        }
+    }));
+    match result {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(e)) => Outcome::Error(reducer::error_signature(&e)),
+        Err(payload) => Outcome::Panic(super::panic_message(&*payload)),
+    }
+}
+
+/// How a statement ended on SQLite, as a replay of it on SQLite alone sees it.
+fn replay_outcome(sqlite: &Outcome) -> replay::Outcome {
+    match sqlite {
+        Outcome::Rows(rows) => replay::Outcome::Ok { affected: rows.len() },
+        Outcome::Affected(affected) => replay::Outcome::Ok { affected: *affected },
+        Outcome::Error(error) => replay::Outcome::Error { error: error.clone() },
+        Outcome::Panic(_) => replay::Outcome::Panic,
+    }
+}
+
+/// Returns a description of the mismatch, or `None` when both drivers agree.
+fn compare(sqlite: &Outcome, limbo: &Outcome) -> Option<String> {
+    match (sqlite, limbo) {
+        (_, Outcome::Panic(msg)) => Some(format!("limbo panicked: {}", msg)),
+        (Outcome::Panic(msg), _) => Some(format!("sqlite panicked: {}", msg)),
+        (Outcome::Error(_), Outcome::Error(_)) => None,
+        (Outcome::Error(e), _) => Some(format!("sqlite failed but limbo succeeded: {}", e)),
+        (_, Outcome::Error(e)) => Some(format!("limbo failed but sqlite succeeded: {}", e)),
+        (Outcome::Affected(a), Outcome::Affected(b)) if a == b => None,
+        (Outcome::Rows(a), Outcome::Rows(b)) if same_rows(a, b) => None,
+        (Outcome::Rows(a), Outcome::Rows(b)) => Some(format!(
+            "result sets differ: sqlite {} rows, limbo {} rows; first differing row: sqlite {}, limbo {}",
+            a.len(),
+            b.len(),
+            show_row(first_unmatched(a, b)),
+            show_row(first_unmatched(b, a))
+        )),
+        (a, b) => Some(format!("outcomes differ: sqlite {:?}, limbo {:?}", a, b)),
+    }
+}
+
+/// The first row of `a` that `b` has fewer copies of, comparing rows as multisets do.
+fn first_unmatched<'a>(a: &'a [Vec<SqlValue>], b: &[Vec<SqlValue>]) -> Option<&'a Vec<SqlValue>> {
+    let mut remaining = std::collections::HashMap::new();
+    for row in b {
+        *remaining.entry(row_key(row)).or_insert(0usize) += 1;
+    }
+    a.iter().find(|row| match remaining.get_mut(&row_key(row)) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            false
+        }
+        _ => true,
+    })
+}
+
+fn show_row(row: Option<&Vec<SqlValue>>) -> String {
+    row.map_or_else(|| "none".to_string(), |row| format!("{:?}", row))
+}
+
+impl super::Engine for DifferentialEngine {
+    fn run(&mut self) {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        let (debug, prob, run_count, thread_per_exec, base_seed) = (
+            self.debug.clone(),
+            self.stmt_prob.clone(),
+            self.run_count,
+            self.thread_per_exec,
+            self.rng.get_seed()
+        );
+
+        // Shared statistics: agreeing successes, agreeing failures, and mismatches
+        let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
+            Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            Arc::new(Mutex::new(std::collections::HashMap::new()))
+        );
+
+        let start_time = std::time::Instant::now();
+        let executor_id = std::env::var("EXEC_PARAM_SEED")
+            .unwrap_or_else(|_| "unknown".to_string());
+        let progress = {
+            let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
+                Arc::clone(&success_count),
+                Arc::clone(&failed_expected_count),
+                Arc::clone(&failed_new_count),
+                Arc::clone(&stmt_type_counts)
+            );
+            let executor_id = executor_id.clone();
+            super::ProgressReporter::start(move || {
+                super::ExecutionStats::new(
+                    start_time.elapsed(),
+                    success_count.load(std::sync::atomic::Ordering::Relaxed),
+                    failed_expected_count.load(std::sync::atomic::Ordering::Relaxed),
+                    failed_new_count.load(std::sync::atomic::Ordering::Relaxed),
+                    thread_per_exec,
+                    stmt_type_counts.lock().map(|counts| counts.clone()).unwrap_or_default(),
+                    executor_id.clone(),
+                )
+            })
+        };
+        let mut handles = vec![];
+
+        for n in 0..thread_per_exec {
+            let thread_run_count = run_count;
+            let gen_options = self.gen_options.clone();
+            let (thread_seed, debug, prob) = (
+                base_seed.wrapping_add(n as u64),
+                debug.clone(),
+                prob.clone()
+            );
+            let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
+                Arc::clone(&success_count),
+                Arc::clone(&failed_expected_count),
+                Arc::clone(&failed_new_count),
+                Arc::clone(&stmt_type_counts)
+            );
+            let record = self.record.clone();
+
+            handles.push(thread::spawn(move || {
+                let mut sqlite = SqliteDriver::new().expect("Failed to create SQLite driver");
+                let mut limbo = DifferentialEngine::new_limbo_driver().expect("Failed to create Limbo driver");
+                let mut rng = LcgRng::new(thread_seed);
+                let mut local_stmt_type_counts = std::collections::HashMap::new();
+                let mut history = Vec::new();
+                let mut recorder = Recorder::open();
+                let mut replay_log = replay::start(record.as_deref(), thread_seed, DRIVER_KIND::SQLITE_IN_MEM);
+
+                for i in 0..thread_run_count {
+                    if i % super::STMT_COUNT_MERGE_INTERVAL == 0 {
+                        super::merge_stmt_type_counts(&stmt_type_counts, &mut local_stmt_type_counts);
+                    }
+                    let sql = if let Some(prob) = &prob {
+                        generate_sql_by_prob(prob, &mut rng, |kind, rng| {
+                            *local_stmt_type_counts.entry(format!("{:?}", kind)).or_insert(0) += 1;
+                            crate::generators::get_stmt_by_seed(&sqlite, rng, kind, &gen_options)
+                        })
+                    } else {
+                        "SELECT 1;".to_string()
+                    };
+                    history.push(sql.clone());
+
+                    let sqlite_outcome = run_on(&sqlite, &sql);
+                    let limbo_outcome = run_on(&limbo, &sql);
+                    if let Some(replay_log) = &mut replay_log {
+                        replay_log.record(&sql, replay_outcome(&sqlite_outcome));
+                    }
+
+                    match compare(&sqlite_outcome, &limbo_outcome) {
+                        None => {
+                            if let Outcome::Error(_) = sqlite_outcome {
+                                failed_expected_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            } else {
+                                success_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                if let Some(debug) = &debug && debug.show_success_sql {
+                                    log::info!("SQL agreed on both drivers: {}", sql);
+                                }
+                            }
+                        }
+                        Some(detail) => {
+                            failed_new_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            if let Some(debug) = &debug && debug.show_failed_sql {
+                                log::info!("Differential mismatch on SQL: {} ({})", sql, detail);
+                            }
+                            if !recorder.repeat(&Signature::new("differential", &sql, &detail)) {
+                                recorder.record(&Finding::new("differential", thread_seed, &sql, detail, history.clone()));
+                            }
+                            // Once a write went differently the databases have diverged, and a panic leaves
+                            // Limbo's connection mutex poisoned, so start over on fresh databases
+                            if statement_kind(&sql) != "SELECT" || matches!(limbo_outcome, Outcome::Panic(_)) {
+                                log::warn!("Differential mismatch changed state, recreating both databases for seed {}", thread_seed);
+                                sqlite = SqliteDriver::new().expect("Failed to create SQLite driver");
+                                limbo = DifferentialEngine::new_limbo_driver().expect("Failed to create Limbo driver");
+                                history.clear();
+                                if let Some(replay_log) = &mut replay_log {
+                                    replay_log.restart();
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Merge local statement type counts
+                super::merge_stmt_type_counts(&stmt_type_counts, &mut local_stmt_type_counts);
+            }));
+        }
+
+        for handle in handles {
+            handle.join().expect("Thread panicked");
+        }
+        drop(progress);
+
+        let elapsed = start_time.elapsed();
+        let (final_success, final_failed_exp, final_failed_new) = (
+            success_count.load(std::sync::atomic::Ordering::Relaxed),
+            failed_expected_count.load(std::sync::atomic::Ordering::Relaxed),
+            failed_new_count.load(std::sync::atomic::Ordering::Relaxed)
+        );
+
+        info!(
+            "finish differential exec in {:.2?}, agreed/both_failed/mismatched: {}/{}/{}",
+            elapsed, final_success, final_failed_exp, final_failed_new
+        );
+
+        let stmt_counts = if let Ok(stmt_type_counts) = stmt_type_counts.lock() {
+            info!("Statement type statistics: {:?}", *stmt_type_counts);
+            stmt_type_counts.clone()
+        } else {
+            std::collections::HashMap::new()
+        };
+
+        let stats = super::ExecutionStats::new(
+            elapsed,
+            final_success,
+            final_failed_exp,
+            final_failed_new,
+            thread_per_exec,
+            stmt_counts,
+            executor_id,
+        );
+
+        if let Err(e) = super::submit_stats_blocking(stats) {
+            log::warn!("Failed to submit statistics: {}", e);
+        }
+    }
+
+    /// Statements are generated against the SQLite side.
+    fn get_driver_kind(&self) -> DRIVER_KIND { DRIVER_KIND::SQLITE_IN_MEM }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_sets_compare_as_multisets() {
+        let a = vec![
+            vec![SqlValue::Integer(1), SqlValue::Text("a".to_string())],
+            vec![SqlValue::Integer(2), SqlValue::Null],
+        ];
+        let b = vec![a[1].clone(), a[0].clone()];
+        assert!(same_rows(&a, &b));
+
+        let c = vec![a[0].clone(), a[0].clone()];
+        assert!(!same_rows(&a, &c), "duplicates must be counted");
+    }
+
+    #[test]
+    fn test_compare_outcomes() {
+        let both_failed = compare(&Outcome::Error("x".into()), &Outcome::Error("y".into()));
+        assert!(both_failed.is_none());
+
+        let one_failed = compare(&Outcome::Affected(1), &Outcome::Error("y".into()));
+        assert!(one_failed.is_some());
+
+        let int_vs_real = compare(
+            &Outcome::Rows(vec![vec![SqlValue::Integer(3)]]),
+            &Outcome::Rows(vec![vec![SqlValue::Real(3.0)]]),
+        );
+        assert!(int_vs_real.is_some(), "storage class differences are reported");
+
+        let value_difference = compare(
+            &Outcome::Rows(vec![vec![SqlValue::Integer(1)], vec![SqlValue::Integer(2)]]),
+            &Outcome::Rows(vec![vec![SqlValue::Integer(2)], vec![SqlValue::Integer(5)]]),
+        )
+        .unwrap();
+        assert!(value_difference.ends_with("first differing row: sqlite [Integer(1)], limbo [Integer(5)]"));
+    }
+}
@@ -1,103 +1,220 @@
+use sqlsmith_rs_common::findings::{Finding, Signature};
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
-use sqlsmith_rs_drivers::limbo_in_mem::LimboDriver;
-use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver};
+use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver, new_conn};
 use log::info;
+use std::collections::VecDeque;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use super::generate_sql_by_prob;
+use crate::findings::Recorder;
+use crate::generators::GeneratorOptions;
+use crate::oracles;
+use crate::replay::{self, Outcome};
 
 pub struct LimboEngine {
     pub rng: LcgRng,
-    pub limbo_driver_box: Box<LimboDriver>,
     pub run_count: usize,
     pub thread_per_exec: usize,
     pub stmt_prob: Option<sqlsmith_rs_common::profile::StmtProb>,
+    pub gen_options: GeneratorOptions,
     pub debug: Option<sqlsmith_rs_common::profile::DebugOptions>,
+    /// Profile hash to start per-thread replay logs with; None when not recording
+    pub record: Option<String>,
+}
+
+/// Limbo surfaces every failure as a message string, so expected errors are matched by text.
+const IGNORABLE_ERRORS: &[&str] = &["constraint failed", "is not nullable"];
+
+fn is_expected_error(e: &anyhow::Error) -> bool {
+    let msg = e.to_string();
+    IGNORABLE_ERRORS.iter().any(|pattern| msg.contains(pattern))
+}
+
+fn new_limbo_driver() -> Box<dyn DatabaseDriver> {
+    new_conn(DRIVER_KIND::LIMBO_IN_MEM).expect("Failed to create Limbo driver")
 }
 
 impl super::Engine for LimboEngine {
     fn run(&mut self) {
-        use std::sync::Arc;
+        use std::sync::{Arc, Mutex};
         use std::thread;
 
-        let (debug, run_count, thread_per_exec) = (
+        let (debug, prob, run_count, thread_per_exec, base_seed) = (
             self.debug.clone(),
+            self.stmt_prob.clone(),
             self.run_count,
             self.thread_per_exec,
+            self.rng.get_seed()
         );
 
         // Shared statistics
-        let (success_count, failed_new_count) = (
+        let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
             Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            Arc::new(Mutex::new(std::collections::HashMap::new()))
         );
 
         let start_time = std::time::Instant::now();
+        let executor_id = std::env::var("EXEC_PARAM_SEED")
+            .unwrap_or_else(|_| "unknown".to_string());
+        let progress = {
+            let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
+                Arc::clone(&success_count),
+                Arc::clone(&failed_expected_count),
+                Arc::clone(&failed_new_count),
+                Arc::clone(&stmt_type_counts)
+            );
+            let executor_id = executor_id.clone();
+            super::ProgressReporter::start(move || {
+                super::ExecutionStats::new(
+                    start_time.elapsed(),
+                    success_count.load(std::sync::atomic::Ordering::Relaxed),
+                    failed_expected_count.load(std::sync::atomic::Ordering::Relaxed),
+                    failed_new_count.load(std::sync::atomic::Ordering::Relaxed),
+                    thread_per_exec,
+                    stmt_type_counts.lock().map(|counts| counts.clone()).unwrap_or_default(),
+                    executor_id.clone(),
+                )
+            })
+        };
         let mut handles = vec![];
 
-        // Divide run_count among threads as evenly as possible
-        let base_per_thread = run_count / thread_per_exec;
-        let extra = run_count % thread_per_exec;
-
         for n in 0..thread_per_exec {
-            let thread_run_count = if n < extra { base_per_thread + 1 } else { base_per_thread };
-            let (success_count, failed_new_count) = (
+            let thread_run_count = run_count;
+            let gen_options = self.gen_options.clone();
+            let (thread_seed, debug, prob) = (
+                base_seed.wrapping_add(n as u64),
+                debug.clone(),
+                prob.clone()
+            );
+            let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
                 Arc::clone(&success_count),
+                Arc::clone(&failed_expected_count),
                 Arc::clone(&failed_new_count),
+                Arc::clone(&stmt_type_counts)
             );
-            let debug = debug.clone();
+            let record = self.record.clone();
 
             handles.push(thread::spawn(move || {
-                let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
-                let mut driver = rt.block_on(async {
-                    LimboDriver::new().await.expect("Failed to create Limbo driver")
-                });
-
-                for _ in 0..thread_run_count {
-                    let sql = "SELECT 1;".to_string();
-
-                    match driver.exec(&sql) {
-                        Ok(affected) => {
-                            if let Some(debug) = &debug {
-                                if debug.show_success_sql {
-                                    log::info!("SQL executed successfully: {} (affected: {})", sql, affected);
-                                }
+                let mut driver = new_limbo_driver();
+                let mut rng = LcgRng::new(thread_seed);
+                let mut local_stmt_type_counts = std::collections::HashMap::new();
+                let mut replay_log = replay::start(record.as_deref(), thread_seed, DRIVER_KIND::LIMBO_IN_MEM);
+                let mut history: VecDeque<String> = VecDeque::with_capacity(super::MAX_HISTORY);
+                let mut recorder = Recorder::open();
+
+                for i in 0..thread_run_count {
+                    if i % super::STMT_COUNT_MERGE_INTERVAL == 0 {
+                        super::merge_stmt_type_counts(&stmt_type_counts, &mut local_stmt_type_counts);
+                    }
+                    let sql = if let Some(prob) = &prob {
+                        generate_sql_by_prob(prob, &mut rng, |kind, rng| {
+                            *local_stmt_type_counts.entry(format!("{:?}", kind)).or_insert(0) += 1;
+                            crate::generators::get_stmt_by_seed(&*driver, rng, kind, &gen_options)
+                        })
+                    } else {
+                        "SELECT 1;".to_string()
+                    };
+
+                    if history.len() == super::MAX_HISTORY {
+                        history.pop_front();
+                    }
+                    history.push_back(sql.clone());
+
+                    // Unimplemented features in Limbo panic instead of returning an error
+                    let result = catch_unwind(AssertUnwindSafe(|| driver.exec(&sql)));
+                    if let Some(replay_log) = &mut replay_log {
+                        let outcome = match &result {
+                            Ok(result) => Outcome::of(result),
+                            Err(_) => Outcome::Panic,
+                        };
+                        replay_log.record(&sql, outcome);
+                    }
+                    match result {
+                        Ok(Ok(affected)) => {
+                            if let Some(debug) = &debug && debug.show_success_sql {
+                                log::info!("SQL executed successfully: {} (affected: {})", sql, affected);
                             }
                             success_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                         }
-                        Err(e) => {
-                            failed_new_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                            if let Some(debug) = &debug {
-                                if debug.show_failed_sql {
-                                    log::info!("Error executing SQL: {} with ret: [{:?}]", sql, e);
+                        Ok(Err(e)) => {
+                            if !is_expected_error(&e) {
+                                failed_new_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                if let Some(debug) = &debug && debug.show_failed_sql {
+                                    log::info!("Error executing SQL: {} with ret: [{}]", sql, e);
                                 }
+                                let detail = e.to_string();
+                                if !recorder.repeat(&Signature::new("error", &sql, &detail)) {
+                                    let history = history.iter().cloned().collect();
+                                    let finding = Finding::new("error", thread_seed, &sql, detail, history)
+                                        .with_schema(oracles::schema_state(&*driver));
+                                    recorder.record(&finding);
+                                }
+                            } else {
+                                failed_expected_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            }
+                        }
+                        Err(payload) => {
+                            failed_new_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            log::warn!("Limbo panicked executing SQL: {} (seed {})", sql, thread_seed);
+                            let detail = super::panic_message(&*payload);
+                            if !recorder.repeat(&Signature::new("panic", &sql, &detail)) {
+                                let history = std::mem::take(&mut history).into();
+                                recorder.record(&Finding::new("panic", thread_seed, &sql, detail, history));
                             }
+                            // The connection mutex is poisoned after a panic, so start over on a fresh database
+                            driver = new_limbo_driver();
+                            history.clear();
                         }
                     }
                 }
+
+                // Merge local statement type counts
+                super::merge_stmt_type_counts(&stmt_type_counts, &mut local_stmt_type_counts);
             }));
         }
 
         for handle in handles {
             handle.join().expect("Thread panicked");
         }
+        drop(progress);
 
         let elapsed = start_time.elapsed();
-        let (final_success, final_failed_new) = (
+        let (final_success, final_failed_exp, final_failed_new) = (
             success_count.load(std::sync::atomic::Ordering::Relaxed),
+            failed_expected_count.load(std::sync::atomic::Ordering::Relaxed),
             failed_new_count.load(std::sync::atomic::Ordering::Relaxed)
         );
 
         info!(
-            "finish exec in {:.2?}, success/failed_new: {}/{}",
-            elapsed, final_success, final_failed_new
+            "finish exec in {:.2?}, success/failed_exp/failed_new: {}/{}/{}",
+            elapsed, final_success, final_failed_exp, final_failed_new
         );
-    }
 
-    fn generate_sql(&mut self) -> String {
-        "SELECT 1;".to_string()
+        let stmt_counts = if let Ok(stmt_type_counts) = stmt_type_counts.lock() {
+            info!("Statement type statistics: {:?}", *stmt_type_counts);
+            stmt_type_counts.clone()
+        } else {
+            std::collections::HashMap::new()
+        };
+
+        // Create and submit statistics
+        let stats = super::ExecutionStats::new(
+            elapsed,
+            final_success,
+            final_failed_exp,
+            final_failed_new,
+            thread_per_exec,
+            stmt_counts,
+            executor_id,
+        );
+
+        // Submit stats using blocking version
+        if let Err(e) = super::submit_stats_blocking(stats) {
+            log::warn!("Failed to submit statistics: {}", e);
+        }
     }
 
     fn get_driver_kind(&self) -> DRIVER_KIND { DRIVER_KIND::LIMBO_IN_MEM }
-    fn get_sqlite_driver_box(&mut self) -> Option<&mut dyn DatabaseDriver<Connection = rusqlite::Connection>> { None }
-    fn get_limbo_driver_box(&mut self) -> Option<&mut dyn DatabaseDriver<Connection = limbo::Connection>> {
-        Some(&mut *self.limbo_driver_box)
-    }
 }
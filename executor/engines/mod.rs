@@ -1,23 +1,95 @@
+use crate::generators::GeneratorOptions;
+use crate::replay;
+use crate::generators::common::SqlKind;
 use sqlsmith_rs_common::profile::Profile;
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
-use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver, new_conn};
+use sqlsmith_rs_drivers::{DRIVER_KIND, new_conn};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
 use std::time::Duration;
 
+/// How often an executor started by a server run submits the statistics it has so far.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
 mod sqlite_engine;
 pub use sqlite_engine::SqliteEngine;
 
 mod limbo_engine;
 pub use limbo_engine::LimboEngine;
 
-// Define Engine trait
+mod differential_engine;
+pub use differential_engine::DifferentialEngine;
+
+mod plan_guide;
+
+// Define Engine trait; every thread generates against and runs on its own connection
 pub trait Engine {
     fn run(&mut self);
-    fn generate_sql(&mut self) -> String;
     fn get_driver_kind(&self) -> DRIVER_KIND;
-    fn get_sqlite_driver_box(&mut self) -> Option<&mut dyn DatabaseDriver<Connection = rusqlite::Connection>>;
-    fn get_limbo_driver_box(&mut self) -> Option<&mut dyn DatabaseDriver<Connection = limbo::Connection>>;
+}
+
+/// Picks a statement kind weighted by `prob` and asks `get_stmt` to generate it.
+pub(crate) fn generate_sql_by_prob<F>(
+    prob: &sqlsmith_rs_common::profile::StmtProb,
+    rng: &mut LcgRng,
+    mut get_stmt: F,
+) -> String
+where
+    F: FnMut(SqlKind, &mut LcgRng) -> Option<String>,
+{
+    let thresholds = [
+        (prob.SELECT, SqlKind::Select),
+        (prob.INSERT, SqlKind::Insert),
+        (prob.UPDATE, SqlKind::Update),
+        (prob.DELETE, SqlKind::Delete),
+        (prob.VACUUM, SqlKind::Vacuum),
+        (prob.PRAGMA, SqlKind::Pragma),
+        (prob.CREATE_TRIGGER, SqlKind::CreateTrigger),
+        (prob.DROP_TRIGGER, SqlKind::DropTrigger),
+        (prob.DATE_FUNC, SqlKind::DateFunc), // Added support for DATE_FUNC
+        (prob.CREATE_TABLE, SqlKind::CreateTable),
+        (prob.CREATE_INDEX, SqlKind::CreateIndex),
+        (prob.CREATE_VIEW, SqlKind::CreateView),
+        (prob.ALTER_TABLE, SqlKind::AlterTable),
+        (prob.DROP_TABLE, SqlKind::DropTable),
+        (prob.DROP_INDEX, SqlKind::DropIndex),
+        (prob.DROP_VIEW, SqlKind::DropView),
+    ];
+
+    let total: u64 = thresholds.iter().map(|(p, _)| p).sum();
+    if total == 0 {
+        return "SELECT 1;".to_string();
+    }
+
+    let r = rng.rand().unsigned_abs() % total;
+    let mut accum = 0;
+
+    for (prob, kind) in thresholds {
+        accum += prob;
+        if r < accum {
+            return get_stmt(kind, rng).unwrap_or_else(|| "SELECT 1;".to_string());
+        }
+    }
+
+    "SELECT 1;".to_string()
+}
+
+/// Generates `count` statements from `seed` against a fresh database of `kind` without
+/// executing them, so the schema stays the initial one throughout.
+pub fn generate_statements(seed: u64, kind: DRIVER_KIND, count: usize, profile: &Profile) -> anyhow::Result<Vec<String>> {
+    let driver = new_conn(kind)?;
+    let gen_options = GeneratorOptions::from_profile(profile);
+    let mut rng = LcgRng::new(seed);
+    Ok((0..count)
+        .map(|_| match &profile.stmt_prob {
+            Some(prob) => generate_sql_by_prob(prob, &mut rng, |kind, rng| {
+                crate::generators::get_stmt_by_seed(&*driver, rng, kind, &gen_options)
+            }),
+            None => "SELECT 1;".to_string(),
+        })
+        .collect())
 }
 
 pub fn with_driver_kind(
@@ -27,28 +99,43 @@ pub fn with_driver_kind(
     profile: &Profile,
 ) -> anyhow::Result<Box<dyn Engine>> {
     let thread_per_exec = profile.thread_per_exec.unwrap_or(5);
+    let record = profile.record.unwrap_or(false).then(|| replay::profile_hash(profile));
+    if profile.differential.unwrap_or(false) {
+        // Differential runs always pair SQLite with Limbo, regardless of `kind`
+        return Ok(Box::new(DifferentialEngine {
+            rng: LcgRng::new(seed),
+            run_count,
+            thread_per_exec,
+            stmt_prob: profile.stmt_prob.clone(),
+            gen_options: GeneratorOptions::from_profile(profile),
+            debug: profile.debug.clone(),
+            record,
+        }));
+    }
     match kind {
         DRIVER_KIND::SQLITE_IN_MEM => {
-            let driver = new_conn(DRIVER_KIND::SQLITE_IN_MEM)?;
             Ok(Box::new(SqliteEngine {
                 rng: LcgRng::new(seed),
-                sqlite_driver_box: driver,
                 run_count,
                 thread_per_exec,
                 stmt_prob: profile.stmt_prob.clone(),
+                gen_options: GeneratorOptions::from_profile(profile),
                 debug: profile.debug.clone(),
+                oracle: profile.oracle,
+                plan_guided: profile.plan_guided.unwrap_or(false),
+                reduce: profile.reduce.unwrap_or(false),
+                record,
             }))
         }
         DRIVER_KIND::LIMBO_IN_MEM => {
-            let rt = tokio::runtime::Runtime::new()?;
-            let driver = rt.block_on(sqlsmith_rs_drivers::limbo_in_mem::LimboDriver::new())?;
             Ok(Box::new(LimboEngine {
                 rng: LcgRng::new(seed),
-                limbo_driver_box: Box::new(driver),
                 run_count,
                 thread_per_exec,
                 stmt_prob: profile.stmt_prob.clone(),
+                gen_options: GeneratorOptions::from_profile(profile),
                 debug: profile.debug.clone(),
+                record,
             }))
         }
     }
@@ -60,12 +147,20 @@ pub struct ExecutionStats {
     pub success_count: usize,
     pub failed_expected_count: usize,
     pub failed_new_count: usize,
+    /// Queries whose result disagreed with the logic oracle's rewrite
+    pub oracle_mismatch_count: usize,
+    /// Distinct EXPLAIN QUERY PLAN shapes among the executed SELECTs, when plan guided
+    pub unique_plan_count: usize,
     pub total_queries: usize,
     pub thread_count: usize,
     pub queries_per_second: f64,
     pub error_rate: f64,
     pub stmt_type_counts: HashMap<String, usize>,
     pub executor_id: String,
+    /// Run of the server that started this executor, if any
+    pub run_id: Option<u64>,
+    /// Submitted while the executor is still running; the last submission has it false
+    pub in_progress: bool,
     pub timestamp: String,
 }
 
@@ -97,38 +192,127 @@ impl ExecutionStats {
             success_count,
             failed_expected_count,
             failed_new_count,
+            oracle_mismatch_count: 0,
+            unique_plan_count: 0,
             total_queries,
             thread_count,
             queries_per_second,
             error_rate,
             stmt_type_counts,
             executor_id,
+            run_id: std::env::var("EXEC_PARAM_RUN_ID").ok().and_then(|id| id.parse().ok()),
+            in_progress: false,
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
+
+    pub fn with_oracle_mismatches(mut self, oracle_mismatch_count: usize) -> Self {
+        self.oracle_mismatch_count = oracle_mismatch_count;
+        self
+    }
+
+    pub fn with_unique_plans(mut self, unique_plan_count: usize) -> Self {
+        self.unique_plan_count = unique_plan_count;
+        self
+    }
 }
 
 pub fn submit_stats_blocking(stats: ExecutionStats) -> Result<(), Box<dyn std::error::Error>> {
-    let client = reqwest::blocking::Client::new();
-    
-    match client
-        .post("http://127.0.0.1:8080/internal/stat/submit")
-        .json(&stats)
-        .send()
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                log::info!("Statistics submitted successfully for executor: {}", stats.executor_id);
-                Ok(())
-            } else {
-                let error_msg = format!("Failed to submit statistics: HTTP {}", response.status());
-                log::warn!("{}", error_msg);
-                Err(error_msg.into())
-            }
+    match post_stats(&stats) {
+        Ok(()) => {
+            log::info!("Statistics submitted successfully for executor: {}", stats.executor_id);
+            Ok(())
         }
         Err(e) => {
             log::warn!("Failed to submit statistics to server: {}", e);
-            Err(e.into())
+            Err(e)
+        }
+    }
+}
+
+fn post_stats(stats: &ExecutionStats) -> Result<(), Box<dyn std::error::Error>> {
+    let response = reqwest::blocking::Client::new()
+        .post("http://127.0.0.1:8080/internal/stat/submit")
+        .json(stats)
+        .send()?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Failed to submit statistics: HTTP {}", response.status()).into())
+    }
+}
+
+/// Submits in-progress statistics every [`PROGRESS_INTERVAL`] from a background thread until
+/// dropped, so the server can show a run while it goes.
+pub struct ProgressReporter {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ProgressReporter {
+    /// Starts reporting if a server run started this executor; `snapshot` gives the
+    /// statistics so far.
+    pub fn start(snapshot: impl Fn() -> ExecutionStats + Send + 'static) -> Option<Self> {
+        std::env::var("EXEC_PARAM_RUN_ID").ok()?;
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let handle = {
+            let stopped = Arc::clone(&stopped);
+            std::thread::spawn(move || {
+                let (lock, wake) = &*stopped;
+                loop {
+                    let guard = lock.lock().unwrap();
+                    let (guard, wait) = wake.wait_timeout_while(guard, PROGRESS_INTERVAL, |stopped| !*stopped).unwrap();
+                    if !wait.timed_out() {
+                        break;
+                    }
+                    drop(guard);
+                    let mut stats = snapshot();
+                    stats.in_progress = true;
+                    if let Err(e) = post_stats(&stats) {
+                        log::debug!("Failed to submit progress: {}", e);
+                    }
+                }
+            })
+        };
+        Some(Self {
+            stopped,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        let (lock, wake) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        wake.notify_one();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Statements a thread runs between merging its statement type counts, so progress includes them.
+pub(crate) const STMT_COUNT_MERGE_INTERVAL: usize = 256;
+
+/// Statements a thread keeps for the findings it records; older ones are forgotten, so a
+/// finding made later than that needs its recorded schema to be replayed.
+pub(crate) const MAX_HISTORY: usize = 2000;
+
+/// The message a panic was raised with.
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Moves a thread's statement type counts into the executor's.
+pub(crate) fn merge_stmt_type_counts(global: &Mutex<HashMap<String, usize>>, local: &mut HashMap<String, usize>) {
+    if let Ok(mut global_map) = global.lock() {
+        for (k, v) in local.drain() {
+            *global_map.entry(k).or_insert(0) += v;
         }
     }
 }
@@ -0,0 +1,216 @@
+// Query plan guidance: every executed SELECT is explained, and the shape of its plan decides
+// whether the query is kept for mutation and how statement kinds are weighted next.
+use crate::generators::ast::{CompoundOp, Dialect, Expr, JoinKind, SelectStmt, Stmt, UnaryOp};
+use crate::generators::{GeneratorOptions, get_select_stmt};
+use sqlsmith_rs_common::profile::StmtProb;
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::{DatabaseDriver, SqlValue};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Queries with new plans kept per thread for mutation.
+const MAX_CORPUS: usize = 256;
+
+/// How many recent SELECTs the novelty rate is measured over.
+const NOVELTY_WINDOW: usize = 64;
+
+/// Below this share of new plans in the window, plan discovery is considered stalled.
+const MIN_NOVELTY_RATE: f64 = 0.05;
+
+/// Plan shapes seen by all threads of an executor.
+pub type PlanCoverage = Arc<Mutex<HashSet<u64>>>;
+
+/// Per-thread state: the queries that found new plans and whether recent ones did.
+pub struct PlanGuide {
+    coverage: PlanCoverage,
+    corpus: Vec<SelectStmt>,
+    recent: VecDeque<bool>,
+}
+
+impl PlanGuide {
+    pub fn new(coverage: PlanCoverage) -> Self {
+        Self {
+            coverage,
+            corpus: vec![],
+            recent: VecDeque::with_capacity(NOVELTY_WINDOW),
+        }
+    }
+
+    /// A query to run next: a mutation of a stored query a third of the time, otherwise a
+    /// freshly generated one.
+    pub fn next_select(
+        &self,
+        driver: &dyn DatabaseDriver,
+        rng: &mut LcgRng,
+        options: &GeneratorOptions,
+    ) -> Option<SelectStmt> {
+        if !self.corpus.is_empty() && rng.rand().unsigned_abs().is_multiple_of(3) {
+            let parent = &self.corpus[(rng.rand().unsigned_abs() as usize) % self.corpus.len()];
+            return Some(mutate(parent.clone(), rng));
+        }
+        get_select_stmt(driver, rng, options)
+    }
+
+    /// Explains a query that ran successfully and records whether its plan is new.
+    pub fn observe(&mut self, driver: &dyn DatabaseDriver, query: SelectStmt) {
+        let sql = Stmt::Select(query.clone()).to_sql(Dialect::from(driver.kind()));
+        let Some(shape) = plan_shape(driver, &sql) else {
+            return;
+        };
+        let novel = self.coverage.lock().map(|mut seen| seen.insert(shape)).unwrap_or(false);
+        if novel {
+            if self.corpus.len() >= MAX_CORPUS {
+                self.corpus.swap_remove(0);
+            }
+            self.corpus.push(query);
+        }
+        if self.recent.len() == NOVELTY_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(novel);
+    }
+
+    /// Reweights `prob` by how plan discovery is going: while new plans keep showing up,
+    /// SELECTs are doubled; once they dry up, the schema changes that give the planner new
+    /// options are favoured instead. Kinds disabled in the profile stay disabled.
+    pub fn adjust(&self, prob: &StmtProb) -> StmtProb {
+        let mut prob = prob.clone();
+        if self.recent.len() < NOVELTY_WINDOW {
+            return prob;
+        }
+        let novel = self.recent.iter().filter(|novel| **novel).count();
+        if (novel as f64) / (NOVELTY_WINDOW as f64) >= MIN_NOVELTY_RATE {
+            prob.SELECT *= 2;
+        } else {
+            prob.CREATE_INDEX *= 4;
+            prob.CREATE_VIEW *= 4;
+            prob.CREATE_TABLE *= 2;
+        }
+        prob
+    }
+}
+
+/// Number of distinct plan shapes recorded so far.
+pub fn unique_plans(coverage: &PlanCoverage) -> usize {
+    coverage.lock().map(|seen| seen.len()).unwrap_or(0)
+}
+
+/// Hashes the shape of `sql`'s query plan: the tree of EXPLAIN QUERY PLAN steps with
+/// relation, index and column names and subquery numbers left out, so the same strategy
+/// over different tables counts once.
+pub fn plan_shape(driver: &dyn DatabaseDriver, sql: &str) -> Option<u64> {
+    let rows = driver.query_rows(&format!("EXPLAIN QUERY PLAN {}", sql)).ok()?;
+    Some(hash_plan(&rows))
+}
+
+/// Rows are (id, parent, notused, detail), parents listed before their children.
+fn hash_plan(rows: &[Vec<SqlValue>]) -> u64 {
+    let mut depths: HashMap<i64, usize> = HashMap::new();
+    let mut hasher = DefaultHasher::new();
+    for row in rows {
+        let (Some(SqlValue::Integer(id)), Some(SqlValue::Integer(parent)), Some(SqlValue::Text(detail))) =
+            (row.first(), row.get(1), row.get(3))
+        else {
+            continue;
+        };
+        let depth = depths.get(parent).map_or(0, |d| d + 1);
+        depths.insert(*id, depth);
+        depth.hash(&mut hasher);
+        normalize_detail(detail).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Keeps the upper-case keywords of a plan step and replaces everything else with `?`.
+fn normalize_detail(detail: &str) -> String {
+    let mut words: Vec<&str> = vec![];
+    for word in detail.split_whitespace() {
+        let keyword = word.bytes().all(|b| b.is_ascii_uppercase() || b == b'-') && word.len() > 1;
+        let word = if keyword { word } else { "?" };
+        if !(word == "?" && words.last() == Some(&"?")) {
+            words.push(word);
+        }
+    }
+    words.join(" ")
+}
+
+/// Applies one random structural change that usually keeps the query valid but gives the
+/// planner a different problem.
+fn mutate(mut query: SelectStmt, rng: &mut LcgRng) -> SelectStmt {
+    const COMPOUND_OPS: [CompoundOp; 4] =
+        [CompoundOp::Union, CompoundOp::UnionAll, CompoundOp::Intersect, CompoundOp::Except];
+    const JOIN_KINDS: [JoinKind; 3] = [JoinKind::Inner, JoinKind::Left, JoinKind::Cross];
+    let pick = |rng: &mut LcgRng, n: usize| (rng.rand().unsigned_abs() as usize) % n;
+
+    match pick(rng, 6) {
+        0 => query.core.distinct = !query.core.distinct,
+        1 => {
+            query.core.where_clause = match query.core.where_clause.take() {
+                Some(predicate) if pick(rng, 2) == 0 => Some(Expr::unary(UnaryOp::Not, predicate)),
+                _ => None,
+            }
+        }
+        2 if !query.compounds.is_empty() => {
+            let i = pick(rng, query.compounds.len());
+            query.compounds[i].0 = COMPOUND_OPS[pick(rng, COMPOUND_OPS.len())];
+        }
+        3 if query.core.from.as_ref().is_some_and(|from| !from.joins.is_empty()) => {
+            let joins = &mut query.core.from.as_mut().unwrap().joins;
+            let i = pick(rng, joins.len());
+            // CROSS JOIN takes no constraint, so only switch between constrained kinds
+            if joins[i].constraint.is_some() || joins[i].natural {
+                joins[i].kind = JOIN_KINDS[pick(rng, 2)];
+            } else {
+                joins[i].kind = JOIN_KINDS[pick(rng, JOIN_KINDS.len())];
+            }
+        }
+        4 => {
+            query.core.group_by.clear();
+            query.core.having = None;
+        }
+        _ => {
+            if query.order_by.is_empty() && query.limit.is_none() {
+                query.limit = Some(pick(rng, 10) as u64);
+            } else {
+                query.order_by.clear();
+                query.limit = None;
+            }
+        }
+    }
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_shape_ignores_names_but_not_strategy() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE a (x INTEGER, y TEXT); CREATE TABLE b (x INTEGER, y TEXT); CREATE INDEX b_x ON b (x);",
+        )
+        .unwrap();
+        let shape = |sql: &str| {
+            let rows: Vec<Vec<SqlValue>> = conn
+                .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+                .unwrap()
+                .query_map([], |row| {
+                    Ok(vec![
+                        SqlValue::Integer(row.get(0)?),
+                        SqlValue::Integer(row.get(1)?),
+                        SqlValue::Integer(row.get(2)?),
+                        SqlValue::Text(row.get(3)?),
+                    ])
+                })
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            hash_plan(&rows)
+        };
+        assert_eq!(shape("SELECT y FROM a WHERE x = 1"), shape("SELECT x FROM b WHERE y = 'v'"));
+        assert_ne!(shape("SELECT y FROM a WHERE x = 1"), shape("SELECT y FROM b WHERE x = 1"));
+    }
+}
@@ -1,112 +1,185 @@
-use crate::generators::common::SqlKind;
 use log::info;
-use rusqlite::Connection;
+use std::collections::VecDeque;
+use sqlsmith_rs_common::findings::{Finding, Signature};
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
-use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver, new_conn};
+use sqlsmith_rs_drivers::{DRIVER_KIND, new_conn};
+
+use super::generate_sql_by_prob;
+use super::plan_guide::{self, PlanGuide};
+use crate::findings::Recorder;
+use crate::generators::{GeneratedStmt, GeneratorOptions};
+use crate::generators::ast::{Dialect, Stmt};
+use crate::generators::common::SqlKind;
+use crate::oracles;
+use crate::reducer::{self, Step, Target};
+use crate::replay::{self, Outcome};
 
-pub struct SqliteEngine<'a> {
+pub struct SqliteEngine {
     pub rng: LcgRng,
-    pub sqlite_driver_box: Box<dyn DatabaseDriver<Connection = Connection> + 'a>,
     pub run_count: usize,
     pub thread_per_exec: usize,
     pub stmt_prob: Option<sqlsmith_rs_common::profile::StmtProb>,
+    pub gen_options: GeneratorOptions,
     pub debug: Option<sqlsmith_rs_common::profile::DebugOptions>,
+    /// Logic bug oracle run in place of every generated SELECT
+    pub oracle: Option<sqlsmith_rs_common::profile::OracleKind>,
+    /// Explain executed SELECTs and steer generation toward unseen query plans
+    pub plan_guided: bool,
+    /// Reduce the statements behind each new finding before recording it
+    pub reduce: bool,
+    /// Profile hash to start per-thread replay logs with; None when not recording
+    pub record: Option<String>,
 }
 
-fn generate_sql_by_prob<F>(
-    prob: &sqlsmith_rs_common::profile::StmtProb,
-    rng: &mut LcgRng,
-    mut get_stmt: F,
-) -> String
-where
-    F: FnMut(SqlKind, &mut LcgRng) -> Option<String>,
-{
-    let thresholds = [
-        (prob.SELECT, SqlKind::Select),
-        (prob.INSERT, SqlKind::Insert),
-        (prob.UPDATE, SqlKind::Update),
-        (prob.DELETE, SqlKind::Delete),
-        (prob.VACUUM, SqlKind::Vacuum),
-        (prob.PRAGMA, SqlKind::Pragma),
-        (prob.CREATE_TRIGGER, SqlKind::CreateTrigger),
-        (prob.DROP_TRIGGER, SqlKind::DropTrigger),
-        (prob.DATE_FUNC, SqlKind::DateFunc), // Added support for DATE_FUNC
-    ];
-
-    let total: u64 = thresholds.iter().map(|(p, _)| p).sum();
-    if total == 0 {
-        return "SELECT 1;".to_string();
-    }
-
-    let r = (rng.rand().abs() as u64) % total;
-    let mut accum = 0;
-
-    for (prob, kind) in thresholds {
-        accum += prob;
-        if r < accum {
-            return get_stmt(kind, rng).unwrap_or_else(|| "SELECT 1;".to_string());
-        }
-    }
-
-    "SELECT 1;".to_string()
-}
-
-impl<'a> super::Engine for SqliteEngine<'a> {
+impl super::Engine for SqliteEngine {
     fn run(&mut self) {
         use std::sync::{Arc, Mutex};
         use std::thread;
 
-        let (debug, prob, run_count, thread_per_exec, base_seed) = (
+        let (debug, prob, run_count, thread_per_exec, base_seed, oracle_kind, plan_guided, reduce) = (
             self.debug.clone(),
             self.stmt_prob.clone(),
             self.run_count,
             self.thread_per_exec,
-            self.rng.get_seed()
+            self.rng.get_seed(),
+            self.oracle,
+            self.plan_guided,
+            self.reduce
         );
 
         // Shared statistics
-        let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
+        let (success_count, failed_expected_count, failed_new_count, oracle_mismatch_count, stmt_type_counts) = (
+            Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             Arc::new(Mutex::new(std::collections::HashMap::new()))
         );
+        let plan_coverage = plan_guide::PlanCoverage::default();
 
         let start_time = std::time::Instant::now();
+        let executor_id = std::env::var("EXEC_PARAM_SEED")
+            .unwrap_or_else(|_| "unknown".to_string());
+        let progress = {
+            let (success_count, failed_expected_count, failed_new_count, oracle_mismatch_count, stmt_type_counts) = (
+                Arc::clone(&success_count),
+                Arc::clone(&failed_expected_count),
+                Arc::clone(&failed_new_count),
+                Arc::clone(&oracle_mismatch_count),
+                Arc::clone(&stmt_type_counts)
+            );
+            let (plan_coverage, executor_id) = (Arc::clone(&plan_coverage), executor_id.clone());
+            super::ProgressReporter::start(move || {
+                super::ExecutionStats::new(
+                    start_time.elapsed(),
+                    success_count.load(std::sync::atomic::Ordering::Relaxed),
+                    failed_expected_count.load(std::sync::atomic::Ordering::Relaxed),
+                    failed_new_count.load(std::sync::atomic::Ordering::Relaxed),
+                    thread_per_exec,
+                    stmt_type_counts.lock().map(|counts| counts.clone()).unwrap_or_default(),
+                    executor_id.clone(),
+                )
+                .with_oracle_mismatches(oracle_mismatch_count.load(std::sync::atomic::Ordering::Relaxed))
+                .with_unique_plans(plan_guide::unique_plans(&plan_coverage))
+            })
+        };
         let mut handles = vec![];
 
         for n in 0..thread_per_exec {
             let thread_run_count = run_count;
+            let gen_options = self.gen_options.clone();
             let (thread_seed, debug, prob) = (
                 base_seed.wrapping_add(n as u64),
                 debug.clone(),
                 prob.clone()
             );
-            let (success_count, failed_expected_count, failed_new_count, stmt_type_counts) = (
+            let (success_count, failed_expected_count, failed_new_count, oracle_mismatch_count, stmt_type_counts) = (
                 Arc::clone(&success_count),
                 Arc::clone(&failed_expected_count),
                 Arc::clone(&failed_new_count),
+                Arc::clone(&oracle_mismatch_count),
                 Arc::clone(&stmt_type_counts)
             );
+            let plan_coverage = Arc::clone(&plan_coverage);
+            let record = self.record.clone();
 
             handles.push(thread::spawn(move || {
-                let mut driver = new_conn(DRIVER_KIND::SQLITE_IN_MEM).expect("Failed to create driver");
+                let driver = new_conn(DRIVER_KIND::SQLITE_IN_MEM).expect("Failed to create driver");
                 let mut rng = LcgRng::new(thread_seed);
                 let ignorable_errors = vec![rusqlite::ErrorCode::ConstraintViolation];
                 let mut local_stmt_type_counts = std::collections::HashMap::new();
+                let oracle = oracle_kind.map(oracles::new_oracle);
+                let mut history: VecDeque<Step> = VecDeque::with_capacity(super::MAX_HISTORY);
+                let mut plan_guide = plan_guided.then(|| PlanGuide::new(plan_coverage));
+                let mut replay_log = replay::start(record.as_deref(), thread_seed, DRIVER_KIND::SQLITE_IN_MEM);
+                let mut recorder = Recorder::open();
 
-                for _ in 0..thread_run_count {
-                    let conn = driver.get_connection_mut();
+                for i in 0..thread_run_count {
+                    if i % super::STMT_COUNT_MERGE_INTERVAL == 0 {
+                        super::merge_stmt_type_counts(&stmt_type_counts, &mut local_stmt_type_counts);
+                    }
+                    let (mut picked, mut guided_select, mut step) = (None, None, None);
                     let sql = if let Some(prob) = &prob {
-                        generate_sql_by_prob(prob, &mut rng, |kind, rng| {
+                        let prob = match &plan_guide {
+                            Some(plan_guide) => plan_guide.adjust(prob),
+                            None => prob.clone(),
+                        };
+                        generate_sql_by_prob(&prob, &mut rng, |kind, rng| {
+                            picked = Some(kind.clone());
+                            // The oracle generates and runs its own queries instead, which are not counted
+                            if kind == SqlKind::Select && oracle.is_some() {
+                                return Some(String::new());
+                            }
                             *local_stmt_type_counts.entry(format!("{:?}", kind)).or_insert(0) += 1;
-                            crate::generators::sqlite::get_stmt_by_seed(conn, rng, kind)
+                            // Guided SELECTs keep their syntax tree so they can be mutated later
+                            if kind == SqlKind::Select
+                                && oracle.is_none()
+                                && let Some(plan_guide) = &plan_guide
+                            {
+                                let query = plan_guide.next_select(&*driver, rng, &gen_options)?;
+                                let generated = GeneratedStmt::Ast(Box::new(Stmt::Select(query.clone())));
+                                let sql = step.insert(Step::new(generated, Dialect::from(driver.kind()))).sql.clone();
+                                guided_select = Some(query);
+                                return Some(sql);
+                            }
+                            let generated = crate::generators::gen_stmt_by_seed(&*driver, rng, kind, &gen_options)?;
+                            Some(step.insert(Step::new(generated, Dialect::from(driver.kind()))).sql.clone())
                         })
                     } else {
                         "SELECT 1;".to_string()
                     };
 
-                    match driver.exec(&sql) {
+                    // Reads do not change the database, so the oracle's queries stand in for them
+                    if picked == Some(SqlKind::Select)
+                        && let Some(oracle) = &oracle
+                    {
+                        if let Some(mismatch) = oracle.check(&*driver, &mut rng) {
+                            oracle_mismatch_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            log::warn!("{} oracle found a logic bug: {} ({})", oracle.name(), mismatch.sql, mismatch.detail);
+                            if !recorder.repeat(&Signature::new(oracle.name(), &mismatch.sql, &mismatch.detail)) {
+                                let history = history.make_contiguous();
+                                let steps = if reduce {
+                                    sqls(&reducer::reduce(history, &Target::Check(&*mismatch.recheck)))
+                                } else {
+                                    sqls(history)
+                                };
+                                let finding = Finding::new(oracle.name(), thread_seed, &mismatch.sql, mismatch.detail, steps)
+                                    .with_schema(oracles::schema_state(&*driver));
+                                recorder.record(&finding);
+                            }
+                        }
+                        continue;
+                    }
+                    if history.len() == super::MAX_HISTORY {
+                        history.pop_front();
+                    }
+                    history.push_back(step.unwrap_or_else(|| Step::text(&sql)));
+
+                    let result = driver.exec(&sql);
+                    if let Some(replay_log) = &mut replay_log {
+                        replay_log.record(&sql, Outcome::of(&result));
+                    }
+                    match result {
                         Ok(affected) => {
                             if let Some(debug) = &debug {
                                 if debug.show_success_sql {
@@ -114,6 +187,9 @@ impl<'a> super::Engine for SqliteEngine<'a> {
                                 }
                             }
                             success_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            if let (Some(plan_guide), Some(query)) = (&mut plan_guide, guided_select) {
+                                plan_guide.observe(&*driver, query);
+                            }
                         }
                         Err(e) => {
                             let error_code = if let Some(rusqlite_error) = e.downcast_ref::<rusqlite::Error>() {
@@ -132,6 +208,19 @@ impl<'a> super::Engine for SqliteEngine<'a> {
                                         log::info!("Error executing SQL: {} with ret: [{:?}]", sql, error_code);
                                     }
                                 }
+                                let signature = reducer::error_signature(&e);
+                                if !recorder.repeat(&Signature::new("error", &sql, &signature)) {
+                                    let history = history.make_contiguous();
+                                    let steps = if reduce {
+                                        sqls(&reducer::reduce(history, &Target::Error(signature.clone())))
+                                    } else {
+                                        sqls(history)
+                                    };
+                                    let last = steps.last().cloned().unwrap_or_else(|| sql.clone());
+                                    let finding = Finding::new("error", thread_seed, &last, signature, steps)
+                                        .with_schema(oracles::schema_state(&*driver));
+                                    recorder.record(&finding);
+                                }
                             } else {
                                 failed_expected_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                             }
@@ -140,28 +229,28 @@ impl<'a> super::Engine for SqliteEngine<'a> {
                 }
 
                 // Merge local statement type counts
-                if let Ok(mut global_map) = stmt_type_counts.lock() {
-                    for (k, v) in local_stmt_type_counts {
-                        *global_map.entry(k).or_insert(0) += v;
-                    }
-                }
+                super::merge_stmt_type_counts(&stmt_type_counts, &mut local_stmt_type_counts);
             }));
         }
 
         for handle in handles {
             handle.join().expect("Thread panicked");
         }
+        drop(progress);
 
         let elapsed = start_time.elapsed();
-        let (final_success, final_failed_exp, final_failed_new) = (
+        let (final_success, final_failed_exp, final_failed_new, final_mismatches) = (
             success_count.load(std::sync::atomic::Ordering::Relaxed),
             failed_expected_count.load(std::sync::atomic::Ordering::Relaxed),
-            failed_new_count.load(std::sync::atomic::Ordering::Relaxed)
+            failed_new_count.load(std::sync::atomic::Ordering::Relaxed),
+            oracle_mismatch_count.load(std::sync::atomic::Ordering::Relaxed)
         );
 
+        let unique_plans = plan_guide::unique_plans(&plan_coverage);
+
         info!(
-            "finish exec in {:.2?}, success/failed_exp/failed_new: {}/{}/{}",
-            elapsed, final_success, final_failed_exp, final_failed_new
+            "finish exec in {:.2?}, success/failed_exp/failed_new/oracle_mismatch: {}/{}/{}/{}, unique plans: {}",
+            elapsed, final_success, final_failed_exp, final_failed_new, final_mismatches, unique_plans
         );
         
         let stmt_counts = if let Ok(stmt_type_counts) = stmt_type_counts.lock() {
@@ -172,9 +261,6 @@ impl<'a> super::Engine for SqliteEngine<'a> {
         };
 
         // Create and submit statistics
-        let executor_id = std::env::var("EXEC_PARAM_SEED")
-            .unwrap_or_else(|_| "unknown".to_string());
-        
         let stats = super::ExecutionStats::new(
             elapsed,
             final_success,
@@ -183,7 +269,9 @@ impl<'a> super::Engine for SqliteEngine<'a> {
             thread_per_exec,
             stmt_counts,
             executor_id,
-        );
+        )
+        .with_oracle_mismatches(final_mismatches)
+        .with_unique_plans(unique_plans);
 
         // Submit stats using blocking version
         if let Err(e) = super::submit_stats_blocking(stats) {
@@ -191,24 +279,9 @@ impl<'a> super::Engine for SqliteEngine<'a> {
         }
     }
 
-    fn generate_sql(&mut self) -> String {
-        let conn = self.sqlite_driver_box.get_connection_mut();
-        if let Some(prob) = &self.stmt_prob {
-            generate_sql_by_prob(prob, &mut self.rng, |kind, rng| {
-                crate::generators::sqlite::get_stmt_by_seed(conn, rng, kind)
-            })
-        } else {
-            "SELECT 1;".to_string()
-        }
-    }
-
     fn get_driver_kind(&self) -> DRIVER_KIND { DRIVER_KIND::SQLITE_IN_MEM }
-    
-    fn get_sqlite_driver_box(&mut self) -> Option<&mut dyn DatabaseDriver<Connection = rusqlite::Connection>> {
-        Some(&mut *self.sqlite_driver_box)
-    }
-    
-    fn get_limbo_driver_box(&mut self) -> Option<&mut dyn DatabaseDriver<Connection = limbo::Connection>> {
-        None
-    }
+}
+
+fn sqls(steps: &[Step]) -> Vec<String> {
+    steps.iter().map(|step| step.sql.clone()).collect()
 }
@@ -0,0 +1,75 @@
+use sqlsmith_rs_common::findings::{FINDINGS_DB, Finding, FindingStore, Signature};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A thread's handle on the finding store, which only takes a finding whose signature no thread
+/// or executor has stored yet; repeats are just counted.
+pub struct Recorder {
+    store: Option<FindingStore>,
+    /// Signatures this thread stored or saw stored, so repeats skip the lookup
+    stored: HashSet<String>,
+}
+
+impl Recorder {
+    /// Opens `findings.db` in the working directory; without it findings are only logged.
+    pub fn open() -> Self {
+        let store = FindingStore::open(Path::new(FINDINGS_DB))
+            .map_err(|e| log::warn!("Failed to open {}, findings will not be stored: {}", FINDINGS_DB, e))
+            .ok();
+        Self {
+            store,
+            stored: HashSet::new(),
+        }
+    }
+
+    /// Whether a finding with this signature is stored already, in which case it is counted and
+    /// need not be reduced or recorded again.
+    pub fn repeat(&mut self, signature: &Signature) -> bool {
+        let key = signature.key();
+        let stored = self.stored.contains(&key)
+            || self
+                .store
+                .as_ref()
+                .is_some_and(|store| store.contains(&key).unwrap_or(false));
+        if stored {
+            if let Some(store) = &self.store
+                && let Err(e) = store.bump(&key)
+            {
+                log::warn!("Failed to count finding: {}", e);
+            }
+            self.stored.insert(key);
+        }
+        stored
+    }
+
+    pub fn record(&mut self, finding: &Finding) {
+        self.stored.insert(finding.signature().key());
+        if let Some(store) = &self.store {
+            match store.record(finding) {
+                Ok(true) => announce(finding),
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to record finding: {}", e),
+            }
+        }
+    }
+}
+
+/// Tells the server about a new finding so it can show it while the run goes; only executors
+/// started by a server run do.
+fn announce(finding: &Finding) {
+    let Some(run_id) = std::env::var("EXEC_PARAM_RUN_ID").ok().and_then(|id| id.parse::<u64>().ok()) else {
+        return;
+    };
+    let submission = serde_json::json!({
+        "run_id": run_id,
+        "executor_id": std::env::var("EXEC_PARAM_SEED").unwrap_or_else(|_| "unknown".to_string()),
+        "finding": finding,
+    });
+    let sent = reqwest::blocking::Client::new()
+        .post("http://127.0.0.1:8080/internal/finding/submit")
+        .json(&submission)
+        .send();
+    if let Err(e) = sent {
+        log::debug!("Failed to announce finding: {}", e);
+    }
+}
@@ -0,0 +1,526 @@
+// Typed SQL syntax tree produced by the generators and printed by `render`
+pub mod render;
+
+pub use render::Dialect;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+    /// `TRUE`/`FALSE`, SQLite's keywords for 1 and 0
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Plus,
+    Not,
+    BitNot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Is,
+    IsNot,
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Concat,
+    BitAnd,
+    BitOr,
+    ShiftLeft,
+    ShiftRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LikeOp {
+    Like,
+    Glob,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collation {
+    Binary,
+    NoCase,
+    RTrim,
+}
+
+/// A column reference, optionally qualified by a table name or alias.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnRef {
+    pub table: Option<String>,
+    pub name: String,
+}
+
+impl ColumnRef {
+    pub fn new(name: &str) -> Self {
+        Self {
+            table: None,
+            name: name.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Literal),
+    Column(ColumnRef),
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// `CASE [operand] WHEN .. THEN .. [ELSE ..] END`
+    Case {
+        operand: Option<Box<Expr>>,
+        when_then: Vec<(Expr, Expr)>,
+        else_expr: Option<Box<Expr>>,
+    },
+    Cast {
+        expr: Box<Expr>,
+        type_name: String,
+    },
+    Between {
+        expr: Box<Expr>,
+        negated: bool,
+        low: Box<Expr>,
+        high: Box<Expr>,
+    },
+    InList {
+        expr: Box<Expr>,
+        negated: bool,
+        list: Vec<Expr>,
+    },
+    Like {
+        expr: Box<Expr>,
+        negated: bool,
+        op: LikeOp,
+        pattern: Box<Expr>,
+    },
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    Collate {
+        expr: Box<Expr>,
+        collation: Collation,
+    },
+    Function(Box<FunctionCall>),
+    /// A scalar subquery
+    Subquery(Box<SelectStmt>),
+    Exists {
+        negated: bool,
+        subquery: Box<SelectStmt>,
+    },
+    InSubquery {
+        expr: Box<Expr>,
+        negated: bool,
+        subquery: Box<SelectStmt>,
+    },
+    /// `RAISE(...)`, only valid inside a trigger body; `message` is unused for `Ignore`
+    Raise {
+        action: RaiseAction,
+        message: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaiseAction {
+    Ignore,
+    Rollback,
+    Abort,
+    Fail,
+}
+
+impl Expr {
+    pub fn column(name: &str) -> Self {
+        Expr::Column(ColumnRef::new(name))
+    }
+
+    pub fn unary(op: UnaryOp, expr: Expr) -> Self {
+        Expr::Unary {
+            op,
+            expr: Box::new(expr),
+        }
+    }
+
+    pub fn function(call: FunctionCall) -> Self {
+        Expr::Function(Box::new(call))
+    }
+
+    pub fn binary(op: BinaryOp, left: Expr, right: Expr) -> Self {
+        Expr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+}
+
+/// A scalar, aggregate or window function call. `count()` with no arguments is `count(*)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    pub distinct: bool,
+    pub args: Vec<Expr>,
+    pub filter: Option<Box<Expr>>,
+    pub over: Option<Over>,
+}
+
+impl FunctionCall {
+    pub fn new(name: &str, args: Vec<Expr>) -> Self {
+        Self {
+            name: name.to_string(),
+            distinct: false,
+            args,
+            filter: None,
+            over: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Over {
+    Window(Box<WindowDef>),
+    /// A window defined in the `WINDOW` clause
+    Named(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowDef {
+    pub partition_by: Vec<Expr>,
+    pub order_by: Vec<OrderingTerm>,
+    pub frame: Option<FrameSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameUnits {
+    Rows,
+    Range,
+    Groups,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(u64),
+    CurrentRow,
+    Following(u64),
+    UnboundedFollowing,
+}
+
+/// `<units> BETWEEN <start> AND <end>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSpec {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl TableRef {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            alias: None,
+        }
+    }
+}
+
+/// An entry of a FROM clause.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableOrSubquery {
+    Table(TableRef),
+    Subquery { query: Box<SelectStmt>, alias: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Cross,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinConstraint {
+    On(Expr),
+    Using(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub natural: bool,
+    pub kind: JoinKind,
+    pub table: TableOrSubquery,
+    pub constraint: Option<JoinConstraint>,
+}
+
+/// A table followed by any number of joins, evaluated left to right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromClause {
+    pub table: TableOrSubquery,
+    pub joins: Vec<Join>,
+}
+
+impl FromClause {
+    pub fn new(table: TableRef) -> Self {
+        Self {
+            table: TableOrSubquery::Table(table),
+            joins: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderingTerm {
+    pub expr: Expr,
+    pub desc: bool,
+}
+
+/// `SELECT ... FROM ... WHERE ... GROUP BY ...`, one arm of a compound query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectCore {
+    pub distinct: bool,
+    pub columns: Vec<SelectItem>,
+    pub from: Option<FromClause>,
+    pub where_clause: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub windows: Vec<(String, WindowDef)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompoundOp {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cte {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: Box<SelectStmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithClause {
+    pub recursive: bool,
+    pub ctes: Vec<Cte>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectStmt {
+    pub with: Option<WithClause>,
+    pub core: SelectCore,
+    pub compounds: Vec<(CompoundOp, SelectCore)>,
+    pub order_by: Vec<OrderingTerm>,
+    pub limit: Option<u64>,
+}
+
+impl SelectStmt {
+    /// A query made of a single core, without ordering or limit.
+    pub fn new(core: SelectCore) -> Self {
+        Self {
+            with: None,
+            core,
+            compounds: vec![],
+            order_by: vec![],
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertSource {
+    DefaultValues,
+    Values(Vec<Vec<Expr>>),
+    Select(Box<SelectStmt>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertStmt {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub source: InsertSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateStmt {
+    pub table: String,
+    pub set: Vec<(String, Expr)>,
+    pub from: Option<TableRef>,
+    pub where_clause: Option<Expr>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteStmt {
+    pub table: String,
+    pub where_clause: Option<Expr>,
+    pub limit: Option<u64>,
+    pub returning: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnConstraint {
+    PrimaryKey { autoincrement: bool },
+    NotNull,
+    Unique,
+    Check(Expr),
+    Default(Expr),
+    Collate(Collation),
+    /// `GENERATED ALWAYS AS (expr) STORED|VIRTUAL`
+    Generated { expr: Expr, stored: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    /// Declared type; empty for a column without one
+    pub type_name: String,
+    pub constraints: Vec<ColumnConstraint>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableConstraint {
+    PrimaryKey(Vec<String>),
+    Unique(Vec<String>),
+    Check(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTableStmt {
+    pub if_not_exists: bool,
+    pub name: String,
+    pub columns: Vec<ColumnDef>,
+    pub constraints: Vec<TableConstraint>,
+    pub without_rowid: bool,
+    pub strict: bool,
+}
+
+/// `CREATE [UNIQUE] INDEX`; a `where_clause` makes it a partial index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateIndexStmt {
+    pub unique: bool,
+    pub if_not_exists: bool,
+    pub name: String,
+    pub table: String,
+    pub columns: Vec<OrderingTerm>,
+    pub where_clause: Option<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateViewStmt {
+    pub if_not_exists: bool,
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: Box<SelectStmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterTableAction {
+    RenameTo(String),
+    RenameColumn { from: String, to: String },
+    AddColumn(ColumnDef),
+    DropColumn(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlterTableStmt {
+    pub table: String,
+    pub action: AlterTableAction,
+}
+
+/// Kinds of schema object removed by [`DropStmt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Table,
+    Index,
+    View,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropStmt {
+    pub kind: ObjectKind,
+    pub if_exists: bool,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerTiming {
+    Before,
+    After,
+    /// Only allowed on views
+    InsteadOf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerEvent {
+    Insert,
+    /// `UPDATE`, or `UPDATE OF columns` when `columns` is not empty
+    Update { columns: Vec<String> },
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTriggerStmt {
+    pub if_not_exists: bool,
+    pub name: String,
+    pub timing: TriggerTiming,
+    pub event: TriggerEvent,
+    pub table: String,
+    pub when: Option<Expr>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropTriggerStmt {
+    pub if_exists: bool,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Select(SelectStmt),
+    Insert(InsertStmt),
+    Update(UpdateStmt),
+    Delete(DeleteStmt),
+    CreateTrigger(CreateTriggerStmt),
+    DropTrigger(DropTriggerStmt),
+    CreateTable(CreateTableStmt),
+    CreateIndex(CreateIndexStmt),
+    CreateView(CreateViewStmt),
+    AlterTable(AlterTableStmt),
+    Drop(DropStmt),
+}
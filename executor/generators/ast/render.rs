@@ -0,0 +1,840 @@
+// Prints the syntax tree as SQL text for a given target engine
+use super::*;
+use sqlsmith_rs_drivers::DRIVER_KIND;
+
+/// SQL flavour a statement is rendered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Sqlite,
+    Limbo,
+}
+
+impl From<DRIVER_KIND> for Dialect {
+    fn from(kind: DRIVER_KIND) -> Self {
+        match kind {
+            DRIVER_KIND::SQLITE_IN_MEM => Dialect::Sqlite,
+            DRIVER_KIND::LIMBO_IN_MEM => Dialect::Limbo,
+        }
+    }
+}
+
+impl Dialect {
+    /// Whether `UPDATE`/`DELETE` accept a trailing `LIMIT`. The bundled SQLite is built
+    /// without SQLITE_ENABLE_UPDATE_DELETE_LIMIT, so the limit goes through a rowid subquery.
+    fn supports_dml_limit(self) -> bool {
+        match self {
+            Dialect::Sqlite => false,
+            Dialect::Limbo => true,
+        }
+    }
+}
+
+impl Stmt {
+    pub fn to_sql(&self, dialect: Dialect) -> String {
+        let mut r = Renderer::new(dialect);
+        r.stmt(self);
+        r.out.push(';');
+        r.out
+    }
+}
+
+struct Renderer {
+    dialect: Dialect,
+    out: String,
+}
+
+impl Renderer {
+    fn new(dialect: Dialect) -> Self {
+        Self {
+            dialect,
+            out: String::new(),
+        }
+    }
+
+    fn push(&mut self, s: &str) {
+        self.out.push_str(s);
+    }
+
+    /// Renders `items` separated by `sep`.
+    fn list<T>(&mut self, items: &[T], sep: &str, mut f: impl FnMut(&mut Self, &T)) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.push(sep);
+            }
+            f(self, item);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Select(s) => self.select(s),
+            Stmt::Insert(s) => self.insert(s),
+            Stmt::Update(s) => self.update(s),
+            Stmt::Delete(s) => self.delete(s),
+            Stmt::CreateTrigger(s) => self.create_trigger(s),
+            Stmt::DropTrigger(s) => self.drop_trigger(s),
+            Stmt::CreateTable(s) => self.create_table(s),
+            Stmt::CreateIndex(s) => self.create_index(s),
+            Stmt::CreateView(s) => self.create_view(s),
+            Stmt::AlterTable(s) => self.alter_table(s),
+            Stmt::Drop(s) => self.drop(s),
+        }
+    }
+
+    fn literal(&mut self, lit: &Literal) {
+        match lit {
+            Literal::Null => self.push("NULL"),
+            Literal::Integer(v) => self.push(&v.to_string()),
+            // `{:?}` always keeps a decimal point or exponent, so the value stays REAL
+            Literal::Real(v) if v.is_nan() => self.push("NULL"),
+            Literal::Real(v) if v.is_infinite() => self.push(if *v > 0.0 { "9e999" } else { "-9e999" }),
+            Literal::Real(v) => self.push(&format!("{:?}", v)),
+            Literal::Text(v) => self.push(&format!("'{}'", v.replace('\'', "''"))),
+            Literal::Blob(v) => {
+                let hex = v.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+                self.push(&format!("X'{}'", hex));
+            }
+            Literal::Bool(v) => self.push(if *v { "TRUE" } else { "FALSE" }),
+        }
+    }
+
+    fn collate(&mut self, collation: Collation) {
+        self.push(match collation {
+            Collation::Binary => " COLLATE BINARY",
+            Collation::NoCase => " COLLATE NOCASE",
+            Collation::RTrim => " COLLATE RTRIM",
+        });
+    }
+
+    fn column(&mut self, col: &ColumnRef) {
+        if let Some(table) = &col.table {
+            self.push(table);
+            self.push(".");
+        }
+        self.push(&col.name);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(lit) => self.literal(lit),
+            Expr::Column(col) => self.column(col),
+            Expr::Unary { op, expr } => {
+                // The trailing space keeps `- -1` from turning into a `--` comment
+                self.push(match op {
+                    UnaryOp::Neg => "(- ",
+                    UnaryOp::Plus => "(+ ",
+                    UnaryOp::Not => "(NOT ",
+                    UnaryOp::BitNot => "(~ ",
+                });
+                self.expr(expr);
+                self.push(")");
+            }
+            Expr::Binary { op, left, right } => {
+                self.push("(");
+                self.expr(left);
+                self.push(match op {
+                    BinaryOp::Eq => " = ",
+                    BinaryOp::NotEq => " != ",
+                    BinaryOp::Lt => " < ",
+                    BinaryOp::LtEq => " <= ",
+                    BinaryOp::Gt => " > ",
+                    BinaryOp::GtEq => " >= ",
+                    BinaryOp::Is => " IS ",
+                    BinaryOp::IsNot => " IS NOT ",
+                    BinaryOp::And => " AND ",
+                    BinaryOp::Or => " OR ",
+                    BinaryOp::Add => " + ",
+                    BinaryOp::Sub => " - ",
+                    BinaryOp::Mul => " * ",
+                    BinaryOp::Div => " / ",
+                    BinaryOp::Mod => " % ",
+                    BinaryOp::Concat => " || ",
+                    BinaryOp::BitAnd => " & ",
+                    BinaryOp::BitOr => " | ",
+                    BinaryOp::ShiftLeft => " << ",
+                    BinaryOp::ShiftRight => " >> ",
+                });
+                self.expr(right);
+                self.push(")");
+            }
+            Expr::Case {
+                operand,
+                when_then,
+                else_expr,
+            } => {
+                self.push("CASE");
+                if let Some(operand) = operand {
+                    self.push(" ");
+                    self.expr(operand);
+                }
+                for (when, then) in when_then {
+                    self.push(" WHEN ");
+                    self.expr(when);
+                    self.push(" THEN ");
+                    self.expr(then);
+                }
+                if let Some(else_expr) = else_expr {
+                    self.push(" ELSE ");
+                    self.expr(else_expr);
+                }
+                self.push(" END");
+            }
+            Expr::Cast { expr, type_name } => {
+                self.push("CAST(");
+                self.expr(expr);
+                self.push(" AS ");
+                self.push(type_name);
+                self.push(")");
+            }
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => {
+                self.push("(");
+                self.expr(expr);
+                self.push(if *negated { " NOT BETWEEN " } else { " BETWEEN " });
+                self.expr(low);
+                self.push(" AND ");
+                self.expr(high);
+                self.push(")");
+            }
+            Expr::InList { expr, negated, list } => {
+                self.push("(");
+                self.expr(expr);
+                self.push(if *negated { " NOT IN (" } else { " IN (" });
+                self.list(list, ", ", |r, e| r.expr(e));
+                self.push("))");
+            }
+            Expr::Like {
+                expr,
+                negated,
+                op,
+                pattern,
+            } => {
+                self.push("(");
+                self.expr(expr);
+                if *negated {
+                    self.push(" NOT");
+                }
+                self.push(match op {
+                    LikeOp::Like => " LIKE ",
+                    LikeOp::Glob => " GLOB ",
+                });
+                self.expr(pattern);
+                self.push(")");
+            }
+            Expr::IsNull { expr, negated } => {
+                self.push("(");
+                self.expr(expr);
+                self.push(if *negated { " IS NOT NULL)" } else { " IS NULL)" });
+            }
+            Expr::Collate { expr, collation } => {
+                self.push("(");
+                self.expr(expr);
+                self.collate(*collation);
+                self.push(")");
+            }
+            Expr::Function(call) => self.function_call(call),
+            Expr::Raise { action, message } => {
+                self.push(match action {
+                    RaiseAction::Ignore => "RAISE(IGNORE",
+                    RaiseAction::Rollback => "RAISE(ROLLBACK, ",
+                    RaiseAction::Abort => "RAISE(ABORT, ",
+                    RaiseAction::Fail => "RAISE(FAIL, ",
+                });
+                if *action != RaiseAction::Ignore {
+                    self.literal(&Literal::Text(message.clone().unwrap_or_default()));
+                }
+                self.push(")");
+            }
+            Expr::Subquery(query) => {
+                self.push("(");
+                self.select(query);
+                self.push(")");
+            }
+            Expr::Exists { negated, subquery } => {
+                self.push(if *negated { "(NOT EXISTS (" } else { "(EXISTS (" });
+                self.select(subquery);
+                self.push("))");
+            }
+            Expr::InSubquery {
+                expr,
+                negated,
+                subquery,
+            } => {
+                self.push("(");
+                self.expr(expr);
+                self.push(if *negated { " NOT IN (" } else { " IN (" });
+                self.select(subquery);
+                self.push("))");
+            }
+        }
+    }
+
+    fn function_call(&mut self, call: &FunctionCall) {
+        self.push(&call.name);
+        self.push(if call.distinct { "(DISTINCT " } else { "(" });
+        self.list(&call.args, ", ", |r, e| r.expr(e));
+        self.push(")");
+        if let Some(filter) = &call.filter {
+            self.push(" FILTER (WHERE ");
+            self.expr(filter);
+            self.push(")");
+        }
+        match &call.over {
+            Some(Over::Window(def)) => {
+                self.push(" OVER (");
+                self.window_def(def);
+                self.push(")");
+            }
+            Some(Over::Named(name)) => {
+                self.push(" OVER ");
+                self.push(name);
+            }
+            None => {}
+        }
+    }
+
+    fn window_def(&mut self, def: &WindowDef) {
+        let mut parts = 0;
+        if !def.partition_by.is_empty() {
+            self.push("PARTITION BY ");
+            self.list(&def.partition_by, ", ", |r, e| r.expr(e));
+            parts += 1;
+        }
+        if !def.order_by.is_empty() {
+            self.push(if parts > 0 { " ORDER BY " } else { "ORDER BY " });
+            self.ordering_terms(&def.order_by);
+            parts += 1;
+        }
+        if let Some(frame) = &def.frame {
+            if parts > 0 {
+                self.push(" ");
+            }
+            self.push(match frame.units {
+                FrameUnits::Rows => "ROWS BETWEEN ",
+                FrameUnits::Range => "RANGE BETWEEN ",
+                FrameUnits::Groups => "GROUPS BETWEEN ",
+            });
+            self.frame_bound(frame.start);
+            self.push(" AND ");
+            self.frame_bound(frame.end);
+        }
+    }
+
+    fn frame_bound(&mut self, bound: FrameBound) {
+        match bound {
+            FrameBound::UnboundedPreceding => self.push("UNBOUNDED PRECEDING"),
+            FrameBound::Preceding(n) => self.push(&format!("{} PRECEDING", n)),
+            FrameBound::CurrentRow => self.push("CURRENT ROW"),
+            FrameBound::Following(n) => self.push(&format!("{} FOLLOWING", n)),
+            FrameBound::UnboundedFollowing => self.push("UNBOUNDED FOLLOWING"),
+        }
+    }
+
+    fn ordering_terms(&mut self, terms: &[OrderingTerm]) {
+        self.list(terms, ", ", |r, term| {
+            r.expr(&term.expr);
+            r.push(if term.desc { " DESC" } else { " ASC" });
+        });
+    }
+
+    fn table_ref(&mut self, table: &TableRef) {
+        self.push(&table.name);
+        if let Some(alias) = &table.alias {
+            self.push(" AS ");
+            self.push(alias);
+        }
+    }
+
+    fn table_or_subquery(&mut self, item: &TableOrSubquery) {
+        match item {
+            TableOrSubquery::Table(table) => self.table_ref(table),
+            TableOrSubquery::Subquery { query, alias } => {
+                self.push("(");
+                self.select(query);
+                self.push(") AS ");
+                self.push(alias);
+            }
+        }
+    }
+
+    fn select_from(&mut self, from: &FromClause) {
+        self.table_or_subquery(&from.table);
+        for join in &from.joins {
+            self.push(if join.natural { " NATURAL" } else { "" });
+            self.push(match join.kind {
+                JoinKind::Inner => " INNER JOIN ",
+                JoinKind::Left => " LEFT JOIN ",
+                JoinKind::Cross => " CROSS JOIN ",
+            });
+            self.table_or_subquery(&join.table);
+            match &join.constraint {
+                Some(JoinConstraint::On(expr)) => {
+                    self.push(" ON ");
+                    self.expr(expr);
+                }
+                Some(JoinConstraint::Using(columns)) => {
+                    self.push(" USING (");
+                    self.push(&columns.join(", "));
+                    self.push(")");
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn select(&mut self, s: &SelectStmt) {
+        if let Some(with) = &s.with {
+            self.push(if with.recursive { "WITH RECURSIVE " } else { "WITH " });
+            self.list(&with.ctes, ", ", |r, cte| {
+                r.push(&cte.name);
+                if !cte.columns.is_empty() {
+                    r.push("(");
+                    r.push(&cte.columns.join(", "));
+                    r.push(")");
+                }
+                r.push(" AS (");
+                r.select(&cte.query);
+                r.push(")");
+            });
+            self.push(" ");
+        }
+        self.select_core(&s.core);
+        for (op, core) in &s.compounds {
+            self.push(match op {
+                CompoundOp::Union => " UNION ",
+                CompoundOp::UnionAll => " UNION ALL ",
+                CompoundOp::Intersect => " INTERSECT ",
+                CompoundOp::Except => " EXCEPT ",
+            });
+            self.select_core(core);
+        }
+        if !s.order_by.is_empty() {
+            self.push(" ORDER BY ");
+            self.ordering_terms(&s.order_by);
+        }
+        if let Some(limit) = s.limit {
+            self.push(&format!(" LIMIT {}", limit));
+        }
+    }
+
+    fn select_core(&mut self, s: &SelectCore) {
+        self.push("SELECT ");
+        if s.distinct {
+            self.push("DISTINCT ");
+        }
+        self.list(&s.columns, ", ", |r, item| {
+            r.expr(&item.expr);
+            if let Some(alias) = &item.alias {
+                r.push(" AS ");
+                r.push(alias);
+            }
+        });
+        if let Some(from) = &s.from {
+            self.push(" FROM ");
+            self.select_from(from);
+        }
+        if let Some(where_clause) = &s.where_clause {
+            self.push(" WHERE ");
+            self.expr(where_clause);
+        }
+        if !s.group_by.is_empty() {
+            self.push(" GROUP BY ");
+            self.list(&s.group_by, ", ", |r, e| r.expr(e));
+        }
+        if let Some(having) = &s.having {
+            self.push(" HAVING ");
+            self.expr(having);
+        }
+        if !s.windows.is_empty() {
+            self.push(" WINDOW ");
+            self.list(&s.windows, ", ", |r, (name, def)| {
+                r.push(name);
+                r.push(" AS (");
+                r.window_def(def);
+                r.push(")");
+            });
+        }
+    }
+
+    fn insert(&mut self, s: &InsertStmt) {
+        self.push("INSERT INTO ");
+        self.push(&s.table);
+        if !s.columns.is_empty() {
+            self.push(" (");
+            self.push(&s.columns.join(", "));
+            self.push(")");
+        }
+        match &s.source {
+            InsertSource::DefaultValues => self.push(" DEFAULT VALUES"),
+            InsertSource::Values(rows) => {
+                self.push(" VALUES ");
+                self.list(rows, ", ", |r, row| {
+                    r.push("(");
+                    r.list(row, ", ", |r, e| r.expr(e));
+                    r.push(")");
+                });
+            }
+            InsertSource::Select(select) => {
+                self.push(" ");
+                self.select(select);
+            }
+        }
+    }
+
+    /// Emits the `WHERE` part of an `UPDATE`/`DELETE`. When the dialect has no `LIMIT` on
+    /// those statements the limit is folded in here as a rowid subquery, and `true` is returned.
+    fn dml_where(&mut self, table: &str, from: Option<&TableRef>, where_clause: Option<&Expr>, limit: Option<u64>) -> bool {
+        match limit {
+            Some(limit) if !self.dialect.supports_dml_limit() => {
+                self.push(&format!(" WHERE {}.rowid IN (SELECT {}.rowid FROM {}", table, table, table));
+                if let Some(from) = from {
+                    self.push(", ");
+                    self.table_ref(from);
+                }
+                if let Some(where_clause) = where_clause {
+                    self.push(" WHERE ");
+                    self.expr(where_clause);
+                }
+                self.push(&format!(" LIMIT {})", limit));
+                true
+            }
+            _ => {
+                if let Some(where_clause) = where_clause {
+                    self.push(" WHERE ");
+                    self.expr(where_clause);
+                }
+                false
+            }
+        }
+    }
+
+    fn update(&mut self, s: &UpdateStmt) {
+        self.push("UPDATE ");
+        self.push(&s.table);
+        self.push(" SET ");
+        self.list(&s.set, ", ", |r, (col, value)| {
+            r.push(col);
+            r.push(" = ");
+            r.expr(value);
+        });
+        if let Some(from) = &s.from {
+            self.push(" FROM ");
+            self.table_ref(from);
+        }
+        if !self.dml_where(&s.table, s.from.as_ref(), s.where_clause.as_ref(), s.limit)
+            && let Some(limit) = s.limit
+        {
+            self.push(&format!(" LIMIT {}", limit));
+        }
+    }
+
+    fn delete(&mut self, s: &DeleteStmt) {
+        self.push("DELETE FROM ");
+        self.push(&s.table);
+        let limit_folded = self.dml_where(&s.table, None, s.where_clause.as_ref(), s.limit);
+        // RETURNING precedes ORDER BY/LIMIT in the grammar
+        if !s.returning.is_empty() {
+            self.push(" RETURNING ");
+            self.list(&s.returning, ", ", |r, e| r.expr(e));
+        }
+        if !limit_folded && let Some(limit) = s.limit {
+            self.push(&format!(" LIMIT {}", limit));
+        }
+    }
+
+    fn create_trigger(&mut self, s: &CreateTriggerStmt) {
+        self.push("CREATE TRIGGER ");
+        if s.if_not_exists {
+            self.push("IF NOT EXISTS ");
+        }
+        self.push(&s.name);
+        self.push(match s.timing {
+            TriggerTiming::Before => " BEFORE",
+            TriggerTiming::After => " AFTER",
+            TriggerTiming::InsteadOf => " INSTEAD OF",
+        });
+        match &s.event {
+            TriggerEvent::Insert => self.push(" INSERT"),
+            TriggerEvent::Update { columns } if columns.is_empty() => self.push(" UPDATE"),
+            TriggerEvent::Update { columns } => {
+                self.push(" UPDATE OF ");
+                self.push(&columns.join(", "));
+            }
+            TriggerEvent::Delete => self.push(" DELETE"),
+        }
+        self.push(" ON ");
+        self.push(&s.table);
+        if let Some(when) = &s.when {
+            self.push(" WHEN ");
+            self.expr(when);
+        }
+        self.push("\nBEGIN\n");
+        for stmt in &s.body {
+            self.push("    ");
+            self.stmt(stmt);
+            self.push(";\n");
+        }
+        self.push("END");
+    }
+
+    fn drop_trigger(&mut self, s: &DropTriggerStmt) {
+        self.push("DROP TRIGGER ");
+        if s.if_exists {
+            self.push("IF EXISTS ");
+        }
+        self.push(&s.name);
+    }
+
+    fn column_def(&mut self, def: &ColumnDef) {
+        self.push(&def.name);
+        if !def.type_name.is_empty() {
+            self.push(" ");
+            self.push(&def.type_name);
+        }
+        for constraint in &def.constraints {
+            match constraint {
+                ColumnConstraint::PrimaryKey { autoincrement } => {
+                    self.push(" PRIMARY KEY");
+                    if *autoincrement {
+                        self.push(" AUTOINCREMENT");
+                    }
+                }
+                ColumnConstraint::NotNull => self.push(" NOT NULL"),
+                ColumnConstraint::Unique => self.push(" UNIQUE"),
+                ColumnConstraint::Check(expr) => {
+                    self.push(" CHECK (");
+                    self.expr(expr);
+                    self.push(")");
+                }
+                // Only literals may follow DEFAULT unparenthesized
+                ColumnConstraint::Default(expr @ Expr::Literal(_)) => {
+                    self.push(" DEFAULT ");
+                    self.expr(expr);
+                }
+                ColumnConstraint::Default(expr) => {
+                    self.push(" DEFAULT (");
+                    self.expr(expr);
+                    self.push(")");
+                }
+                ColumnConstraint::Collate(collation) => self.collate(*collation),
+                ColumnConstraint::Generated { expr, stored } => {
+                    self.push(" GENERATED ALWAYS AS (");
+                    self.expr(expr);
+                    self.push(if *stored { ") STORED" } else { ") VIRTUAL" });
+                }
+            }
+        }
+    }
+
+    fn create_table(&mut self, s: &CreateTableStmt) {
+        self.push("CREATE TABLE ");
+        if s.if_not_exists {
+            self.push("IF NOT EXISTS ");
+        }
+        self.push(&s.name);
+        self.push(" (");
+        self.list(&s.columns, ", ", |r, def| r.column_def(def));
+        for constraint in &s.constraints {
+            self.push(", ");
+            match constraint {
+                TableConstraint::PrimaryKey(cols) => {
+                    self.push("PRIMARY KEY (");
+                    self.push(&cols.join(", "));
+                    self.push(")");
+                }
+                TableConstraint::Unique(cols) => {
+                    self.push("UNIQUE (");
+                    self.push(&cols.join(", "));
+                    self.push(")");
+                }
+                TableConstraint::Check(expr) => {
+                    self.push("CHECK (");
+                    self.expr(expr);
+                    self.push(")");
+                }
+            }
+        }
+        self.push(")");
+        match (s.without_rowid, s.strict) {
+            (true, true) => self.push(" WITHOUT ROWID, STRICT"),
+            (true, false) => self.push(" WITHOUT ROWID"),
+            (false, true) => self.push(" STRICT"),
+            (false, false) => {}
+        }
+    }
+
+    fn create_index(&mut self, s: &CreateIndexStmt) {
+        self.push(if s.unique { "CREATE UNIQUE INDEX " } else { "CREATE INDEX " });
+        if s.if_not_exists {
+            self.push("IF NOT EXISTS ");
+        }
+        self.push(&s.name);
+        self.push(" ON ");
+        self.push(&s.table);
+        self.push(" (");
+        self.ordering_terms(&s.columns);
+        self.push(")");
+        if let Some(where_clause) = &s.where_clause {
+            self.push(" WHERE ");
+            self.expr(where_clause);
+        }
+    }
+
+    fn create_view(&mut self, s: &CreateViewStmt) {
+        self.push("CREATE VIEW ");
+        if s.if_not_exists {
+            self.push("IF NOT EXISTS ");
+        }
+        self.push(&s.name);
+        if !s.columns.is_empty() {
+            self.push("(");
+            self.push(&s.columns.join(", "));
+            self.push(")");
+        }
+        self.push(" AS ");
+        self.select(&s.query);
+    }
+
+    fn alter_table(&mut self, s: &AlterTableStmt) {
+        self.push("ALTER TABLE ");
+        self.push(&s.table);
+        match &s.action {
+            AlterTableAction::RenameTo(name) => {
+                self.push(" RENAME TO ");
+                self.push(name);
+            }
+            AlterTableAction::RenameColumn { from, to } => {
+                self.push(" RENAME COLUMN ");
+                self.push(from);
+                self.push(" TO ");
+                self.push(to);
+            }
+            AlterTableAction::AddColumn(def) => {
+                self.push(" ADD COLUMN ");
+                self.column_def(def);
+            }
+            AlterTableAction::DropColumn(name) => {
+                self.push(" DROP COLUMN ");
+                self.push(name);
+            }
+        }
+    }
+
+    fn drop(&mut self, s: &DropStmt) {
+        self.push(match s.kind {
+            ObjectKind::Table => "DROP TABLE ",
+            ObjectKind::Index => "DROP INDEX ",
+            ObjectKind::View => "DROP VIEW ",
+        });
+        if s.if_exists {
+            self.push("IF EXISTS ");
+        }
+        self.push(&s.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delete_with_limit() -> Stmt {
+        Stmt::Delete(DeleteStmt {
+            table: "item".to_string(),
+            where_clause: Some(Expr::binary(
+                BinaryOp::Eq,
+                Expr::column("i_name"),
+                Expr::Literal(Literal::Text("it's".to_string())),
+            )),
+            limit: Some(3),
+            returning: vec![Expr::column("i_id")],
+        })
+    }
+
+    #[test]
+    fn test_literals_render_as_valid_sql() {
+        let select = Stmt::Select(SelectStmt::new(SelectCore {
+            distinct: false,
+            columns: [Literal::Real(5.0), Literal::Blob(vec![0xab, 0x01]), Literal::Null]
+                .into_iter()
+                .map(|lit| SelectItem { expr: Expr::Literal(lit), alias: None })
+                .collect(),
+            from: None,
+            where_clause: None,
+            group_by: vec![],
+            having: None,
+            windows: vec![],
+        }));
+        assert_eq!(select.to_sql(Dialect::Sqlite), "SELECT 5.0, X'ab01', NULL;");
+    }
+
+    #[test]
+    fn test_dml_limit_is_rendered_per_dialect() {
+        let stmt = delete_with_limit();
+        assert_eq!(
+            stmt.to_sql(Dialect::Limbo),
+            "DELETE FROM item WHERE (i_name = 'it''s') RETURNING i_id LIMIT 3;"
+        );
+        assert_eq!(
+            stmt.to_sql(Dialect::Sqlite),
+            "DELETE FROM item WHERE item.rowid IN (SELECT item.rowid FROM item WHERE (i_name = 'it''s') LIMIT 3) RETURNING i_id;"
+        );
+    }
+
+    #[test]
+    fn test_aggregates_and_windows_render() {
+        let mut count = FunctionCall::new("count", vec![]);
+        count.filter = Some(Box::new(Expr::IsNull {
+            expr: Box::new(Expr::column("i_data")),
+            negated: true,
+        }));
+        let mut rank = FunctionCall::new("rank", vec![]);
+        rank.over = Some(Over::Named("w0".to_string()));
+        let mut sum = FunctionCall::new("sum", vec![Expr::column("i_price")]);
+        sum.distinct = true;
+        sum.over = Some(Over::Window(Box::new(WindowDef {
+            partition_by: vec![],
+            order_by: vec![OrderingTerm {
+                expr: Expr::column("i_id"),
+                desc: false,
+            }],
+            frame: Some(FrameSpec {
+                units: FrameUnits::Rows,
+                start: FrameBound::Preceding(1),
+                end: FrameBound::CurrentRow,
+            }),
+        })));
+        let select = Stmt::Select(SelectStmt::new(SelectCore {
+            distinct: false,
+            columns: [count, rank, sum]
+                .into_iter()
+                .map(|call| SelectItem { expr: Expr::function(call), alias: None })
+                .collect(),
+            from: Some(FromClause::new(TableRef::new("item"))),
+            where_clause: None,
+            group_by: vec![Expr::column("i_im_id")],
+            having: Some(Expr::binary(
+                BinaryOp::Gt,
+                Expr::function(FunctionCall::new("max", vec![Expr::column("i_price")])),
+                Expr::Literal(Literal::Integer(1)),
+            )),
+            windows: vec![(
+                "w0".to_string(),
+                WindowDef {
+                    partition_by: vec![Expr::column("i_im_id")],
+                    order_by: vec![],
+                    frame: None,
+                },
+            )],
+        }));
+        assert_eq!(
+            select.to_sql(Dialect::Sqlite),
+            "SELECT count() FILTER (WHERE (i_data IS NOT NULL)), rank() OVER w0, \
+             sum(DISTINCT i_price) OVER (ORDER BY i_id ASC ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) \
+             FROM item GROUP BY i_im_id HAVING (max(i_price) > 1) WINDOW w0 AS (PARTITION BY i_im_id);"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+// Common ALTER TABLE statement generation logic, shared between limbo/sqlite drivers
+use crate::generators::ast::{AlterTableAction, AlterTableStmt, ColumnConstraint, ColumnDef, Expr, Literal};
+use crate::generators::SEEDED_TABLES;
+use crate::generators::common::create_table_stmt_common::{gen_column_constraints, unused_name};
+use crate::generators::common::data_type::generate_value_by_type;
+use crate::generators::common::expr_common::{ExprGenerator, ExprType};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::TableSchema;
+
+const COLUMN_TYPES: &[&str] = &["INTEGER", "REAL", "TEXT", "BLOB", "NUMERIC", ""];
+
+/// Generates an ALTER TABLE statement renaming the table, or adding, renaming or dropping a column.
+/// The TPC-C tables only get columns added, so the schema the other statements rely on stays.
+/// # Arguments
+/// * `tables` - List of available tables to alter
+/// * `existing` - Names of the tables that already exist, which a renamed one must not take
+/// * `rng` - Random number generator for value selection
+pub fn gen_alter_table_stmt(tables: &[TableSchema], existing: &[String], rng: &mut LcgRng) -> Option<AlterTableStmt> {
+    if tables.is_empty() {
+        return None;
+    }
+    let table = &tables[(rng.rand().unsigned_abs() as usize) % tables.len()];
+    let column_names: Vec<&str> = table.columns.iter().map(|(name, _)| name.as_str()).collect();
+    let new_column_name = unused_name("col", &column_names, rng)?;
+    let existing_column = (!table.columns.is_empty())
+        .then(|| table.columns[(rng.rand().unsigned_abs() as usize) % table.columns.len()].0.clone());
+    let seeded = SEEDED_TABLES.contains(&table.name.as_str());
+
+    let action = match (rng.rand().unsigned_abs() % 6, existing_column) {
+        _ if seeded => AlterTableAction::AddColumn(gen_added_column(table, new_column_name, rng)),
+        (0, _) => AlterTableAction::RenameTo(unused_name("tbl_", existing, rng)?),
+        (1, Some(from)) => AlterTableAction::RenameColumn {
+            from,
+            to: new_column_name,
+        },
+        (2, Some(column)) => AlterTableAction::DropColumn(column),
+        _ => AlterTableAction::AddColumn(gen_added_column(table, new_column_name, rng)),
+    };
+    Some(AlterTableStmt {
+        table: table.name.clone(),
+        action,
+    })
+}
+
+/// A column definition obeying ADD COLUMN's rules: no PRIMARY KEY or UNIQUE, a non-NULL
+/// default for NOT NULL columns, and only VIRTUAL generated columns.
+fn gen_added_column(table: &TableSchema, name: String, rng: &mut LcgRng) -> ColumnDef {
+    let type_name = COLUMN_TYPES[(rng.rand().unsigned_abs() as usize) % COLUMN_TYPES.len()].to_string();
+    let ty = ExprType::from_decl(&type_name);
+    let expr_gen = ExprGenerator::new(2).with_columns(None, &table.columns);
+
+    let mut constraints = Vec::new();
+    if rng.rand().unsigned_abs().is_multiple_of(5) {
+        constraints.push(ColumnConstraint::Generated {
+            expr: expr_gen.gen_expr(ty, rng),
+            stored: false,
+        });
+    } else if rng.rand().unsigned_abs().is_multiple_of(2) {
+        let mut value = generate_value_by_type(ty.sql_name(), rng);
+        if value == Literal::Null {
+            value = Literal::Integer(0);
+        }
+        constraints.push(ColumnConstraint::Default(Expr::Literal(value)));
+    }
+    let has_default = matches!(constraints.first(), Some(ColumnConstraint::Default(_)));
+    constraints.extend(
+        gen_column_constraints(&expr_gen, ty, rng)
+            .into_iter()
+            .filter(|c| match c {
+                ColumnConstraint::Unique => false,
+                ColumnConstraint::NotNull => has_default,
+                _ => true,
+            }),
+    );
+    ColumnDef {
+        name,
+        type_name,
+        constraints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(name: &str, columns: &[&str]) -> TableSchema {
+        TableSchema {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| (c.to_string(), "INTEGER".to_string())).collect(),
+            primary_key: vec![],
+            without_rowid: false,
+        }
+    }
+
+    #[test]
+    fn test_seeded_tables_only_get_columns_and_names_are_unused() {
+        let mut rng = LcgRng::new(3);
+        let seeded = [table("warehouse", &["w_id", "w_name"])];
+        for _ in 0..100 {
+            let stmt = gen_alter_table_stmt(&seeded, &[], &mut rng).unwrap();
+            assert!(matches!(&stmt.action, AlterTableAction::AddColumn(column) if column.name.starts_with("col")));
+        }
+
+        let columns: Vec<String> = (0..100).map(|n| format!("col{}", n)).collect();
+        let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+        let existing: Vec<String> = (0..100).map(|n| format!("tbl_{}", n)).collect();
+        let generated = [table("tbl_0", &columns)];
+        for _ in 0..100 {
+            match gen_alter_table_stmt(&generated, &existing, &mut rng).unwrap().action {
+                AlterTableAction::RenameTo(name) => assert!(!existing.contains(&name), "{} already exists", name),
+                AlterTableAction::RenameColumn { to, .. } => assert!(!columns.contains(&to.as_str())),
+                AlterTableAction::AddColumn(column) => assert!(!columns.contains(&column.name.as_str())),
+                AlterTableAction::DropColumn(_) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Common CREATE INDEX statement generation logic, shared between limbo/sqlite drivers
+use crate::generators::ast::{ColumnRef, CreateIndexStmt, Expr, Literal, OrderingTerm};
+use crate::generators::common::expr_common::{ExprGenerator, ExprType};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::TableSchema;
+
+/// Index expressions and partial index predicates stay shallow.
+const INDEX_EXPR_DEPTH: usize = 2;
+
+/// Generates a CREATE [UNIQUE] INDEX statement, possibly partial or on expressions
+/// # Arguments
+/// * `tables` - List of available tables to index
+/// * `rng` - Random number generator for value selection
+pub fn gen_create_index_stmt(tables: &[TableSchema], rng: &mut LcgRng) -> Option<CreateIndexStmt> {
+    let candidates: Vec<&TableSchema> = tables.iter().filter(|t| !t.columns.is_empty()).collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    let table = candidates[(rng.rand().unsigned_abs() as usize) % candidates.len()];
+
+    // Index expressions may only use deterministic functions and no subqueries
+    let expr_gen = ExprGenerator::new(INDEX_EXPR_DEPTH).with_columns(None, &table.columns);
+    let column_count = (rng.rand().unsigned_abs() as usize % 3) + 1;
+    let columns = (0..column_count)
+        .map(|_| {
+            let ty = ExprType::random(rng);
+            let expr = match expr_gen.gen_expr(ty, rng) {
+                expr if rng.rand().unsigned_abs().is_multiple_of(4) && !is_string_literal(&expr) => expr,
+                _ => {
+                    let (name, _) = &table.columns[(rng.rand().unsigned_abs() as usize) % table.columns.len()];
+                    Expr::Column(ColumnRef::new(name))
+                }
+            };
+            OrderingTerm {
+                expr,
+                desc: rng.rand().unsigned_abs() % 2 == 1,
+            }
+        })
+        .collect();
+
+    // Add optional WHERE clause, making it a partial index
+    let where_clause = if rng.rand().unsigned_abs().is_multiple_of(3) {
+        Some(expr_gen.gen_predicate(rng))
+    } else {
+        None
+    };
+
+    Some(CreateIndexStmt {
+        unique: rng.rand().unsigned_abs().is_multiple_of(4),
+        if_not_exists: rng.rand().unsigned_abs().is_multiple_of(2),
+        name: format!("idx_{}_{}", table.name, rng.rand().unsigned_abs() % 100),
+        table: table.name.clone(),
+        columns,
+        where_clause,
+    })
+}
+
+/// SQLite reads a string literal indexed term, even under COLLATE, as a column name.
+fn is_string_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(Literal::Text(_)) => true,
+        Expr::Collate { expr, .. } => is_string_literal(expr),
+        _ => false,
+    }
+}
@@ -0,0 +1,183 @@
+// Common CREATE TABLE statement generation logic, shared between limbo/sqlite drivers
+use crate::generators::ast::{Collation, ColumnConstraint, ColumnDef, CreateTableStmt, Expr, TableConstraint};
+use crate::generators::common::data_type::generate_value_by_type;
+use crate::generators::common::expr_common::{ExprGenerator, ExprType};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+
+/// Column types for ordinary tables; the empty type gives a column without declared type.
+const COLUMN_TYPES: &[&str] = &["INTEGER", "INT", "REAL", "TEXT", "VARCHAR(16)", "BLOB", "NUMERIC", "BOOLEAN", ""];
+
+/// The only column types a STRICT table accepts.
+const STRICT_COLUMN_TYPES: &[&str] = &["INTEGER", "INT", "REAL", "TEXT", "BLOB", "ANY"];
+
+/// Expressions in CHECK constraints and generated columns stay shallow.
+const CONSTRAINT_EXPR_DEPTH: usize = 2;
+
+fn rand_below(rng: &mut LcgRng, n: usize) -> usize {
+    (rng.rand().unsigned_abs() as usize) % n
+}
+
+/// The first `<prefix><n>` from a random `n` on that `taken` does not hold, ignoring case as
+/// SQLite does.
+pub fn unused_name<T: AsRef<str>>(prefix: &str, taken: &[T], rng: &mut LcgRng) -> Option<String> {
+    let first = rng.rand().unsigned_abs() % 100;
+    (first..)
+        .map(|n| format!("{}{}", prefix, n))
+        .find(|name| !taken.iter().any(|other| other.as_ref().eq_ignore_ascii_case(name)))
+}
+
+/// Generates a CREATE TABLE statement for a new table `tbl_<n>`
+/// # Arguments
+/// * `existing` - Names of the tables that already exist, which the new one must not take
+/// * `rng` - Random number generator for value selection
+pub fn gen_create_table_stmt(existing: &[String], rng: &mut LcgRng) -> Option<CreateTableStmt> {
+    let name = unused_name("tbl_", existing, rng)?;
+    let strict = rand_below(rng, 4) == 0;
+    let types = if strict { STRICT_COLUMN_TYPES } else { COLUMN_TYPES };
+
+    let column_count = rand_below(rng, 5) + 1;
+    let mut columns: Vec<ColumnDef> = (0..column_count)
+        .map(|i| ColumnDef {
+            name: format!("col{}", i),
+            type_name: types[rand_below(rng, types.len())].to_string(),
+            constraints: vec![],
+        })
+        .collect();
+
+    // Primary key: on one column, over several columns in a table constraint, or none
+    let mut constraints = Vec::new();
+    let mut pk_columns = Vec::new();
+    let mut autoincrement = false;
+    match rand_below(rng, 3) {
+        0 => {
+            let idx = rand_below(rng, column_count);
+            pk_columns.push(idx);
+            // AUTOINCREMENT is only allowed on an INTEGER PRIMARY KEY
+            autoincrement = columns[idx].type_name == "INTEGER" && rand_below(rng, 3) == 0;
+            columns[idx].constraints.push(ColumnConstraint::PrimaryKey { autoincrement });
+        }
+        1 => {
+            pk_columns = pick_columns(column_count, rng);
+            constraints.push(TableConstraint::PrimaryKey(
+                pk_columns.iter().map(|&i| columns[i].name.clone()).collect(),
+            ));
+        }
+        _ => {}
+    }
+    let without_rowid = !pk_columns.is_empty() && !autoincrement && rand_below(rng, 4) == 0;
+
+    // Generated columns are computed from the ordinary ones and never part of the key
+    let generated: Vec<bool> = (0..column_count)
+        .map(|i| i > 0 && !pk_columns.contains(&i) && rand_below(rng, 6) == 0)
+        .collect();
+    let plain_columns: Vec<(String, String)> = columns
+        .iter()
+        .zip(&generated)
+        .filter(|(_, generated)| !**generated)
+        .map(|(def, _)| (def.name.clone(), def.type_name.clone()))
+        .collect();
+    let expr_gen = ExprGenerator::new(CONSTRAINT_EXPR_DEPTH).with_columns(None, &plain_columns);
+
+    for (def, generated) in columns.iter_mut().zip(generated) {
+        let ty = ExprType::from_decl(&def.type_name);
+        if generated {
+            def.constraints.push(ColumnConstraint::Generated {
+                expr: expr_gen.gen_expr(ty, rng),
+                stored: rand_below(rng, 2) == 0,
+            });
+        } else if rand_below(rng, 3) == 0 {
+            def.constraints
+                .push(ColumnConstraint::Default(Expr::Literal(generate_value_by_type(ty.sql_name(), rng))));
+        }
+        def.constraints.extend(gen_column_constraints(&expr_gen, ty, rng));
+    }
+
+    // Add optional table constraints
+    if rand_below(rng, 5) == 0 {
+        let unique = pick_columns(column_count, rng);
+        constraints.push(TableConstraint::Unique(
+            unique.iter().map(|&i| columns[i].name.clone()).collect(),
+        ));
+    }
+    if rand_below(rng, 5) == 0 {
+        constraints.push(TableConstraint::Check(expr_gen.gen_predicate(rng)));
+    }
+
+    Some(CreateTableStmt {
+        if_not_exists: rand_below(rng, 2) == 0,
+        name,
+        columns,
+        constraints,
+        without_rowid,
+        strict,
+    })
+}
+
+/// NOT NULL, UNIQUE, CHECK and COLLATE constraints for a column of type `ty`.
+pub fn gen_column_constraints(expr_gen: &ExprGenerator, ty: ExprType, rng: &mut LcgRng) -> Vec<ColumnConstraint> {
+    let mut constraints = Vec::new();
+    if rand_below(rng, 5) == 0 {
+        constraints.push(ColumnConstraint::NotNull);
+    }
+    if rand_below(rng, 6) == 0 {
+        constraints.push(ColumnConstraint::Unique);
+    }
+    if rand_below(rng, 6) == 0 {
+        constraints.push(ColumnConstraint::Check(expr_gen.gen_predicate(rng)));
+    }
+    if ty == ExprType::Text && rand_below(rng, 4) == 0 {
+        let collation = [Collation::Binary, Collation::NoCase, Collation::RTrim][rand_below(rng, 3)];
+        constraints.push(ColumnConstraint::Collate(collation));
+    }
+    constraints
+}
+
+/// One or two distinct column indexes, in ascending order.
+fn pick_columns(column_count: usize, rng: &mut LcgRng) -> Vec<usize> {
+    let first = rand_below(rng, column_count);
+    let second = rand_below(rng, column_count);
+    if first == second || rand_below(rng, 2) == 0 {
+        vec![first]
+    } else {
+        vec![first.min(second), first.max(second)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::{Dialect, Stmt};
+    use crate::generators::common::create_index_stmt_common::gen_create_index_stmt;
+    use sqlsmith_rs_drivers::TableSchema;
+
+    #[test]
+    fn test_generated_tables_and_indexes_are_valid_sqlite() {
+        let mut rng = LcgRng::new(11);
+        for _ in 0..300 {
+            let conn = rusqlite::Connection::open_in_memory().unwrap();
+            let create = gen_create_table_stmt(&[], &mut rng).unwrap();
+            let table = TableSchema {
+                name: create.name.clone(),
+                columns: create.columns.iter().map(|c| (c.name.clone(), c.type_name.clone())).collect(),
+                primary_key: vec![],
+                without_rowid: create.without_rowid,
+            };
+            let sql = Stmt::CreateTable(create).to_sql(Dialect::Sqlite);
+            if let Err(e) = conn.execute_batch(&sql) {
+                panic!("invalid SQL generated: {} ({})", sql, e);
+            }
+            let index = gen_create_index_stmt(&[table], &mut rng).unwrap();
+            let sql = Stmt::CreateIndex(index).to_sql(Dialect::Sqlite);
+            if let Err(e) = conn.execute_batch(&sql) {
+                panic!("invalid SQL generated: {} ({})", sql, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_tables_avoid_existing_names() {
+        let existing: Vec<String> = (0..100).map(|n| format!("tbl_{}", n)).collect();
+        let create = gen_create_table_stmt(&existing, &mut LcgRng::new(5)).unwrap();
+        assert!(!existing.contains(&create.name), "{} already exists", create.name);
+    }
+}
@@ -1,58 +1,208 @@
 // Common CREATE TRIGGER statement generation logic, shared between limbo/sqlite drivers
+use crate::generators::ast::{
+    CreateTriggerStmt, Expr, InsertSource, RaiseAction, SelectCore, SelectItem, SelectStmt, Stmt, TriggerEvent, TriggerTiming,
+};
+use crate::generators::common::delete_stmt_common::gen_delete_stmt;
+use crate::generators::common::expr_common::{ExprGenerator, OuterScope};
+use crate::generators::common::insert_stmt_common::gen_insert_stmt;
+use crate::generators::common::update_stmt_common::gen_update_stmt;
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::TableSchema;
 
-/// Trait defining required table/trigger metadata for trigger generation
-pub trait TriggerTableLike {
-    fn name(&self) -> &str;
-    fn columns(&self) -> Vec<(String, String)>; // (column name, type)
-    fn has_primary_key(&self) -> bool;
-}
+/// WHEN conditions and RAISE guards stay shallow.
+const TRIGGER_EXPR_DEPTH: usize = 2;
 
-/// Generates a CREATE TRIGGER statement
+/// Generates a CREATE TRIGGER statement whose body reads `NEW.`/`OLD.` columns
 /// # Arguments
-/// * `tables` - List of available tables to reference in the trigger
+/// * `tables` - Tables to attach BEFORE/AFTER triggers to, and that the body modifies
+/// * `views` - Views to attach INSTEAD OF triggers to
 /// * `rng` - Random number generator for value selection
-pub fn gen_create_trigger_stmt<T: TriggerTableLike>(
-    tables: &[T],
+pub fn gen_create_trigger_stmt(
+    tables: &[TableSchema],
+    views: &[TableSchema],
     rng: &mut LcgRng,
-) -> Option<String> {
+) -> Option<CreateTriggerStmt> {
     if tables.is_empty() {
         return None;
     }
 
-    // Select random table to attach trigger to
-    let table_idx = (rng.rand().unsigned_abs() as usize) % tables.len();
-    let table = &tables[table_idx];
-
-    // Randomly choose trigger timing (BEFORE/AFTER)
-    let timing = if rng.rand().unsigned_abs() % 2 == 0 {
-        "BEFORE"
+    // Select a view for an INSTEAD OF trigger, or a table for a BEFORE/AFTER one
+    let (target, timing) = if !views.is_empty() && rng.rand().unsigned_abs().is_multiple_of(4) {
+        (&views[(rng.rand().unsigned_abs() as usize) % views.len()], TriggerTiming::InsteadOf)
     } else {
-        "AFTER"
+        let timing = if rng.rand().unsigned_abs().is_multiple_of(2) {
+            TriggerTiming::Before
+        } else {
+            TriggerTiming::After
+        };
+        (&tables[(rng.rand().unsigned_abs() as usize) % tables.len()], timing)
     };
+    let columns = &target.columns;
 
-    // Randomly choose trigger event (INSERT/UPDATE/DELETE)
-    let event = match rng.rand().unsigned_abs() % 3 {
-        0 => "INSERT",
-        1 => "UPDATE",
-        _ => "DELETE",
+    // Randomly choose trigger event; NEW is visible for INSERT/UPDATE, OLD for UPDATE/DELETE
+    let (event, outer) = match rng.rand().unsigned_abs() % 3 {
+        0 => (TriggerEvent::Insert, OuterScope::new().with("NEW", columns)),
+        1 => {
+            let of_columns = if !columns.is_empty() && rng.rand().unsigned_abs().is_multiple_of(2) {
+                // UPDATE OF one or two neighbouring columns
+                let count = (rng.rand().unsigned_abs() as usize % columns.len().min(2)) + 1;
+                let start = rng.rand().unsigned_abs() as usize % columns.len();
+                (0..count).map(|i| columns[(start + i) % columns.len()].0.clone()).collect()
+            } else {
+                vec![]
+            };
+            let outer = OuterScope::new().with("NEW", columns).with("OLD", columns);
+            (TriggerEvent::Update { columns: of_columns }, outer)
+        }
+        _ => (TriggerEvent::Delete, OuterScope::new().with("OLD", columns)),
     };
+    let expr_gen = ExprGenerator::new(TRIGGER_EXPR_DEPTH).with_outer(&outer);
 
-    // Simple trigger body example: log operation
-    let trigger_body = format!(
-        "BEGIN\n    -- Example trigger action\n    INSERT INTO trigger_log (operation, table_name) VALUES ('{}', '{}');\nEND",
-        event,
-        table.name()
-    );
-
-    Some(format!(
-        "CREATE TRIGGER IF NOT EXISTS trig_{}_{}_{}\n{} {} ON {}\n{}",
-        table.name(),
-        timing.to_lowercase(),
-        event.to_lowercase(),
+    // Add optional WHEN condition
+    let when = if rng.rand().unsigned_abs().is_multiple_of(3) {
+        Some(expr_gen.gen_predicate(rng))
+    } else {
+        None
+    };
+
+    let body: Vec<Stmt> = (0..(rng.rand().unsigned_abs() % 3) + 1)
+        .filter_map(|_| gen_body_stmt(tables, &outer, &expr_gen, rng))
+        .collect();
+    if body.is_empty() {
+        return None;
+    }
+
+    let timing_name = match timing {
+        TriggerTiming::Before => "before",
+        TriggerTiming::After => "after",
+        TriggerTiming::InsteadOf => "instead",
+    };
+    let event_name = match event {
+        TriggerEvent::Insert => "insert",
+        TriggerEvent::Update { .. } => "update",
+        TriggerEvent::Delete => "delete",
+    };
+    Some(CreateTriggerStmt {
+        if_not_exists: true,
+        name: format!(
+            "trig_{}_{}_{}_{}",
+            target.name,
+            timing_name,
+            event_name,
+            rng.rand().unsigned_abs() % 100
+        ),
         timing,
         event,
-        table.name(),
-        trigger_body
-    ))
+        table: target.name.clone(),
+        when,
+        body,
+    })
+}
+
+/// One statement of a trigger body. Trigger bodies allow neither DEFAULT VALUES, RETURNING
+/// nor LIMIT on UPDATE/DELETE, so those are left out.
+fn gen_body_stmt(
+    tables: &[TableSchema],
+    outer: &OuterScope,
+    expr_gen: &ExprGenerator,
+    rng: &mut LcgRng,
+) -> Option<Stmt> {
+    match rng.rand().unsigned_abs() % 4 {
+        0 => gen_insert_stmt(tables, outer, rng)
+            .filter(|insert| insert.source != InsertSource::DefaultValues)
+            .map(Stmt::Insert),
+        1 => gen_update_stmt(tables, outer, rng).map(|mut update| {
+            update.limit = None;
+            Stmt::Update(update)
+        }),
+        2 => gen_delete_stmt(tables, outer, rng).map(|mut delete| {
+            delete.limit = None;
+            delete.returning.clear();
+            Stmt::Delete(delete)
+        }),
+        _ => {
+            // SELECT RAISE(...) WHERE <condition>
+            let action = match rng.rand().unsigned_abs() % 4 {
+                0 => RaiseAction::Ignore,
+                1 => RaiseAction::Rollback,
+                2 => RaiseAction::Abort,
+                _ => RaiseAction::Fail,
+            };
+            let message = (action != RaiseAction::Ignore).then(|| format!("raised {}", rng.rand().unsigned_abs() % 100));
+            Some(Stmt::Select(SelectStmt::new(SelectCore {
+                distinct: false,
+                columns: vec![SelectItem {
+                    expr: Expr::Raise { action, message },
+                    alias: None,
+                }],
+                from: None,
+                where_clause: Some(expr_gen.gen_predicate(rng)),
+                group_by: vec![],
+                having: None,
+                windows: vec![],
+            })))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::Dialect;
+
+    fn schema(name: &str, columns: &[(&str, &str)]) -> TableSchema {
+        TableSchema {
+            name: name.to_string(),
+            columns: columns.iter().map(|(n, t)| (n.to_string(), t.to_string())).collect(),
+            primary_key: vec![],
+            without_rowid: false,
+        }
+    }
+
+    #[test]
+    fn test_generated_triggers_fire_without_errors() {
+        let tables = [
+            schema("item", &[("i_id", "INTEGER"), ("i_name", "TEXT"), ("i_price", "REAL")]),
+            schema("stock", &[("s_i_id", "INTEGER"), ("s_quantity", "INTEGER"), ("s_data", "TEXT")]),
+        ];
+        let views = [schema("item_view", &[("v0", "INTEGER"), ("v1", "TEXT")])];
+        let mut rng = LcgRng::new(5);
+
+        for _ in 0..300 {
+            let conn = rusqlite::Connection::open_in_memory().unwrap();
+            conn.execute_batch(
+                "CREATE TABLE item (i_id INTEGER, i_name TEXT, i_price REAL);
+                 CREATE TABLE stock (s_i_id INTEGER, s_quantity INTEGER, s_data TEXT);
+                 CREATE VIEW item_view(v0, v1) AS SELECT i_id, i_name FROM item;
+                 INSERT INTO item VALUES (1, 'a', 1.5), (2, 'b', 2.5);
+                 INSERT INTO stock VALUES (1, 10, 'x'), (2, 20, 'y');",
+            )
+            .unwrap();
+            let Some(trigger) = gen_create_trigger_stmt(&tables, &views, &mut rng) else {
+                continue;
+            };
+            let target = tables.iter().chain(&views).find(|t| t.name == trigger.table).unwrap();
+            let fire = match &trigger.event {
+                TriggerEvent::Insert => {
+                    let values = vec!["1"; target.columns.len()].join(", ");
+                    format!("INSERT INTO {} VALUES ({});", target.name, values)
+                }
+                TriggerEvent::Update { .. } => {
+                    let set: Vec<String> = target.columns.iter().map(|(c, _)| format!("{} = {}", c, c)).collect();
+                    format!("UPDATE {} SET {};", target.name, set.join(", "))
+                }
+                TriggerEvent::Delete => format!("DELETE FROM {};", target.name),
+            };
+            let sql = Stmt::CreateTrigger(trigger).to_sql(Dialect::Sqlite);
+            if let Err(e) = conn.execute_batch(&sql) {
+                panic!("invalid SQL generated: {} ({})", sql, e);
+            }
+            // RAISE and constraint failures are expected, anything else means a broken body
+            if let Err(e) = conn.execute_batch(&fire)
+                && e.sqlite_error_code() != Some(rusqlite::ErrorCode::ConstraintViolation)
+            {
+                panic!("trigger failed to fire: {} ({})", sql, e);
+            }
+        }
+    }
 }
@@ -0,0 +1,21 @@
+// Common CREATE VIEW statement generation logic, shared between limbo/sqlite drivers
+use crate::generators::ast::CreateViewStmt;
+use crate::generators::common::select_stmt_common::{TableLike, gen_select_stmt};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+
+/// Generates a CREATE VIEW statement over a random query
+/// # Arguments
+/// * `relations` - Tables and views the query may read
+/// * `max_depth` - Subquery nesting allowed in the query
+/// * `rng` - Random number generator for value selection
+pub fn gen_create_view_stmt<T: TableLike>(relations: &[T], max_depth: usize, rng: &mut LcgRng) -> Option<CreateViewStmt> {
+    let query = gen_select_stmt(relations, max_depth, rng)?;
+    // Name the columns explicitly, as computed result columns have no usable name
+    let columns = (0..query.core.columns.len()).map(|idx| format!("v{}", idx)).collect();
+    Some(CreateViewStmt {
+        if_not_exists: rng.rand().unsigned_abs().is_multiple_of(2),
+        name: format!("view_{}", rng.rand().unsigned_abs() % 100),
+        columns,
+        query: Box::new(query),
+    })
+}
@@ -1,7 +1,10 @@
 use crate::generators::common::insert_stmt_common::TableColumnLike;
+use crate::generators::ast::{DeleteStmt, Expr};
+use crate::generators::common::expr_common::{DEFAULT_EXPR_DEPTH, ExprGenerator, OuterScope};
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
 
-pub fn gen_delete_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Option<String> {
+/// Generates a DELETE whose WHERE clause may also use the columns of `outer`.
+pub fn gen_delete_stmt<T: TableColumnLike>(tables: &[T], outer: &OuterScope, rng: &mut LcgRng) -> Option<DeleteStmt> {
     if tables.is_empty() {
         return None;
     }
@@ -17,60 +20,36 @@ pub fn gen_delete_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Op
 
     // Generate WHERE clause
     let where_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        columns
-            .iter()
-            .take(1) // Use first column for WHERE condition
-            .map(|(name, _)| {
-                format!(
-                    "{} = {}",
-                    name,
-                    match rng.rand().abs() % 4 {
-                        0 => (rng.rand().abs() % 1000).to_string(),
-                        1 => format!("'val{}'", rng.rand().abs() % 1000),
-                        2 => "NULL".to_string(),
-                        _ => "1".to_string(),
-                    }
-                )
-            })
-            .collect::<Vec<_>>()
-            .join(" AND ")
+        let expr_gen = ExprGenerator::new(DEFAULT_EXPR_DEPTH).with_columns(None, &columns).with_outer(outer);
+        Some(expr_gen.gen_predicate(rng))
     } else {
-        String::new()
+        None
     };
 
     // Generate LIMIT clause
-    let limit_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let limit = (rng.rand().unsigned_abs() % 100).to_string(); // Random limit
-        format!("LIMIT {}", limit)
+    let limit = if rng.rand().unsigned_abs() % 2 == 0 && !table.without_rowid() {
+        Some(rng.rand().unsigned_abs() % 100) // Random limit
     } else {
-        String::new()
+        None
     };
 
     // Generate RETURNING clause
-    let returning_clause = if rng.rand().unsigned_abs() % 2 == 0 {
+    let returning = if rng.rand().unsigned_abs() % 2 == 0 {
         let col_count = ((rng.rand().unsigned_abs() as usize) % columns.len()) + 1;
         let mut selected_cols = columns.clone();
         for i in (1..selected_cols.len()).rev() {
             let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
             selected_cols.swap(i, j);
         }
-        let selected_cols = &selected_cols[..col_count];
-        let col_names: Vec<&str> = selected_cols.iter().map(|(name, _)| name.as_str()).collect();
-        format!("RETURNING {}", col_names.join(", "))
+        selected_cols[..col_count].iter().map(|(name, _)| Expr::column(name)).collect()
     } else {
-        String::new()
+        vec![]
     };
 
-    // Combine all parts into a full DELETE statement
-    Some(format!(
-        "DELETE FROM {} {} {} {};",
-        table.name(),
-        if !where_clause.is_empty() {
-            format!("WHERE {}", where_clause)
-        } else {
-            String::new()
-        },
-        limit_clause,
-        returning_clause
-    ))
+    Some(DeleteStmt {
+        table: table.name().to_string(),
+        where_clause,
+        limit,
+        returning,
+    })
 }
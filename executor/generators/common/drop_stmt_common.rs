@@ -0,0 +1,20 @@
+// Common DROP TABLE / INDEX / VIEW statement generation logic
+use crate::generators::ast::{DropStmt, ObjectKind};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+
+/// Generates a DROP statement for one of the existing objects in `names`
+/// # Arguments
+/// * `kind` - Kind of object being dropped
+/// * `names` - Names of the existing objects of that kind
+/// * `rng` - Random number generator for value selection
+pub fn gen_drop_stmt(kind: ObjectKind, names: &[impl AsRef<str>], rng: &mut LcgRng) -> Option<DropStmt> {
+    if names.is_empty() {
+        return None;
+    }
+    let name = names[(rng.rand().unsigned_abs() as usize) % names.len()].as_ref();
+    Some(DropStmt {
+        kind,
+        if_exists: rng.rand().unsigned_abs().is_multiple_of(2),
+        name: name.to_string(),
+    })
+}
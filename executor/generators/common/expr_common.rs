@@ -0,0 +1,610 @@
+// Recursive, type-aware expression generation shared by all statement generators
+use crate::generators::ast::{
+    BinaryOp, Collation, ColumnRef, Expr, FrameBound, FrameSpec, FrameUnits, FunctionCall, LikeOp, Literal,
+    OrderingTerm, Over, SelectStmt, UnaryOp, WindowDef,
+};
+use crate::generators::common::data_type::generate_value_by_type;
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+
+/// How deep generated expressions may nest unless a caller asks otherwise.
+pub const DEFAULT_EXPR_DEPTH: usize = 3;
+
+/// The kind of value an expression is generated to produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprType {
+    Integer,
+    Real,
+    Text,
+    Blob,
+    Numeric,
+    /// A truth value, i.e. a predicate
+    Bool,
+}
+
+const VALUE_TYPES: &[ExprType] = &[
+    ExprType::Integer,
+    ExprType::Real,
+    ExprType::Text,
+    ExprType::Blob,
+    ExprType::Numeric,
+];
+
+impl ExprType {
+    /// Maps a declared column type to its affinity, following SQLite's rules.
+    pub fn from_decl(decl: &str) -> Self {
+        let decl = decl.to_uppercase();
+        if decl.contains("INT") {
+            ExprType::Integer
+        } else if decl.contains("CHAR") || decl.contains("CLOB") || decl.contains("TEXT") {
+            ExprType::Text
+        } else if decl.contains("BLOB") || decl.is_empty() {
+            ExprType::Blob
+        } else if decl.contains("REAL") || decl.contains("FLOA") || decl.contains("DOUB") {
+            ExprType::Real
+        } else {
+            ExprType::Numeric
+        }
+    }
+
+    /// A random value type (never `Bool`).
+    pub fn random(rng: &mut LcgRng) -> Self {
+        VALUE_TYPES[rand_below(rng, VALUE_TYPES.len())]
+    }
+
+    /// Type name usable in `CAST` and by `generate_value_by_type`.
+    pub fn sql_name(self) -> &'static str {
+        match self {
+            ExprType::Integer | ExprType::Bool => "INTEGER",
+            ExprType::Real => "REAL",
+            ExprType::Text => "TEXT",
+            ExprType::Blob => "BLOB",
+            ExprType::Numeric => "NUMERIC",
+        }
+    }
+}
+
+/// Deterministic scalar functions: (name, result type, argument types; `None` accepts any type).
+const FUNCTIONS: &[(&str, ExprType, &[Option<ExprType>])] = &[
+    ("abs", ExprType::Integer, &[Some(ExprType::Integer)]),
+    ("length", ExprType::Integer, &[Some(ExprType::Text)]),
+    ("instr", ExprType::Integer, &[Some(ExprType::Text), Some(ExprType::Text)]),
+    ("unicode", ExprType::Integer, &[Some(ExprType::Text)]),
+    ("max", ExprType::Integer, &[Some(ExprType::Integer), Some(ExprType::Integer)]),
+    ("min", ExprType::Integer, &[Some(ExprType::Integer), Some(ExprType::Integer)]),
+    ("coalesce", ExprType::Integer, &[Some(ExprType::Integer), Some(ExprType::Integer)]),
+    ("nullif", ExprType::Integer, &[Some(ExprType::Integer), Some(ExprType::Integer)]),
+    ("abs", ExprType::Real, &[Some(ExprType::Real)]),
+    ("round", ExprType::Real, &[Some(ExprType::Real)]),
+    ("max", ExprType::Real, &[Some(ExprType::Real), Some(ExprType::Real)]),
+    ("min", ExprType::Real, &[Some(ExprType::Real), Some(ExprType::Real)]),
+    ("ifnull", ExprType::Real, &[Some(ExprType::Real), Some(ExprType::Real)]),
+    ("lower", ExprType::Text, &[Some(ExprType::Text)]),
+    ("upper", ExprType::Text, &[Some(ExprType::Text)]),
+    ("trim", ExprType::Text, &[Some(ExprType::Text)]),
+    ("ltrim", ExprType::Text, &[Some(ExprType::Text)]),
+    ("rtrim", ExprType::Text, &[Some(ExprType::Text)]),
+    ("substr", ExprType::Text, &[Some(ExprType::Text), Some(ExprType::Integer), Some(ExprType::Integer)]),
+    ("replace", ExprType::Text, &[Some(ExprType::Text), Some(ExprType::Text), Some(ExprType::Text)]),
+    ("hex", ExprType::Text, &[None]),
+    ("quote", ExprType::Text, &[None]),
+    ("typeof", ExprType::Text, &[None]),
+    ("ifnull", ExprType::Text, &[Some(ExprType::Text), Some(ExprType::Text)]),
+    ("coalesce", ExprType::Blob, &[Some(ExprType::Blob), Some(ExprType::Blob)]),
+];
+
+/// Ranking and navigation window functions; aggregates can be used as window functions too.
+const WINDOW_FUNCTIONS: &[&str] = &[
+    "row_number",
+    "rank",
+    "dense_rank",
+    "percent_rank",
+    "cume_dist",
+    "ntile",
+    "lag",
+    "lead",
+    "first_value",
+    "last_value",
+    "nth_value",
+];
+
+const COMPARISONS: &[BinaryOp] = &[
+    BinaryOp::Eq,
+    BinaryOp::NotEq,
+    BinaryOp::Lt,
+    BinaryOp::LtEq,
+    BinaryOp::Gt,
+    BinaryOp::GtEq,
+    BinaryOp::Is,
+    BinaryOp::IsNot,
+];
+
+fn rand_below(rng: &mut LcgRng, n: usize) -> usize {
+    (rng.rand().unsigned_abs() as usize) % n
+}
+
+fn small_int(rng: &mut LcgRng, low: i64, high: i64) -> Expr {
+    Expr::Literal(Literal::Integer(low + rand_below(rng, (high - low + 1) as usize) as i64))
+}
+
+/// Columns a statement may reference from its surroundings, such as `NEW.`/`OLD.` inside a
+/// trigger body, each group under its qualifier.
+#[derive(Debug, Clone, Default)]
+pub struct OuterScope {
+    groups: Vec<(String, Vec<(String, String)>)>, // (qualifier, [(name, type)])
+}
+
+impl OuterScope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the `(name, declared type)` columns reachable as `qualifier.name`.
+    pub fn with(mut self, qualifier: &str, columns: &[(String, String)]) -> Self {
+        self.groups.push((qualifier.to_string(), columns.to_vec()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.iter().all(|(_, columns)| columns.is_empty())
+    }
+}
+
+/// Supplies nested queries for subquery expressions.
+pub trait SubqueryGen {
+    /// A query returning a single column of `ty`, or any number of columns when `ty` is `None`.
+    fn gen_subquery(&self, ty: Option<ExprType>, rng: &mut LcgRng) -> Option<SelectStmt>;
+}
+
+/// Generates expressions over a fixed set of typed columns.
+pub struct ExprGenerator<'a> {
+    columns: Vec<(ColumnRef, ExprType)>,
+    max_depth: usize,
+    subqueries: Option<&'a dyn SubqueryGen>,
+}
+
+impl<'a> ExprGenerator<'a> {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            columns: Vec::new(),
+            max_depth,
+            subqueries: None,
+        }
+    }
+
+    /// Lets expressions embed scalar, `EXISTS` and `IN` subqueries produced by `subqueries`.
+    pub fn with_subqueries(mut self, subqueries: &'a dyn SubqueryGen) -> Self {
+        self.subqueries = Some(subqueries);
+        self
+    }
+
+    /// Makes the `(name, declared type)` columns of a table available, optionally qualified.
+    pub fn with_columns(mut self, qualifier: Option<&str>, columns: &[(String, String)]) -> Self {
+        self.columns.extend(columns.iter().map(|(name, ty)| {
+            let col = ColumnRef {
+                table: qualifier.map(str::to_string),
+                name: name.clone(),
+            };
+            (col, ExprType::from_decl(ty))
+        }));
+        self
+    }
+
+    /// Makes the columns of an enclosing scope available.
+    pub fn with_outer(self, outer: &OuterScope) -> Self {
+        outer
+            .groups
+            .iter()
+            .fold(self, |expr_gen, (qualifier, columns)| expr_gen.with_columns(Some(qualifier), columns))
+    }
+
+    /// A random value expression of random type.
+    pub fn gen_any(&self, rng: &mut LcgRng) -> Expr {
+        let ty = ExprType::random(rng);
+        self.gen_expr(ty, rng)
+    }
+
+    /// A random expression producing `ty`.
+    pub fn gen_expr(&self, ty: ExprType, rng: &mut LcgRng) -> Expr {
+        self.expr_at(ty, 0, rng)
+    }
+
+    /// A random predicate, for WHERE and similar clauses.
+    pub fn gen_predicate(&self, rng: &mut LcgRng) -> Expr {
+        self.expr_at(ExprType::Bool, 0, rng)
+    }
+
+    /// An aggregate call producing `ty`, possibly `DISTINCT` and with a `FILTER` clause.
+    pub fn gen_aggregate(&self, ty: ExprType, rng: &mut LcgRng) -> Expr {
+        let mut call = self.aggregate_call(ty, rng);
+        // DISTINCT aggregates take exactly one argument
+        if call.args.len() == 1 && rand_below(rng, 5) == 0 {
+            call.distinct = true;
+        }
+        if rand_below(rng, 5) == 0 {
+            call.filter = Some(Box::new(self.predicate_at(self.max_depth.saturating_sub(1), rng)));
+        }
+        Expr::function(call)
+    }
+
+    /// A predicate over aggregates, for the HAVING clause of a grouped query.
+    pub fn gen_having(&self, rng: &mut LcgRng) -> Expr {
+        let ty = ExprType::random(rng);
+        let op = COMPARISONS[rand_below(rng, COMPARISONS.len())];
+        let cond = Expr::binary(op, self.gen_aggregate(ty, rng), self.leaf(ty, rng));
+        match rand_below(rng, 4) {
+            0 => Expr::binary(BinaryOp::And, cond, self.gen_having(rng)),
+            1 => Expr::binary(BinaryOp::Or, cond, self.gen_having(rng)),
+            _ => cond,
+        }
+    }
+
+    /// A window specification: partitioning, ordering and an optional frame.
+    pub fn gen_window_def(&self, rng: &mut LcgRng) -> WindowDef {
+        let partition_by = (0..rand_below(rng, 3)).map(|_| self.gen_any(rng)).collect();
+        let order_by: Vec<OrderingTerm> = (0..rand_below(rng, 3))
+            .map(|_| OrderingTerm {
+                expr: self.gen_any(rng),
+                desc: rand_below(rng, 2) == 0,
+            })
+            .collect();
+        let frame = (rand_below(rng, 2) == 0).then(|| self.frame_spec(!order_by.is_empty(), rng));
+        WindowDef {
+            partition_by,
+            order_by,
+            frame,
+        }
+    }
+
+    /// A window function call over `over`, together with the type it produces.
+    pub fn gen_window_function(&self, over: Over, rng: &mut LcgRng) -> (Expr, ExprType) {
+        let pick = rand_below(rng, WINDOW_FUNCTIONS.len() + 3);
+        let Some(name) = WINDOW_FUNCTIONS.get(pick) else {
+            let ty = ExprType::random(rng);
+            let mut call = self.aggregate_call(ty, rng);
+            call.over = Some(over);
+            return (Expr::function(call), ty);
+        };
+        let value_ty = ExprType::random(rng);
+        let depth = self.max_depth.saturating_sub(1);
+        let (args, ty) = match *name {
+            "percent_rank" | "cume_dist" => (vec![], ExprType::Real),
+            "ntile" => (vec![small_int(rng, 1, 5)], ExprType::Integer),
+            "lag" | "lead" => {
+                let mut args = vec![self.expr_at(value_ty, depth, rng)];
+                if rand_below(rng, 2) == 0 {
+                    args.push(small_int(rng, 0, 3));
+                    if rand_below(rng, 2) == 0 {
+                        args.push(self.leaf(value_ty, rng));
+                    }
+                }
+                (args, value_ty)
+            }
+            "first_value" | "last_value" => (vec![self.expr_at(value_ty, depth, rng)], value_ty),
+            "nth_value" => {
+                let arg = self.expr_at(value_ty, depth, rng);
+                (vec![arg, small_int(rng, 1, 3)], value_ty)
+            }
+            _ => (vec![], ExprType::Integer),
+        };
+        let mut call = FunctionCall::new(name, args);
+        call.over = Some(over);
+        (Expr::function(call), ty)
+    }
+
+    fn aggregate_call(&self, ty: ExprType, rng: &mut LcgRng) -> FunctionCall {
+        let depth = self.max_depth.saturating_sub(1);
+        match ty {
+            ExprType::Integer if rand_below(rng, 2) == 0 => {
+                // count(*) or count(expr)
+                let args = if rand_below(rng, 2) == 0 { vec![] } else { vec![self.any_at(depth, rng)] };
+                FunctionCall::new("count", args)
+            }
+            ExprType::Real if rand_below(rng, 2) == 0 => {
+                let name = if rand_below(rng, 2) == 0 { "avg" } else { "total" };
+                let arg_ty = [ExprType::Integer, ExprType::Real, ExprType::Numeric][rand_below(rng, 3)];
+                FunctionCall::new(name, vec![self.expr_at(arg_ty, depth, rng)])
+            }
+            ExprType::Integer | ExprType::Real | ExprType::Numeric if rand_below(rng, 2) == 0 => {
+                FunctionCall::new("sum", vec![self.expr_at(ty, depth, rng)])
+            }
+            ExprType::Text if rand_below(rng, 2) == 0 => {
+                let mut args = vec![self.expr_at(ty, depth, rng)];
+                if rand_below(rng, 2) == 0 {
+                    args.push(Expr::Literal(Literal::Text([",", "|", ""][rand_below(rng, 3)].to_string())));
+                }
+                FunctionCall::new("group_concat", args)
+            }
+            _ => {
+                let name = if rand_below(rng, 2) == 0 { "min" } else { "max" };
+                FunctionCall::new(name, vec![self.expr_at(ty, depth, rng)])
+            }
+        }
+    }
+
+    /// A frame whose start never lies after its end. `RANGE` and `GROUPS` frames need an ORDER BY.
+    fn frame_spec(&self, ordered: bool, rng: &mut LcgRng) -> FrameSpec {
+        let units = if ordered {
+            [FrameUnits::Rows, FrameUnits::Range, FrameUnits::Groups][rand_below(rng, 3)]
+        } else {
+            FrameUnits::Rows
+        };
+        // RANGE offsets require a single numeric ORDER BY term; stick to unbounded/current row bounds
+        let offsets = units != FrameUnits::Range;
+        let bound = |rank: usize, rng: &mut LcgRng| match rank {
+            0 => FrameBound::UnboundedPreceding,
+            1 if offsets => FrameBound::Preceding(rand_below(rng, 3) as u64),
+            3 if offsets => FrameBound::Following(rand_below(rng, 3) as u64),
+            4 => FrameBound::UnboundedFollowing,
+            _ => FrameBound::CurrentRow,
+        };
+        let start_rank = rand_below(rng, 4);
+        let end_rank = start_rank.max(1) + rand_below(rng, 5 - start_rank.max(1));
+        let start = bound(start_rank, rng);
+        let end = bound(end_rank, rng);
+        FrameSpec { units, start, end }
+    }
+
+    fn expr_at(&self, ty: ExprType, depth: usize, rng: &mut LcgRng) -> Expr {
+        if ty == ExprType::Bool {
+            return self.predicate_at(depth, rng);
+        }
+        // Leaves become more likely the deeper we are
+        if depth >= self.max_depth || rand_below(rng, self.max_depth + 1) <= depth {
+            return self.leaf(ty, rng);
+        }
+        let depth = depth + 1;
+        match rand_below(rng, 7) {
+            0 => self.arithmetic(ty, depth, rng),
+            1 => self.function(ty, depth, rng).unwrap_or_else(|| self.leaf(ty, rng)),
+            2 => self.case(ty, depth, rng),
+            3 => Expr::Cast {
+                expr: Box::new(self.any_at(depth, rng)),
+                type_name: ty.sql_name().to_string(),
+            },
+            4 if ty == ExprType::Text => Expr::Collate {
+                expr: Box::new(self.expr_at(ty, depth, rng)),
+                collation: self.collation(rng),
+            },
+            6 => self
+                .subquery(Some(ty), rng)
+                .map(|query| Expr::Subquery(Box::new(query)))
+                .unwrap_or_else(|| self.leaf(ty, rng)),
+            _ => self.leaf(ty, rng),
+        }
+    }
+
+    fn subquery(&self, ty: Option<ExprType>, rng: &mut LcgRng) -> Option<SelectStmt> {
+        self.subqueries?.gen_subquery(ty, rng)
+    }
+
+    fn any_at(&self, depth: usize, rng: &mut LcgRng) -> Expr {
+        let ty = ExprType::random(rng);
+        self.expr_at(ty, depth, rng)
+    }
+
+    /// A column of the requested type when there is one, otherwise a literal.
+    fn leaf(&self, ty: ExprType, rng: &mut LcgRng) -> Expr {
+        let candidates: Vec<&ColumnRef> = self
+            .columns
+            .iter()
+            .filter(|(_, col_ty)| *col_ty == ty)
+            .map(|(col, _)| col)
+            .collect();
+        if !candidates.is_empty() && rand_below(rng, 3) != 0 {
+            return Expr::Column(candidates[rand_below(rng, candidates.len())].clone());
+        }
+        if rand_below(rng, 10) == 0 {
+            return Expr::Literal(Literal::Null);
+        }
+        Expr::Literal(generate_value_by_type(ty.sql_name(), rng))
+    }
+
+    fn arithmetic(&self, ty: ExprType, depth: usize, rng: &mut LcgRng) -> Expr {
+        match ty {
+            ExprType::Text => Expr::binary(
+                BinaryOp::Concat,
+                self.expr_at(ty, depth, rng),
+                self.expr_at(ty, depth, rng),
+            ),
+            ExprType::Integer => {
+                if rand_below(rng, 4) == 0 {
+                    let op = [UnaryOp::Neg, UnaryOp::Plus, UnaryOp::BitNot][rand_below(rng, 3)];
+                    return Expr::unary(op, self.expr_at(ty, depth, rng));
+                }
+                let op = [
+                    BinaryOp::Add,
+                    BinaryOp::Sub,
+                    BinaryOp::Mul,
+                    BinaryOp::Div,
+                    BinaryOp::Mod,
+                    BinaryOp::BitAnd,
+                    BinaryOp::BitOr,
+                    BinaryOp::ShiftLeft,
+                    BinaryOp::ShiftRight,
+                ][rand_below(rng, 9)];
+                Expr::binary(op, self.expr_at(ty, depth, rng), self.expr_at(ty, depth, rng))
+            }
+            ExprType::Real | ExprType::Numeric => {
+                if rand_below(rng, 4) == 0 {
+                    return Expr::unary(UnaryOp::Neg, self.expr_at(ty, depth, rng));
+                }
+                let op = [BinaryOp::Add, BinaryOp::Sub, BinaryOp::Mul, BinaryOp::Div][rand_below(rng, 4)];
+                Expr::binary(op, self.expr_at(ty, depth, rng), self.expr_at(ty, depth, rng))
+            }
+            ExprType::Blob | ExprType::Bool => self.leaf(ty, rng),
+        }
+    }
+
+    fn function(&self, ty: ExprType, depth: usize, rng: &mut LcgRng) -> Option<Expr> {
+        let candidates: Vec<_> = FUNCTIONS.iter().filter(|(_, ret, _)| *ret == ty).collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let (name, _, arg_types) = candidates[rand_below(rng, candidates.len())];
+        let args = arg_types
+            .iter()
+            .map(|arg_ty| match arg_ty {
+                Some(arg_ty) => self.expr_at(*arg_ty, depth, rng),
+                None => self.any_at(depth, rng),
+            })
+            .collect();
+        Some(Expr::function(FunctionCall::new(name, args)))
+    }
+
+    fn case(&self, ty: ExprType, depth: usize, rng: &mut LcgRng) -> Expr {
+        let branches = rand_below(rng, 3) + 1;
+        // Either a simple CASE comparing an operand, or a searched CASE over predicates
+        let operand_ty = ExprType::random(rng);
+        let simple = rand_below(rng, 2) == 0;
+        let operand = simple.then(|| Box::new(self.expr_at(operand_ty, depth, rng)));
+        let when_then = (0..branches)
+            .map(|_| {
+                let when = if simple {
+                    self.expr_at(operand_ty, depth, rng)
+                } else {
+                    self.predicate_at(depth, rng)
+                };
+                (when, self.expr_at(ty, depth, rng))
+            })
+            .collect();
+        let else_expr = (rand_below(rng, 2) == 0).then(|| Box::new(self.expr_at(ty, depth, rng)));
+        Expr::Case {
+            operand,
+            when_then,
+            else_expr,
+        }
+    }
+
+    fn collation(&self, rng: &mut LcgRng) -> Collation {
+        [Collation::Binary, Collation::NoCase, Collation::RTrim][rand_below(rng, 3)]
+    }
+
+    fn predicate_at(&self, depth: usize, rng: &mut LcgRng) -> Expr {
+        let at_limit = depth >= self.max_depth;
+        let depth = depth + 1;
+        let choice = if at_limit { rand_below(rng, 2) } else { rand_below(rng, 11) };
+        match choice {
+            0 => self.comparison(depth, rng),
+            1 => Expr::IsNull {
+                expr: Box::new(self.any_at(depth, rng)),
+                negated: rand_below(rng, 2) == 0,
+            },
+            2 => {
+                let ty = ExprType::random(rng);
+                Expr::Between {
+                    expr: Box::new(self.expr_at(ty, depth, rng)),
+                    negated: rand_below(rng, 4) == 0,
+                    low: Box::new(self.expr_at(ty, depth, rng)),
+                    high: Box::new(self.expr_at(ty, depth, rng)),
+                }
+            }
+            3 => {
+                let ty = ExprType::random(rng);
+                let len = rand_below(rng, 4) + 1;
+                Expr::InList {
+                    expr: Box::new(self.expr_at(ty, depth, rng)),
+                    negated: rand_below(rng, 4) == 0,
+                    list: (0..len).map(|_| self.expr_at(ty, depth, rng)).collect(),
+                }
+            }
+            4 => {
+                let op = if rand_below(rng, 2) == 0 { LikeOp::Like } else { LikeOp::Glob };
+                Expr::Like {
+                    expr: Box::new(self.expr_at(ExprType::Text, depth, rng)),
+                    negated: rand_below(rng, 4) == 0,
+                    op,
+                    pattern: Box::new(self.pattern(op, depth, rng)),
+                }
+            }
+            5 => Expr::unary(UnaryOp::Not, self.predicate_at(depth, rng)),
+            9 => match self.subquery(None, rng) {
+                Some(query) => Expr::Exists {
+                    negated: rand_below(rng, 4) == 0,
+                    subquery: Box::new(query),
+                },
+                None => self.comparison(depth, rng),
+            },
+            10 => {
+                let ty = ExprType::random(rng);
+                match self.subquery(Some(ty), rng) {
+                    Some(query) => Expr::InSubquery {
+                        expr: Box::new(self.expr_at(ty, depth, rng)),
+                        negated: rand_below(rng, 4) == 0,
+                        subquery: Box::new(query),
+                    },
+                    None => self.comparison(depth, rng),
+                }
+            }
+            6 | 7 => {
+                let op = if rand_below(rng, 2) == 0 { BinaryOp::And } else { BinaryOp::Or };
+                Expr::binary(op, self.predicate_at(depth, rng), self.predicate_at(depth, rng))
+            }
+            _ => self.comparison(depth, rng),
+        }
+    }
+
+    fn comparison(&self, depth: usize, rng: &mut LcgRng) -> Expr {
+        let ty = ExprType::random(rng);
+        let op = COMPARISONS[rand_below(rng, COMPARISONS.len())];
+        let left = self.expr_at(ty, depth, rng);
+        let mut right = self.expr_at(ty, depth, rng);
+        if ty == ExprType::Text && rand_below(rng, 4) == 0 {
+            right = Expr::Collate {
+                expr: Box::new(right),
+                collation: self.collation(rng),
+            };
+        }
+        Expr::binary(op, left, right)
+    }
+
+    /// A LIKE/GLOB pattern: usually a literal with wildcards, sometimes an arbitrary text expression.
+    fn pattern(&self, op: LikeOp, depth: usize, rng: &mut LcgRng) -> Expr {
+        if rand_below(rng, 4) == 0 {
+            return self.expr_at(ExprType::Text, depth, rng);
+        }
+        let (many, one) = match op {
+            LikeOp::Like => ("%", "_"),
+            LikeOp::Glob => ("*", "?"),
+        };
+        let pieces = ["val", "VAL", "1", "2", "a", many, many, one];
+        let len = rand_below(rng, 4) + 1;
+        let pattern = (0..len).map(|_| pieces[rand_below(rng, pieces.len())]).collect::<String>();
+        Expr::Literal(Literal::Text(pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::{Dialect, FromClause, SelectCore, SelectItem, SelectStmt, Stmt, TableRef};
+
+    #[test]
+    fn test_generated_expressions_are_valid_sqlite() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (a INTEGER, b REAL, c TEXT, d BLOB, e NUMERIC);")
+            .unwrap();
+        let columns: Vec<(String, String)> = [("a", "INTEGER"), ("b", "REAL"), ("c", "TEXT"), ("d", "BLOB"), ("e", "NUMERIC")]
+            .iter()
+            .map(|(n, t)| (n.to_string(), t.to_string()))
+            .collect();
+        let expr_gen = ExprGenerator::new(DEFAULT_EXPR_DEPTH).with_columns(Some("t"), &columns);
+        let mut rng = LcgRng::new(42);
+
+        for _ in 0..500 {
+            let stmt = Stmt::Select(SelectStmt::new(SelectCore {
+                distinct: false,
+                columns: vec![SelectItem {
+                    expr: expr_gen.gen_any(&mut rng),
+                    alias: None,
+                }],
+                from: Some(FromClause::new(TableRef::new("t"))),
+                where_clause: Some(expr_gen.gen_predicate(&mut rng)),
+                group_by: vec![],
+                having: None,
+                windows: vec![],
+            }));
+            let sql = stmt.to_sql(Dialect::Sqlite);
+            assert!(conn.prepare(&sql).is_ok(), "invalid SQL generated: {}", sql);
+        }
+    }
+}
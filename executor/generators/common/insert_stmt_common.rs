@@ -1,13 +1,33 @@
 // 通用 INSERT 语句生成逻辑，供 limbo/sqlite 共享
 use crate::generators::common::data_type::generate_value_by_type;
+use crate::generators::common::expr_common::{DEFAULT_EXPR_DEPTH, ExprGenerator, ExprType, OuterScope};
+use crate::generators::ast::{Expr, FromClause, InsertSource, InsertStmt, SelectCore, SelectItem, SelectStmt, TableRef};
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::TableSchema;
 
 pub trait TableColumnLike {
     fn name(&self) -> &str;
     fn columns(&self) -> Vec<(String, String)>; // (name, type)
+    /// Tables without rowid cannot take the rowid subquery that stands in for `LIMIT`
+    fn without_rowid(&self) -> bool {
+        false
+    }
+}
+
+impl TableColumnLike for TableSchema {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn columns(&self) -> Vec<(String, String)> {
+        self.columns.clone()
+    }
+    fn without_rowid(&self) -> bool {
+        self.without_rowid
+    }
 }
 
-pub fn gen_insert_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Option<String> {
+/// Generates an INSERT. Inserted values are literals, or expressions over `outer` when it has columns.
+pub fn gen_insert_stmt<T: TableColumnLike>(tables: &[T], outer: &OuterScope, rng: &mut LcgRng) -> Option<InsertStmt> {
     if tables.is_empty() {
         return None;
     }
@@ -26,31 +46,33 @@ pub fn gen_insert_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Op
     match insert_type {
         0 => {
             // INSERT INTO ... DEFAULT VALUES
-            Some(format!("INSERT INTO {} DEFAULT VALUES;", table.name()))
+            Some(InsertStmt {
+                table: table.name().to_string(),
+                columns: vec![],
+                source: InsertSource::DefaultValues,
+            })
         }
         1 => {
             // INSERT INTO ... VALUES (...)
             let col_count = ((rng.rand().unsigned_abs() as usize) % columns.len()) + 1;
-            let mut selected_cols = columns.clone();
-            for i in (1..selected_cols.len()).rev() {
-                let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
-                selected_cols.swap(i, j);
-            }
-            let selected_cols = &selected_cols[..col_count];
-            let col_names: Vec<&str> = selected_cols
-                .iter()
-                .map(|(name, _)| name.as_str())
-                .collect();
-            let values: Vec<String> = selected_cols
-                .iter()
-                .map(|(_, ty)| generate_value_by_type(ty, rng))
-                .collect();
-            Some(format!(
-                "INSERT INTO {} ({}) VALUES ({});",
-                table.name(),
-                col_names.join(", "),
-                values.join(", ")
-            ))
+            let selected_cols = shuffled_prefix(&columns, col_count, rng);
+            let values = if outer.is_empty() {
+                selected_cols
+                    .iter()
+                    .map(|(_, ty)| Expr::Literal(generate_value_by_type(ty, rng)))
+                    .collect()
+            } else {
+                let expr_gen = ExprGenerator::new(DEFAULT_EXPR_DEPTH).with_outer(outer);
+                selected_cols
+                    .iter()
+                    .map(|(_, ty)| expr_gen.gen_expr(ExprType::from_decl(ty), rng))
+                    .collect()
+            };
+            Some(InsertStmt {
+                table: table.name().to_string(),
+                columns: selected_cols.into_iter().map(|(name, _)| name).collect(),
+                source: InsertSource::Values(vec![values]),
+            })
         }
         2 => {
             // INSERT INTO ... SELECT ...
@@ -60,29 +82,30 @@ pub fn gen_insert_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Op
                 if other_table.name() != table.name() {
                     let other_columns = other_table.columns();
                     if !other_columns.is_empty() {
-                        let col_count = ((rng.rand().unsigned_abs() as usize) % columns.len()) + 1;
-                        let mut selected_cols = columns.clone();
-                        for i in (1..selected_cols.len()).rev() {
-                            let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
-                            selected_cols.swap(i, j);
-                        }
-                        let selected_cols = &selected_cols[..col_count];
-                        let col_names: Vec<&str> = selected_cols
-                            .iter()
-                            .map(|(name, _)| name.as_str())
-                            .collect();
-                        let other_col_names: Vec<&str> = other_columns
-                            .iter()
-                            .map(|(name, _)| name.as_str())
-                            .take(col_count)
-                            .collect();
-                        return Some(format!(
-                            "INSERT INTO {} ({}) SELECT {} FROM {};",
-                            table.name(),
-                            col_names.join(", "),
-                            other_col_names.join(", "),
-                            other_table.name()
-                        ));
+                        // Both sides need the same number of columns
+                        let col_count = ((rng.rand().unsigned_abs() as usize) % columns.len().min(other_columns.len())) + 1;
+                        let selected_cols = shuffled_prefix(&columns, col_count, rng);
+                        let select = SelectStmt::new(SelectCore {
+                            distinct: false,
+                            columns: other_columns
+                                .iter()
+                                .take(col_count)
+                                .map(|(name, _)| SelectItem {
+                                    expr: Expr::column(name),
+                                    alias: None,
+                                })
+                                .collect(),
+                            from: Some(FromClause::new(TableRef::new(other_table.name()))),
+                            where_clause: None,
+                            group_by: vec![],
+                            having: None,
+                            windows: vec![],
+                        });
+                        return Some(InsertStmt {
+                            table: table.name().to_string(),
+                            columns: selected_cols.into_iter().map(|(name, _)| name).collect(),
+                            source: InsertSource::Select(Box::new(select)),
+                        });
                     }
                 }
             }
@@ -91,3 +114,14 @@ pub fn gen_insert_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Op
         _ => None,
     }
 }
+
+/// Picks `count` columns in random order.
+fn shuffled_prefix(columns: &[(String, String)], count: usize, rng: &mut LcgRng) -> Vec<(String, String)> {
+    let mut selected_cols = columns.to_vec();
+    for i in (1..selected_cols.len()).rev() {
+        let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
+        selected_cols.swap(i, j);
+    }
+    selected_cols.truncate(count);
+    selected_cols
+}
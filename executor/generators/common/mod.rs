@@ -1,9 +1,15 @@
 // 仅用于公开 common 子模块
+pub mod alter_table_stmt_common;
+pub mod create_index_stmt_common;
+pub mod create_table_stmt_common;
 pub mod create_trigger_stmt_common;
+pub mod create_view_stmt_common;
 pub mod data_type;
 pub mod datefunc_stmt_common; // Added module for date functions
 pub mod delete_stmt_common;
+pub mod drop_stmt_common;
 pub mod drop_trigger_stmt_common; // New module declaration
+pub mod expr_common;
 pub mod insert_stmt_common;
 pub mod pragma_stmt_common;
 pub mod select_stmt_common;
@@ -22,71 +28,11 @@ pub enum SqlKind {
     CreateTrigger,
     DropTrigger,
     DateFunc, // Added DateFunc SqlKind
-}
-
-use sqlsmith_rs_common::rand_by_seed::LcgRng;
-
-pub enum DriverKind {
-    Sqlite,
-    Limbo,
-}
-
-// Helper function to handle driver-specific connection downcasting and stmt generation
-fn call_driver_get_stmt_by_seed(
-    driver_kind: DriverKind,
-    conn: &dyn std::any::Any,
-    rng: &mut LcgRng,
-    kind: SqlKind,
-) -> Option<String> {
-    match driver_kind {
-        DriverKind::Sqlite => {
-            if let Some(sqlite_conn) = conn.downcast_ref::<rusqlite::Connection>() {
-                crate::generators::sqlite::get_stmt_by_seed(sqlite_conn, rng, kind)
-            } else {
-                None
-            }
-        }
-        DriverKind::Limbo => {
-            if let Some(limbo_conn) = conn.downcast_ref::<limbo::Connection>() {
-                crate::generators::limbo::get_stmt_by_seed(limbo_conn, rng, kind)
-            } else {
-                None
-            }
-        }
-    }
-}
-
-pub fn gen_stmt(
-    sql_kind: SqlKind,
-    driver_kind: DriverKind,
-    conn: &dyn std::any::Any,
-    rng: &mut LcgRng,
-) -> Option<String> {
-    match sql_kind {
-        SqlKind::Select => call_driver_get_stmt_by_seed(driver_kind, conn, rng, SqlKind::Select),
-        SqlKind::Insert => call_driver_get_stmt_by_seed(driver_kind, conn, rng, SqlKind::Insert),
-        SqlKind::Update => call_driver_get_stmt_by_seed(driver_kind, conn, rng, SqlKind::Update),
-        SqlKind::Delete => call_driver_get_stmt_by_seed(driver_kind, conn, rng, SqlKind::Delete),
-        SqlKind::CreateTrigger => {
-            call_driver_get_stmt_by_seed(driver_kind, conn, rng, SqlKind::CreateTrigger)
-        }
-        SqlKind::DropTrigger => {
-            call_driver_get_stmt_by_seed(driver_kind, conn, rng, SqlKind::DropTrigger)
-        }
-        SqlKind::Vacuum => crate::generators::common::vacuum_stmt_common::gen_vacuum_stmt(),
-        SqlKind::Pragma => match driver_kind {
-            DriverKind::Sqlite => {
-                if let Some(sqlite_conn) = conn.downcast_ref::<rusqlite::Connection>() {
-                    crate::generators::common::pragma_stmt_common::get_pragma_stmt_by_seed(
-                        sqlite_conn,
-                        rng,
-                    )
-                } else {
-                    None
-                }
-            }
-            DriverKind::Limbo => None,
-        },
-        SqlKind::DateFunc => crate::generators::common::datefunc_stmt_common::gen_datefunc_stmt(rng),
-    }
+    CreateTable,
+    CreateIndex,
+    CreateView,
+    AlterTable,
+    DropTable,
+    DropIndex,
+    DropView,
 }
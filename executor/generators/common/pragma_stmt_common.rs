@@ -1,4 +1,3 @@
-use rusqlite::Connection;
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
 
 enum PragmaKind {
@@ -8,7 +7,7 @@ enum PragmaKind {
     StringArg(&'static str),
 }
 
-pub fn get_pragma_stmt_by_seed(_conn: &Connection, rng: &mut LcgRng) -> Option<String> {
+pub fn get_pragma_stmt_by_seed(rng: &mut LcgRng) -> Option<String> {
     use PragmaKind::*;
     const PRAGMAS: &[PragmaKind] = &[
         // No-argument pragmas
@@ -1,91 +1,642 @@
 // 通用 SELECT 语句生成逻辑，供 limbo/sqlite 共享
-// TableInfo: 需实现 name: &str, columns: &[String] trait
+// TableInfo: 需实现 name: &str, typed_columns: (name, type) trait
+use crate::generators::ast::{
+    BinaryOp, ColumnRef, CompoundOp, Cte, Expr, FromClause, Join, JoinConstraint, JoinKind, Literal, OrderingTerm,
+    Over, SelectCore, SelectItem, SelectStmt, TableOrSubquery, TableRef, UnaryOp, WithClause,
+};
+use crate::generators::common::expr_common::{DEFAULT_EXPR_DEPTH, ExprGenerator, ExprType, SubqueryGen};
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::TableSchema;
 
 pub trait TableLike {
     fn name(&self) -> &str;
-    fn columns(&self) -> Vec<String>;
+    fn typed_columns(&self) -> Vec<(String, String)>; // (name, type)
 }
 
-pub fn gen_select_stmt<T: TableLike>(tables: &[T], rng: &mut LcgRng) -> Option<String> {
-    if tables.is_empty() {
-        return None;
+impl TableLike for TableSchema {
+    fn name(&self) -> &str {
+        &self.name
     }
+    fn typed_columns(&self) -> Vec<(String, String)> {
+        self.columns.clone()
+    }
+}
+
+/// Something a query can read from: a base table, a CTE or a derived table.
+#[derive(Debug, Clone)]
+struct Relation {
+    name: String,
+    columns: Vec<(String, String)>, // (name, type)
+}
+
+/// A table in the FROM clause, with the name its columns are qualified by.
+struct ScopeTable {
+    qualifier: Option<String>,
+    columns: Vec<(String, String)>, // (name, type)
+}
 
-    // Select a random table
-    let table_idx = (rng.rand().unsigned_abs() as usize) % tables.len();
-    let table = &tables[table_idx];
-    let columns = table.columns();
-    if columns.is_empty() {
-        return None;
+impl ScopeTable {
+    fn column_ref(&self, name: &str) -> ColumnRef {
+        ColumnRef {
+            table: self.qualifier.clone(),
+            name: name.to_string(),
+        }
     }
+}
+
+/// Generates queries over `relations`. Subqueries, CTEs and derived tables nest at most
+/// `depth` further levels.
+struct QueryGen {
+    relations: Vec<Relation>,
+    depth: usize,
+}
+
+pub fn gen_select_stmt<T: TableLike>(tables: &[T], max_depth: usize, rng: &mut LcgRng) -> Option<SelectStmt> {
+    QueryGen::over(tables, max_depth)?.gen_query(None, false, rng).map(|(query, _)| query)
+}
 
-    // Select random columns
-    let col_count = ((rng.rand().unsigned_abs() as usize) % columns.len()) + 1;
-    let mut selected_cols = columns.clone();
-    for i in (1..selected_cols.len()).rev() {
-        let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
-        selected_cols.swap(i, j);
+/// Generates a single-core query without WHERE, grouping, windows, compound arms, ORDER BY
+/// or LIMIT, together with a predicate over its FROM clause. The logic oracles
+/// add the predicate in different ways and compare the results.
+pub fn gen_oracle_query<T: TableLike>(tables: &[T], max_depth: usize, rng: &mut LcgRng) -> Option<(SelectStmt, Expr)> {
+    let query_gen = QueryGen::over(tables, max_depth)?;
+    let (mut core, _, scope) = query_gen.gen_core(None, false, Some(CoreMode::Plain), rng)?;
+    core.distinct = false;
+    core.where_clause = None;
+
+    let nested = query_gen.nested();
+    let predicate = query_gen.expr_generator(&scope, nested.as_ref()).gen_predicate(rng);
+    Some((SelectStmt::new(core), predicate))
+}
+
+/// The kinds of SELECT core the generator produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoreMode {
+    Plain,
+    /// GROUP BY with aggregates, and possibly HAVING
+    Grouped,
+    /// Window functions over the plain columns
+    Windowed,
+}
+
+/// Name of the window defined in a query's WINDOW clause.
+const WINDOW_NAME: &str = "w0";
+
+/// Names result columns `c0`, `c1`, ... so enclosing queries can refer to them.
+fn result_column_name(idx: usize) -> String {
+    format!("c{}", idx)
+}
+
+impl SubqueryGen for QueryGen {
+    fn gen_subquery(&self, ty: Option<ExprType>, rng: &mut LcgRng) -> Option<SelectStmt> {
+        let shape = ty.map(|ty| vec![ty]);
+        self.gen_query(shape.as_deref(), false, rng).map(|(query, _)| query)
     }
-    let selected_cols = &selected_cols[..col_count];
+}
 
-    // Add optional DISTINCT
-    let distinct = if rng.rand().unsigned_abs() % 2 == 0 {
-        "DISTINCT "
-    } else {
-        ""
-    };
+impl QueryGen {
+    /// A generator over the non-empty `tables`, or `None` if there are none.
+    fn over<T: TableLike>(tables: &[T], depth: usize) -> Option<QueryGen> {
+        let relations: Vec<Relation> = tables
+            .iter()
+            .map(|t| Relation {
+                name: t.name().to_string(),
+                columns: t.typed_columns(),
+            })
+            .filter(|r| !r.columns.is_empty())
+            .collect();
+        (!relations.is_empty()).then_some(QueryGen { relations, depth })
+    }
 
-    // Add optional WHERE clause
-    let where_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let col_idx = (rng.rand().unsigned_abs() as usize) % columns.len();
-        let col = &columns[col_idx];
-        let value = (rng.rand().unsigned_abs() % 100).to_string(); // Random value
-        format!("WHERE {} = {}", col, value)
-    } else {
-        String::new()
-    };
+    /// The generator for queries nested one level deeper, if the depth budget allows.
+    fn nested(&self) -> Option<QueryGen> {
+        (self.depth > 0).then(|| QueryGen {
+            relations: self.relations.clone(),
+            depth: self.depth - 1,
+        })
+    }
 
-    // Add optional GROUP BY clause
-    let group_by_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let col_idx = (rng.rand().unsigned_abs() as usize) % columns.len();
-        let col = &columns[col_idx];
-        format!("GROUP BY {}", col)
-    } else {
-        String::new()
-    };
+    fn expr_generator<'a>(
+        &self,
+        scope: impl IntoIterator<Item = &'a ScopeTable>,
+        nested: Option<&'a QueryGen>,
+    ) -> ExprGenerator<'a> {
+        let expr_gen = scope.into_iter().fold(ExprGenerator::new(DEFAULT_EXPR_DEPTH), |expr_gen, t| {
+            expr_gen.with_columns(t.qualifier.as_deref(), &t.columns)
+        });
+        match nested {
+            Some(nested) => expr_gen.with_subqueries(nested),
+            None => expr_gen,
+        }
+    }
 
-    // Add optional ORDER BY clause
-    let order_by_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let col_idx = (rng.rand().unsigned_abs() as usize) % columns.len();
-        let col = &columns[col_idx];
-        let order = if rng.rand().unsigned_abs() % 2 == 0 {
-            "ASC"
+    /// Generates a full query and the types of its result columns. `shape` fixes the
+    /// result column types; `aliased` names the result columns `c0`, `c1`, ...
+    fn gen_query(&self, shape: Option<&[ExprType]>, aliased: bool, rng: &mut LcgRng) -> Option<(SelectStmt, Vec<ExprType>)> {
+        // Add optional CTEs, visible to the rest of this query
+        let mut relations = self.relations.clone();
+        let with = match self.nested() {
+            Some(nested) if rng.rand().unsigned_abs().is_multiple_of(5) => {
+                let (with, ctes) = nested.gen_with(self.depth, rng);
+                relations.extend(ctes);
+                Some(with)
+            }
+            _ => None,
+        };
+        let query_gen = QueryGen {
+            relations,
+            depth: self.depth,
+        };
+
+        let (core, types, scope) = query_gen.gen_core(shape, aliased, None, rng)?;
+
+        // Add optional compound arms with the same result types
+        let mut compounds = Vec::new();
+        if rng.rand().unsigned_abs().is_multiple_of(6) {
+            for _ in 0..(rng.rand().unsigned_abs() % 2) + 1 {
+                let op = match rng.rand().unsigned_abs() % 4 {
+                    0 => CompoundOp::Union,
+                    1 => CompoundOp::UnionAll,
+                    2 => CompoundOp::Intersect,
+                    _ => CompoundOp::Except,
+                };
+                let (arm, _, _) = query_gen.gen_core(Some(&types), false, None, rng)?;
+                compounds.push((op, arm));
+            }
+        }
+
+        // Add optional ORDER BY clause
+        let order_by = if rng.rand().unsigned_abs() % 2 == 0 {
+            let expr = if compounds.is_empty() {
+                let nested = query_gen.nested();
+                let expr_gen = query_gen.expr_generator(&scope, nested.as_ref());
+                let mut expr = expr_gen.gen_any(rng);
+                if is_signed_literal(&expr) {
+                    expr = random_column(&scope, rng);
+                }
+                expr
+            } else {
+                // Compound queries can only be ordered by result columns
+                Expr::Literal(Literal::Integer((rng.rand().unsigned_abs() as usize % types.len()) as i64 + 1))
+            };
+            vec![OrderingTerm {
+                expr,
+                desc: rng.rand().unsigned_abs() % 2 == 1,
+            }]
         } else {
-            "DESC"
+            vec![]
         };
-        format!("ORDER BY {} {}", col, order)
-    } else {
-        String::new()
-    };
 
-    // Add optional LIMIT clause
-    let limit_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let limit = (rng.rand().unsigned_abs() % 100).to_string(); // Random limit
-        format!("LIMIT {}", limit)
-    } else {
-        String::new()
+        // Add optional LIMIT clause
+        let limit = if rng.rand().unsigned_abs() % 2 == 0 {
+            Some(rng.rand().unsigned_abs() % 100) // Random limit
+        } else {
+            None
+        };
+
+        let query = SelectStmt {
+            with,
+            core,
+            compounds,
+            order_by,
+            limit,
+        };
+        Some((query, types))
+    }
+
+    /// Generates one or two CTEs for a query at `depth`; CTE names carry the depth so
+    /// nested WITH clauses never shadow the ones they can see.
+    fn gen_with(&self, depth: usize, rng: &mut LcgRng) -> (WithClause, Vec<Relation>) {
+        let mut ctes = Vec::new();
+        let mut relations = Vec::new();
+        let mut recursive = false;
+        for i in 0..(rng.rand().unsigned_abs() as usize % 2) + 1 {
+            let name = format!("cte{}_{}", depth, i);
+            if rng.rand().unsigned_abs().is_multiple_of(3) {
+                recursive = true;
+                ctes.push(counter_cte(&name, rng));
+                relations.push(Relation {
+                    name,
+                    columns: vec![(result_column_name(0), "INTEGER".to_string())],
+                });
+            } else if let Some((query, types)) = self.gen_query(None, true, rng) {
+                ctes.push(Cte {
+                    name: name.clone(),
+                    columns: vec![],
+                    query: Box::new(query),
+                });
+                relations.push(Relation {
+                    name,
+                    columns: types
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, ty)| (result_column_name(idx), ty.sql_name().to_string()))
+                        .collect(),
+                });
+            }
+        }
+        (WithClause { recursive, ctes }, relations)
+    }
+
+    /// Generates one SELECT core; `mode` is picked at random when not given.
+    fn gen_core(
+        &self,
+        shape: Option<&[ExprType]>,
+        aliased: bool,
+        mode: Option<CoreMode>,
+        rng: &mut LcgRng,
+    ) -> Option<(SelectCore, Vec<ExprType>, Vec<ScopeTable>)> {
+        let (from, scope) = self.gen_from_clause(rng)?;
+        let columns: Vec<(ColumnRef, ExprType)> = scope
+            .iter()
+            .flat_map(|t| t.columns.iter().map(|(name, ty)| (t.column_ref(name), ExprType::from_decl(ty))))
+            .collect();
+        if columns.is_empty() {
+            return None;
+        }
+
+        let nested = self.nested();
+        let expr_gen = self.expr_generator(&scope, nested.as_ref());
+
+        // Choose between a plain, a grouped (aggregate) and a windowed core
+        let mode = mode.unwrap_or_else(|| match rng.rand().unsigned_abs() % 8 {
+            0 | 1 => CoreMode::Grouped,
+            2 => CoreMode::Windowed,
+            _ => CoreMode::Plain,
+        });
+        let mut group_by = Vec::new();
+        let mut having = None;
+        let mut windows = Vec::new();
+        let items: Vec<(Expr, ExprType)> = if mode == CoreMode::Grouped {
+            // Every result column is either a grouping column or an aggregate
+            let grouped: Vec<(ColumnRef, ExprType)> = (0..rng.rand().unsigned_abs() as usize % 3)
+                .map(|_| columns[(rng.rand().unsigned_abs() as usize) % columns.len()].clone())
+                .collect();
+            group_by = grouped.iter().map(|(col, _)| Expr::Column(col.clone())).collect();
+            if rng.rand().unsigned_abs().is_multiple_of(2) {
+                having = Some(expr_gen.gen_having(rng));
+            }
+            match shape {
+                Some(types) => types
+                    .iter()
+                    .map(|ty| {
+                        let same_type: Vec<&ColumnRef> =
+                            grouped.iter().filter(|(_, col_ty)| col_ty == ty).map(|(col, _)| col).collect();
+                        let expr = if !same_type.is_empty() && rng.rand().unsigned_abs() % 2 == 0 {
+                            Expr::Column(same_type[(rng.rand().unsigned_abs() as usize) % same_type.len()].clone())
+                        } else {
+                            expr_gen.gen_aggregate(*ty, rng)
+                        };
+                        (expr, *ty)
+                    })
+                    .collect(),
+                None => {
+                    let mut items: Vec<(Expr, ExprType)> =
+                        grouped.into_iter().map(|(col, ty)| (Expr::Column(col), ty)).collect();
+                    for _ in 0..(rng.rand().unsigned_abs() % 2) + 1 {
+                        let ty = ExprType::random(rng);
+                        items.push((expr_gen.gen_aggregate(ty, rng), ty));
+                    }
+                    items
+                }
+            }
+        } else {
+            let mut items: Vec<(Expr, ExprType)> = match shape {
+                // Subqueries and compound arms must produce exactly these types
+                Some(types) => types
+                    .iter()
+                    .map(|ty| {
+                        let same_type: Vec<&ColumnRef> =
+                            columns.iter().filter(|(_, col_ty)| col_ty == ty).map(|(col, _)| col).collect();
+                        let expr = if !same_type.is_empty() && rng.rand().unsigned_abs() % 2 == 0 {
+                            Expr::Column(same_type[(rng.rand().unsigned_abs() as usize) % same_type.len()].clone())
+                        } else {
+                            expr_gen.gen_expr(*ty, rng)
+                        };
+                        (expr, *ty)
+                    })
+                    .collect(),
+                None => {
+                    // Select random columns
+                    let col_count = ((rng.rand().unsigned_abs() as usize) % columns.len()) + 1;
+                    let mut selected_cols = columns.clone();
+                    for i in (1..selected_cols.len()).rev() {
+                        let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
+                        selected_cols.swap(i, j);
+                    }
+                    let mut items: Vec<(Expr, ExprType)> = selected_cols[..col_count]
+                        .iter()
+                        .map(|(col, ty)| (Expr::Column(col.clone()), *ty))
+                        .collect();
+                    // Add an optional computed column
+                    if rng.rand().unsigned_abs().is_multiple_of(3) {
+                        let ty = ExprType::random(rng);
+                        items.push((expr_gen.gen_expr(ty, rng), ty));
+                    }
+                    items
+                }
+            };
+            if mode == CoreMode::Windowed && shape.is_none() {
+                // Add window functions, sharing a named window some of the time
+                if rng.rand().unsigned_abs().is_multiple_of(2) {
+                    windows.push((WINDOW_NAME.to_string(), expr_gen.gen_window_def(rng)));
+                }
+                for _ in 0..(rng.rand().unsigned_abs() % 2) + 1 {
+                    let over = if !windows.is_empty() && rng.rand().unsigned_abs() % 2 == 0 {
+                        Over::Named(WINDOW_NAME.to_string())
+                    } else {
+                        Over::Window(Box::new(expr_gen.gen_window_def(rng)))
+                    };
+                    items.push(expr_gen.gen_window_function(over, rng));
+                }
+            }
+            items
+        };
+        let types = items.iter().map(|(_, ty)| *ty).collect();
+        let select_items = items
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (expr, _))| SelectItem {
+                expr,
+                alias: aliased.then(|| result_column_name(idx)),
+            })
+            .collect();
+
+        // Add optional DISTINCT
+        let distinct = rng.rand().unsigned_abs() % 2 == 0;
+
+        // Add optional WHERE clause
+        let where_clause = if rng.rand().unsigned_abs() % 2 == 0 {
+            Some(expr_gen.gen_predicate(rng))
+        } else {
+            None
+        };
+
+        let core = SelectCore {
+            distinct,
+            columns: select_items,
+            from: Some(from),
+            where_clause,
+            group_by,
+            having,
+            windows,
+        };
+        Some((core, types, scope))
+    }
+
+    /// Picks one to three tables (the same table may appear twice under different aliases)
+    /// and joins them. Joined tables are always aliased `t0`, `t1`, ...
+    fn gen_from_clause(&self, rng: &mut LcgRng) -> Option<(FromClause, Vec<ScopeTable>)> {
+        let table_count = match rng.rand().unsigned_abs() % 4 {
+            0 | 1 => 1,
+            2 => 2,
+            _ => 3,
+        };
+
+        // A lone table is only sometimes aliased
+        let first_alias = (table_count > 1 || rng.rand().unsigned_abs().is_multiple_of(4)).then(|| "t0".to_string());
+        let (first, first_scope) = self.gen_table_or_subquery(first_alias, "t0", rng)?;
+        let mut from = FromClause {
+            table: first,
+            joins: vec![],
+        };
+        let mut scope = vec![first_scope];
+
+        for n in 1..table_count {
+            let alias = format!("t{}", n);
+            let Some((table, joined)) = self.gen_table_or_subquery(Some(alias.clone()), &alias, rng) else {
+                continue;
+            };
+            let (natural, kind) = match rng.rand().unsigned_abs() % 8 {
+                0..=2 => (false, JoinKind::Inner),
+                3 | 4 => (false, JoinKind::Left),
+                5 => (false, JoinKind::Cross),
+                6 => (true, JoinKind::Inner),
+                _ => (true, JoinKind::Left),
+            };
+            let constraint = if natural || kind == JoinKind::Cross {
+                None
+            } else {
+                Some(self.gen_join_constraint(&scope, &joined, rng))
+            };
+            from.joins.push(Join {
+                natural,
+                kind,
+                table,
+                constraint,
+            });
+            scope.push(joined);
+        }
+
+        Some((from, scope))
+    }
+
+    /// A relation in scope, or a derived table when the depth budget allows. Derived tables
+    /// always need an alias, so `fallback_alias` is used when `alias` is `None`.
+    fn gen_table_or_subquery(
+        &self,
+        alias: Option<String>,
+        fallback_alias: &str,
+        rng: &mut LcgRng,
+    ) -> Option<(TableOrSubquery, ScopeTable)> {
+        if let Some(nested) = self.nested()
+            && rng.rand().unsigned_abs().is_multiple_of(6)
+            && let Some((query, types)) = nested.gen_query(None, true, rng)
+        {
+            let alias = alias.unwrap_or_else(|| fallback_alias.to_string());
+            let columns = types
+                .iter()
+                .enumerate()
+                .map(|(idx, ty)| (result_column_name(idx), ty.sql_name().to_string()))
+                .collect();
+            let scope = ScopeTable {
+                qualifier: Some(alias.clone()),
+                columns,
+            };
+            let item = TableOrSubquery::Subquery {
+                query: Box::new(query),
+                alias,
+            };
+            return Some((item, scope));
+        }
+
+        let relation = &self.relations[(rng.rand().unsigned_abs() as usize) % self.relations.len()];
+        let scope = ScopeTable {
+            qualifier: alias.clone(),
+            columns: relation.columns.clone(),
+        };
+        let item = TableOrSubquery::Table(TableRef {
+            name: relation.name.clone(),
+            alias,
+        });
+        Some((item, scope))
+    }
+
+    fn gen_join_constraint(&self, scope: &[ScopeTable], joined: &ScopeTable, rng: &mut LcgRng) -> JoinConstraint {
+        let using = using_candidates(scope, joined);
+        if !using.is_empty() && rng.rand().unsigned_abs() % 2 == 0 {
+            let col = &using[(rng.rand().unsigned_abs() as usize) % using.len()];
+            return JoinConstraint::Using(vec![col.clone()]);
+        }
+
+        // Equate a pair of same-typed columns, preferring ones that look like a key relationship
+        let mut pairs = Vec::new();
+        let mut related = Vec::new();
+        for t in scope {
+            for (left, left_ty) in &t.columns {
+                for (right, right_ty) in &joined.columns {
+                    if ExprType::from_decl(left_ty) != ExprType::from_decl(right_ty) {
+                        continue;
+                    }
+                    let pair = (t.column_ref(left), joined.column_ref(right));
+                    if related_names(left, right) {
+                        related.push(pair);
+                    } else {
+                        pairs.push(pair);
+                    }
+                }
+            }
+        }
+        let candidates = if related.is_empty() { pairs } else { related };
+
+        let expr_gen = self.expr_generator(scope.iter().chain([joined]), None);
+        if candidates.is_empty() {
+            return JoinConstraint::On(expr_gen.gen_predicate(rng));
+        }
+        let (left, right) = candidates[(rng.rand().unsigned_abs() as usize) % candidates.len()].clone();
+        let mut on = Expr::binary(BinaryOp::Eq, Expr::Column(left), Expr::Column(right));
+        if rng.rand().unsigned_abs().is_multiple_of(4) {
+            on = Expr::binary(BinaryOp::And, on, expr_gen.gen_predicate(rng));
+        }
+        JoinConstraint::On(on)
+    }
+}
+
+/// `WITH RECURSIVE name(c0) AS (SELECT start UNION ALL SELECT c0 + 1 FROM name WHERE c0 < end)`,
+/// which always terminates.
+fn counter_cte(name: &str, rng: &mut LcgRng) -> Cte {
+    let start = (rng.rand().unsigned_abs() % 10) as i64;
+    let end = start + (rng.rand().unsigned_abs() % 20) as i64 + 1;
+    let counter = Expr::column(&result_column_name(0));
+    let base = SelectCore {
+        distinct: false,
+        columns: vec![SelectItem {
+            expr: Expr::Literal(Literal::Integer(start)),
+            alias: None,
+        }],
+        from: None,
+        where_clause: None,
+        group_by: vec![],
+        having: None,
+        windows: vec![],
     };
+    let step = SelectCore {
+        distinct: false,
+        columns: vec![SelectItem {
+            expr: Expr::binary(BinaryOp::Add, counter.clone(), Expr::Literal(Literal::Integer(1))),
+            alias: None,
+        }],
+        from: Some(FromClause::new(TableRef::new(name))),
+        where_clause: Some(Expr::binary(BinaryOp::Lt, counter, Expr::Literal(Literal::Integer(end)))),
+        group_by: vec![],
+        having: None,
+        windows: vec![],
+    };
+    Cte {
+        name: name.to_string(),
+        columns: vec![result_column_name(0)],
+        query: Box::new(SelectStmt {
+            with: None,
+            core: base,
+            compounds: vec![(CompoundOp::UnionAll, step)],
+            order_by: vec![],
+            limit: None,
+        }),
+    }
+}
 
-    // Combine all parts into a full SELECT statement
-    Some(format!(
-        "SELECT {}{} FROM {} {} {} {} {};",
-        distinct,
-        selected_cols.join(", "),
-        table.name(),
-        where_clause,
-        group_by_clause,
-        order_by_clause,
-        limit_clause
-    ))
+fn random_column(scope: &[ScopeTable], rng: &mut LcgRng) -> Expr {
+    let columns: Vec<ColumnRef> = scope
+        .iter()
+        .flat_map(|t| t.columns.iter().map(|(name, _)| t.column_ref(name)))
+        .collect();
+    Expr::Column(columns[(rng.rand().unsigned_abs() as usize) % columns.len()].clone())
+}
+
+/// Column names that exist in `joined` and in exactly one table already in scope.
+fn using_candidates(scope: &[ScopeTable], joined: &ScopeTable) -> Vec<String> {
+    joined
+        .columns
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| {
+            scope
+                .iter()
+                .filter(|t| t.columns.iter().any(|(n, _)| n == *name))
+                .count()
+                == 1
+        })
+        .cloned()
+        .collect()
+}
+
+/// TPC-C style key naming: `d_w_id` refers to `w_id`, and `c_d_id`/`o_d_id` to the same district.
+fn related_names(a: &str, b: &str) -> bool {
+    if a == b || a.ends_with(&format!("_{}", b)) || b.ends_with(&format!("_{}", a)) {
+        return true;
+    }
+    matches!(
+        (a.split_once('_'), b.split_once('_')),
+        (Some((_, ka)), Some((_, kb))) if ka == kb && ka.contains('_')
+    )
+}
+
+/// A bare (possibly signed) integer in ORDER BY is read as a result column index.
+fn is_signed_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_) => true,
+        Expr::Unary {
+            op: UnaryOp::Neg | UnaryOp::Plus,
+            expr,
+        } => is_signed_literal(expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::{Dialect, Stmt};
+
+    fn table(name: &str, columns: &[(&str, &str)]) -> TableSchema {
+        TableSchema {
+            name: name.to_string(),
+            columns: columns.iter().map(|(n, t)| (n.to_string(), t.to_string())).collect(),
+            primary_key: vec![],
+            without_rowid: false,
+        }
+    }
+
+    #[test]
+    fn test_nested_queries_are_valid_sqlite() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE warehouse (w_id INTEGER, w_name TEXT, w_tax REAL);
+             CREATE TABLE district (d_id INTEGER, d_w_id INTEGER, d_name TEXT, d_ytd NUMERIC);",
+        )
+        .unwrap();
+        let tables = [
+            table("warehouse", &[("w_id", "INTEGER"), ("w_name", "TEXT"), ("w_tax", "REAL")]),
+            table("district", &[("d_id", "INTEGER"), ("d_w_id", "INTEGER"), ("d_name", "TEXT"), ("d_ytd", "NUMERIC")]),
+        ];
+        let mut rng = LcgRng::new(7);
+
+        for _ in 0..300 {
+            let query = gen_select_stmt(&tables, 2, &mut rng).unwrap();
+            let sql = Stmt::Select(query).to_sql(Dialect::Sqlite);
+            if let Err(e) = conn.prepare(&sql) {
+                panic!("invalid SQL generated: {} ({})", sql, e);
+            }
+        }
+    }
 }
@@ -1,13 +1,32 @@
 // 通用 UPDATE 语句生成逻辑，供 limbo/sqlite 共享
-use crate::generators::common::data_type::generate_value_by_type;
+use crate::generators::ast::{TableRef, UpdateStmt};
+use crate::generators::common::expr_common::{DEFAULT_EXPR_DEPTH, ExprGenerator, ExprType, OuterScope};
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::TableSchema;
 
 pub trait TableColumnLike {
     fn name(&self) -> &str;
     fn columns(&self) -> Vec<(String, String)>; // (name, type)
+    /// Tables without rowid cannot take the rowid subquery that stands in for `LIMIT`
+    fn without_rowid(&self) -> bool {
+        false
+    }
+}
+
+impl TableColumnLike for TableSchema {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn columns(&self) -> Vec<(String, String)> {
+        self.columns.clone()
+    }
+    fn without_rowid(&self) -> bool {
+        self.without_rowid
+    }
 }
 
-pub fn gen_update_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Option<String> {
+/// Generates an UPDATE whose expressions may also use the columns of `outer`.
+pub fn gen_update_stmt<T: TableColumnLike>(tables: &[T], outer: &OuterScope, rng: &mut LcgRng) -> Option<UpdateStmt> {
     if tables.is_empty() {
         return None;
     }
@@ -27,53 +46,47 @@ pub fn gen_update_stmt<T: TableColumnLike>(tables: &[T], rng: &mut LcgRng) -> Op
         let j = (rng.rand().unsigned_abs() as usize) % (i + 1);
         selected_cols.swap(i, j);
     }
-    let selected_cols = &selected_cols[..col_count];
-    let set_clause: Vec<String> = selected_cols
+    // Columns are qualified so they stay unambiguous when a FROM table is added
+    let expr_gen = ExprGenerator::new(DEFAULT_EXPR_DEPTH)
+        .with_columns(Some(table.name()), &columns)
+        .with_outer(outer);
+    let set = selected_cols[..col_count]
         .iter()
-        .map(|(name, ty)| {
-            let value = generate_value_by_type(ty, rng);
-            format!("{} = {}", name, value)
-        })
+        .map(|(name, ty)| (name.clone(), expr_gen.gen_expr(ExprType::from_decl(ty), rng)))
         .collect();
 
     // Add optional WHERE clause
     let where_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let col_idx = (rng.rand().unsigned_abs() as usize) % columns.len();
-        let col = &columns[col_idx].0;
-        let value = generate_value_by_type(&columns[col_idx].1, rng);
-        format!("WHERE {} = {}", col, value)
+        Some(expr_gen.gen_predicate(rng))
     } else {
-        String::new()
+        None
     };
 
     // Add optional LIMIT clause
-    let limit_clause = if rng.rand().unsigned_abs() % 2 == 0 {
-        let limit = (rng.rand().unsigned_abs() % 100).to_string(); // Random limit
-        format!("LIMIT {}", limit)
+    let limit = if rng.rand().unsigned_abs() % 2 == 0 && !table.without_rowid() {
+        Some(rng.rand().unsigned_abs() % 100) // Random limit
     } else {
-        String::new()
+        None
     };
 
     // Add optional FROM clause (for multi-table updates)
-    let from_clause = if rng.rand().unsigned_abs() % 2 == 0 && tables.len() > 1 {
+    let from = if rng.rand().unsigned_abs() % 2 == 0 && tables.len() > 1 {
         let other_table_idx = (rng.rand().unsigned_abs() as usize) % tables.len();
         let other_table = &tables[other_table_idx];
         if other_table.name() != table.name() {
-            format!("FROM {}", other_table.name())
+            Some(TableRef::new(other_table.name()))
         } else {
-            String::new()
+            None
         }
     } else {
-        String::new()
+        None
     };
 
-    // Combine all parts into a full UPDATE statement
-    Some(format!(
-        "UPDATE {} {} SET {} {} {};",
-        table.name(),
-        from_clause,
-        set_clause.join(", "),
+    Some(UpdateStmt {
+        table: table.name().to_string(),
+        set,
+        from,
         where_clause,
-        limit_clause
-    ))
+        limit,
+    })
 }
@@ -1,40 +1,185 @@
-use crate::engines::Engine;
+use crate::generators::ast::{Dialect, Expr, ObjectKind, SelectStmt, Stmt};
 use crate::generators::common::SqlKind;
+use crate::generators::common::alter_table_stmt_common::gen_alter_table_stmt;
+use crate::generators::common::create_index_stmt_common::gen_create_index_stmt;
+use crate::generators::common::create_table_stmt_common::gen_create_table_stmt;
+use crate::generators::common::create_trigger_stmt_common::gen_create_trigger_stmt;
+use crate::generators::common::create_view_stmt_common::gen_create_view_stmt;
+use crate::generators::common::delete_stmt_common::gen_delete_stmt;
+use crate::generators::common::drop_stmt_common::gen_drop_stmt;
+use crate::generators::common::drop_trigger_stmt_common::gen_drop_trigger_stmt;
+use crate::generators::common::expr_common::OuterScope;
+use crate::generators::common::insert_stmt_common::gen_insert_stmt;
+use crate::generators::common::select_stmt_common::{gen_oracle_query, gen_select_stmt};
+use crate::generators::common::update_stmt_common::gen_update_stmt;
+use sqlsmith_rs_common::profile::Profile;
 use sqlsmith_rs_common::rand_by_seed::LcgRng;
-use sqlsmith_rs_drivers::DRIVER_KIND;
-use sqlsmith_rs_drivers::DatabaseDriver;
+use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver, TableSchema};
 
+pub mod ast;
 pub mod common;
-pub mod limbo;
-pub mod sqlite;
 
+/// Tables of the TPC-C schema every database starts with.
+pub(crate) const SEEDED_TABLES: &[&str] = &[
+    "warehouse",
+    "district",
+    "customer",
+    "history",
+    "item",
+    "stock",
+    "customer_order",
+    "new_order",
+    "order_line",
+];
+
+/// Generation settings taken from the profile.
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    /// How many levels of subqueries, CTEs and derived tables a SELECT may nest
+    pub subquery_depth: usize,
+}
+
+impl GeneratorOptions {
+    pub fn from_profile(profile: &Profile) -> Self {
+        Self {
+            subquery_depth: profile.subquery_depth.unwrap_or(2),
+        }
+    }
+}
+
+/// 辅助函数，用于获取表信息
+fn get_tables(driver: &dyn DatabaseDriver) -> Option<Vec<TableSchema>> {
+    match driver.get_tables() {
+        Ok(tables) if !tables.is_empty() => Some(tables),
+        Ok(_) => None,
+        Err(e) => {
+            log::error!("Failed to read schema from {:?} driver: {}", driver.kind(), e);
+            None
+        }
+    }
+}
+
+/// Tables followed by the views that can still be queried; only used for reading.
+fn get_relations(driver: &dyn DatabaseDriver) -> Option<Vec<TableSchema>> {
+    let mut relations = get_tables(driver)?;
+    match driver.get_views() {
+        Ok(views) => relations.extend(views),
+        Err(e) => log::error!("Failed to read views from {:?} driver: {}", driver.kind(), e),
+    }
+    Some(relations)
+}
+
+/// Names of the existing objects of `object_type`, e.g. "index" or "view".
+fn get_object_names(driver: &dyn DatabaseDriver, object_type: &str) -> Option<Vec<String>> {
+    match driver.get_object_names(object_type) {
+        Ok(names) => Some(names),
+        Err(e) => {
+            log::error!("Failed to list {}s from {:?} driver: {}", object_type, driver.kind(), e);
+            None
+        }
+    }
+}
+
+/// A generated statement: a syntax tree, or text for the kinds generated directly as SQL.
+#[derive(Debug, Clone)]
+pub enum GeneratedStmt {
+    Ast(Box<Stmt>),
+    Text(String),
+}
+
+impl GeneratedStmt {
+    pub fn to_sql(&self, dialect: Dialect) -> String {
+        match self {
+            GeneratedStmt::Ast(stmt) => stmt.clone().to_sql(dialect),
+            GeneratedStmt::Text(sql) => sql.clone(),
+        }
+    }
+}
+
+fn ast(stmt: Stmt) -> Option<GeneratedStmt> {
+    Some(GeneratedStmt::Ast(Box::new(stmt)))
+}
+
+/// Generates a query and a predicate to partition it by, for the logic oracles.
+pub fn get_oracle_query(
+    driver: &dyn DatabaseDriver,
+    seeder: &mut LcgRng,
+    options: &GeneratorOptions,
+) -> Option<(SelectStmt, Expr)> {
+    gen_oracle_query(&get_relations(driver)?, options.subquery_depth, seeder)
+}
+
+/// Generates a query against the live schema, as a syntax tree so it can be mutated later.
+pub fn get_select_stmt(driver: &dyn DatabaseDriver, seeder: &mut LcgRng, options: &GeneratorOptions) -> Option<SelectStmt> {
+    gen_select_stmt(&get_relations(driver)?, options.subquery_depth, seeder)
+}
+
+/// Generates one statement of `kind` against the live schema of `driver`, printed in the
+/// driver's dialect.
 pub fn get_stmt_by_seed(
+    driver: &dyn DatabaseDriver,
     seeder: &mut LcgRng,
     kind: SqlKind,
-    mut engine: Box<dyn Engine>,
+    options: &GeneratorOptions,
 ) -> Option<String> {
-    match engine.get_driver_kind() {
-        DRIVER_KIND::SQLITE_IN_MEM => {
-            let driver_box = engine.get_sqlite_driver_box();
-            let conn = match driver_box {
-                Some(box_instance) => box_instance.get_connection_mut(),
-                None => {
-                    log::error!("Failed to get SQLite driver connection");
-                    return None;
-                }
-            };
-            sqlite::get_stmt_by_seed(conn, seeder, kind)
-        }
-        DRIVER_KIND::LIMBO_IN_MEM => {
-            let driver_box = engine.get_limbo_driver_box();
-            let conn = match driver_box {
-                Some(box_instance) => box_instance.get_connection_mut(),
-                None => {
-                    log::error!("Failed to get Limbo driver connection");
-                    return None;
-                }
-            };
-            limbo::get_stmt_by_seed(conn, seeder, kind)
+    gen_stmt_by_seed(driver, seeder, kind, options).map(|stmt| stmt.to_sql(Dialect::from(driver.kind())))
+}
+
+/// Generates one statement of `kind` against the live schema of `driver`.
+pub fn gen_stmt_by_seed(
+    driver: &dyn DatabaseDriver,
+    seeder: &mut LcgRng,
+    kind: SqlKind,
+    options: &GeneratorOptions,
+) -> Option<GeneratedStmt> {
+    match kind {
+        SqlKind::Select => ast(Stmt::Select(get_select_stmt(driver, seeder, options)?)),
+        SqlKind::Insert => ast(Stmt::Insert(gen_insert_stmt(&get_tables(driver)?, &OuterScope::new(), seeder)?)),
+        SqlKind::Update => ast(Stmt::Update(gen_update_stmt(&get_tables(driver)?, &OuterScope::new(), seeder)?)),
+        SqlKind::Delete => ast(Stmt::Delete(gen_delete_stmt(&get_tables(driver)?, &OuterScope::new(), seeder)?)),
+        SqlKind::CreateTrigger => {
+            let tables = get_tables(driver)?;
+            let views = driver.get_views().unwrap_or_default();
+            ast(Stmt::CreateTrigger(gen_create_trigger_stmt(&tables, &views, seeder)?))
+        }
+        SqlKind::DropTrigger => {
+            let names = get_object_names(driver, "trigger")?;
+            ast(Stmt::DropTrigger(gen_drop_trigger_stmt(&names, seeder)?))
+        }
+        SqlKind::Vacuum => common::vacuum_stmt_common::gen_vacuum_stmt().map(GeneratedStmt::Text),
+        SqlKind::Pragma => match driver.kind() {
+            DRIVER_KIND::SQLITE_IN_MEM => common::pragma_stmt_common::get_pragma_stmt_by_seed(seeder).map(GeneratedStmt::Text),
+            // Limbo 目前对 Pragma 无处理
+            DRIVER_KIND::LIMBO_IN_MEM => None,
+        },
+        SqlKind::DateFunc => common::datefunc_stmt_common::gen_datefunc_stmt(seeder).map(GeneratedStmt::Text),
+        SqlKind::CreateTable => {
+            let names = get_object_names(driver, "table")?;
+            ast(Stmt::CreateTable(gen_create_table_stmt(&names, seeder)?))
+        }
+        SqlKind::CreateIndex => ast(Stmt::CreateIndex(gen_create_index_stmt(&get_tables(driver)?, seeder)?)),
+        SqlKind::CreateView => ast(
+            Stmt::CreateView(gen_create_view_stmt(&get_relations(driver)?, options.subquery_depth, seeder)?),
+        ),
+        SqlKind::AlterTable => {
+            let names = get_object_names(driver, "table")?;
+            ast(Stmt::AlterTable(gen_alter_table_stmt(&get_tables(driver)?, &names, seeder)?))
+        }
+        SqlKind::DropTable => {
+            // Only generated tables are dropped; without the TPC-C ones most later statements would fail
+            let names: Vec<String> = get_object_names(driver, "table")?
+                .into_iter()
+                .filter(|name| !SEEDED_TABLES.contains(&name.as_str()))
+                .collect();
+            ast(Stmt::Drop(gen_drop_stmt(ObjectKind::Table, &names, seeder)?))
+        }
+        SqlKind::DropIndex => {
+            let names = get_object_names(driver, "index")?;
+            ast(Stmt::Drop(gen_drop_stmt(ObjectKind::Index, &names, seeder)?))
+        }
+        SqlKind::DropView => {
+            let names = get_object_names(driver, "view")?;
+            ast(Stmt::Drop(gen_drop_stmt(ObjectKind::View, &names, seeder)?))
         }
     }
 }
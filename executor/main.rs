@@ -1,36 +1,93 @@
 // src/main.rs
 
 // Declare the drivers module so Rust can find its content
+mod cli;
 mod engines;
+mod findings;
 mod generators;
+mod oracles;
+mod reducer;
+mod replay;
 
 use anyhow::Result;
+use cli::{Command, Parsed};
 use engines::Engine;
 use log::info;
-use sqlsmith_rs_common::profile::read_profile;
+use sqlsmith_rs_common::profile::{read_profile, read_profile_from};
+use std::path::Path;
 
 use crate::engines::with_driver_kind;
 
 fn main() -> Result<()> {
-    sqlsmith_rs_common::logger::init(); // Configure logging
+    let cli = match cli::parse(std::env::args().skip(1)) {
+        Ok(Parsed::Cli(cli)) => cli,
+        Ok(Parsed::Help) => {
+            print!("{}", cli::usage());
+            return Ok(());
+        }
+        Ok(Parsed::Version) => {
+            println!("executor {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::usage());
+            std::process::exit(2);
+        }
+    };
 
-    let profile = read_profile();
+    // Commands that print their result keep stdout to themselves
+    match cli.command {
+        Command::Gen | Command::Reduce(_) => sqlsmith_rs_common::logger::init_stderr(),
+        Command::Run | Command::Replay(_) => sqlsmith_rs_common::logger::init(), // Configure logging
+    }
+
+    let mut profile = match &cli.profile {
+        Some(path) => read_profile_from(Path::new(path)).map_err(|e| anyhow::anyhow!("cannot read {}: {}", path, e))?,
+        None => read_profile(),
+    };
+    cli.apply(&mut profile).map_err(anyhow::Error::msg)?;
     let driver_kind = profile.driver.expect("driver kind must be specified");
     let run_count = profile.count.expect("run count must be an unsigned number");
     profile.print();
 
-    // Get seed from environment variable or fallback to profile seed
-    let seed = std::env::var("EXEC_PARAM_SEED")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or_else(|| profile.seed.unwrap_or(0));
+    // Get seed from the command line, the environment variable or fallback to profile seed
+    let seed = cli.seed().unwrap_or_else(|| {
+        std::env::var("EXEC_PARAM_SEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| profile.seed.unwrap_or(0))
+    });
+
+    match &cli.command {
+        Command::Run => {}
+        Command::Replay(path) => {
+            let diverged = replay::replay(path, &profile)?;
+            if diverged > 0 {
+                anyhow::bail!("{} statements of {} ended differently than recorded", diverged, path);
+            }
+            return Ok(());
+        }
+        Command::Reduce(path) => {
+            for sql in replay::reduce_log(path)? {
+                println!("{}", sql);
+            }
+            return Ok(());
+        }
+        Command::Gen => {
+            for sql in engines::generate_statements(seed, driver_kind, run_count, &profile)? {
+                println!("{}", sql);
+            }
+            return Ok(());
+        }
+    }
+
     info!(
         "init executor engine with seed: {:?}, base seed: {:?}",
         seed,
-        profile.seed.unwrap()
+        profile.seed.unwrap_or(0)
     );
     let mut engine = with_driver_kind(seed, driver_kind, run_count, &profile)?;
-    info!("SQLite connection prepared and verified.");
+    info!("{:?} engine prepared.", engine.get_driver_kind());
 
     engine.run();
 
@@ -0,0 +1,962 @@
+// Evaluates generated expressions against a single row in Rust the way SQLite does: storage
+// classes, type affinity, three-valued logic and collating sequences. Only the subset of SQL
+// the expression generator emits is modelled. Everything else, and every corner where the
+// outcome depends on SQLite internals not mirrored here, evaluates to `None`, so callers can
+// skip the expression instead of guessing.
+use crate::generators::ast::{BinaryOp, Collation, Expr, FunctionCall, LikeOp, Literal, UnaryOp};
+use crate::generators::common::expr_common::ExprType;
+use sqlsmith_rs_drivers::SqlValue;
+use std::cmp::Ordering;
+
+/// The affinity of a column or CAST, as far as comparisons are concerned; INTEGER and REAL
+/// affinity compare like NUMERIC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Affinity {
+    Blob,
+    Text,
+    Numeric,
+}
+
+impl Affinity {
+    fn from_decl(decl: &str) -> Self {
+        // ANY columns of STRICT tables store values unchanged
+        if decl.eq_ignore_ascii_case("ANY") {
+            return Affinity::Blob;
+        }
+        match ExprType::from_decl(decl) {
+            ExprType::Text => Affinity::Text,
+            ExprType::Blob => Affinity::Blob,
+            _ => Affinity::Numeric,
+        }
+    }
+}
+
+/// A number read from text, as SQLite's `sqlite3AtoF` sees it.
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i64),
+    Real(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(i) => i as f64,
+            Number::Real(r) => r,
+        }
+    }
+
+    fn into_value(self) -> SqlValue {
+        match self {
+            Number::Int(i) => SqlValue::Integer(i),
+            Number::Real(r) => SqlValue::Real(r),
+        }
+    }
+}
+
+/// The longest numeric prefix of a text.
+struct ParsedNumber {
+    /// Zero when there are no digits at all
+    value: Number,
+    has_digits: bool,
+    /// Nothing but whitespace follows the number
+    complete: bool,
+}
+
+/// Evaluates expressions over the columns of one row of a table.
+pub struct RowEvaluator<'a> {
+    columns: Vec<(&'a str, Affinity, &'a SqlValue)>,
+}
+
+impl<'a> RowEvaluator<'a> {
+    /// `columns` are the table's `(name, declared type)` pairs, `values` one row of it in the
+    /// same order. Columns are assumed to use the default BINARY collation.
+    pub fn new(columns: &'a [(String, String)], values: &'a [SqlValue]) -> Self {
+        Self {
+            columns: columns
+                .iter()
+                .zip(values)
+                .map(|((name, decl), value)| (name.as_str(), Affinity::from_decl(decl), value))
+                .collect(),
+        }
+    }
+
+    /// The value of `expr` for this row, or `None` when it cannot be determined.
+    pub fn eval(&self, expr: &Expr) -> Option<SqlValue> {
+        if has_ambiguous_collation(expr) {
+            return None;
+        }
+        self.value(expr)
+    }
+
+    fn column(&self, name: &str) -> Option<(Affinity, &SqlValue)> {
+        self.columns
+            .iter()
+            .find(|(column, _, _)| column.eq_ignore_ascii_case(name))
+            .map(|(_, affinity, value)| (*affinity, *value))
+    }
+
+    fn value(&self, expr: &Expr) -> Option<SqlValue> {
+        match expr {
+            Expr::Literal(lit) => Some(literal_value(lit)),
+            Expr::Column(col) => self.column(&col.name).map(|(_, value)| value.clone()),
+            Expr::Unary { op, expr } => {
+                let value = self.value(expr)?;
+                match op {
+                    UnaryOp::Plus => Some(value),
+                    // Coded as `0 - x`
+                    UnaryOp::Neg => arithmetic(BinaryOp::Sub, &SqlValue::Integer(0), &value),
+                    UnaryOp::BitNot if value == SqlValue::Null => Some(SqlValue::Null),
+                    UnaryOp::BitNot => Some(SqlValue::Integer(!int_value(&value)?)),
+                    UnaryOp::Not => Some(truth_value(truth(&value)?.map(|b| !b))),
+                }
+            }
+            Expr::Binary { op, left, right } => match op {
+                BinaryOp::And | BinaryOp::Or => {
+                    let l = truth(&self.value(left)?)?;
+                    let r = truth(&self.value(right)?)?;
+                    let result = if *op == BinaryOp::And {
+                        match (l, r) {
+                            (Some(false), _) | (_, Some(false)) => Some(false),
+                            (Some(true), Some(true)) => Some(true),
+                            _ => None,
+                        }
+                    } else {
+                        match (l, r) {
+                            (Some(true), _) | (_, Some(true)) => Some(true),
+                            (Some(false), Some(false)) => Some(false),
+                            _ => None,
+                        }
+                    };
+                    Some(truth_value(result))
+                }
+                BinaryOp::Eq
+                | BinaryOp::NotEq
+                | BinaryOp::Lt
+                | BinaryOp::LtEq
+                | BinaryOp::Gt
+                | BinaryOp::GtEq
+                | BinaryOp::Is
+                | BinaryOp::IsNot => self.compare(*op, left, right),
+                BinaryOp::Concat => {
+                    let (l, r) = (self.value(left)?, self.value(right)?);
+                    if l == SqlValue::Null || r == SqlValue::Null {
+                        return Some(SqlValue::Null);
+                    }
+                    Some(SqlValue::Text(text_of(&l)? + &text_of(&r)?))
+                }
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
+                    arithmetic(*op, &self.value(left)?, &self.value(right)?)
+                }
+                BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::ShiftLeft | BinaryOp::ShiftRight => {
+                    bitwise(*op, &self.value(left)?, &self.value(right)?)
+                }
+            },
+            Expr::Case {
+                operand,
+                when_then,
+                else_expr,
+            } => {
+                for (when, then) in when_then {
+                    let hit = match operand {
+                        Some(operand) => self.compare(BinaryOp::Eq, operand, when)? == SqlValue::Integer(1),
+                        None => truth(&self.value(when)?)? == Some(true),
+                    };
+                    if hit {
+                        return self.value(then);
+                    }
+                }
+                match else_expr {
+                    Some(else_expr) => self.value(else_expr),
+                    None => Some(SqlValue::Null),
+                }
+            }
+            Expr::Cast { expr, type_name } => cast(self.value(expr)?, type_name),
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => {
+                // `x BETWEEN a AND b` is coded as `x >= a AND x <= b`
+                let ge = truth(&self.compare(BinaryOp::GtEq, expr, low)?)?;
+                let le = truth(&self.compare(BinaryOp::LtEq, expr, high)?)?;
+                let between = match (ge, le) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                };
+                Some(truth_value(between.map(|b| b != *negated)))
+            }
+            Expr::InList { expr, negated, list } => self.in_list(expr, *negated, list),
+            Expr::Like {
+                expr,
+                negated,
+                op,
+                pattern,
+            } => {
+                let (value, pattern) = (self.value(expr)?, self.value(pattern)?);
+                if value == SqlValue::Null || pattern == SqlValue::Null {
+                    return Some(SqlValue::Null);
+                }
+                let (value, pattern) = (text_of(&value)?, text_of(&pattern)?);
+                let matched = match op {
+                    LikeOp::Glob if pattern.contains('[') => return None,
+                    LikeOp::Glob => wildcard_match(&value, &pattern, '*', '?', false),
+                    LikeOp::Like => {
+                        // PRAGMA case_sensitive_like may be in effect, so only trust results
+                        // that do not depend on it
+                        let folded = wildcard_match(&value, &pattern, '%', '_', true);
+                        if folded != wildcard_match(&value, &pattern, '%', '_', false) {
+                            return None;
+                        }
+                        folded
+                    }
+                };
+                Some(truth_value(Some(matched != *negated)))
+            }
+            Expr::IsNull { expr, negated } => {
+                let is_null = self.value(expr)? == SqlValue::Null;
+                Some(truth_value(Some(is_null != *negated)))
+            }
+            Expr::Collate { expr, .. } => self.value(expr),
+            Expr::Function(call) => self.function(call),
+            Expr::Subquery(_) | Expr::Exists { .. } | Expr::InSubquery { .. } | Expr::Raise { .. } => None,
+        }
+    }
+
+    /// Affinity of an expression in a comparison: columns and CASTs have one, and COLLATE
+    /// passes it through. Everything else, including `+x`, has none.
+    fn affinity(&self, expr: &Expr) -> Option<Affinity> {
+        match expr {
+            Expr::Column(col) => self.column(&col.name).map(|(affinity, _)| affinity),
+            Expr::Cast { type_name, .. } => Some(Affinity::from_decl(type_name)),
+            Expr::Collate { expr, .. } => self.affinity(expr),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, op: BinaryOp, left: &Expr, right: &Expr) -> Option<SqlValue> {
+        let affinity = comparison_affinity(self.affinity(left), self.affinity(right));
+        // An explicit collation on the left wins over one on the right
+        let collation = if has_collate(left) {
+            collation(left)
+        } else if has_collate(right) {
+            collation(right)
+        } else {
+            None
+        };
+        let (l, r) = (self.value(left)?, self.value(right)?);
+        compare_values(op, l, r, affinity, collation.unwrap_or(Collation::Binary))
+    }
+
+    /// `x IN (list)` compares under the affinity and collation of `x` alone.
+    fn in_list(&self, expr: &Expr, negated: bool, list: &[Expr]) -> Option<SqlValue> {
+        let affinity = self.affinity(expr);
+        let collation = collation(expr).unwrap_or(Collation::Binary);
+        let value = self.value(expr)?;
+        if value == SqlValue::Null {
+            return Some(SqlValue::Null);
+        }
+        let mut saw_null = false;
+        for item in list {
+            let item = self.value(item)?;
+            if item == SqlValue::Null {
+                saw_null = true;
+            } else if compare_values(BinaryOp::Eq, value.clone(), item, affinity, collation)? == SqlValue::Integer(1) {
+                return Some(truth_value(Some(!negated)));
+            }
+        }
+        Some(truth_value(if saw_null { None } else { Some(negated) }))
+    }
+
+    fn function(&self, call: &FunctionCall) -> Option<SqlValue> {
+        if call.distinct || call.filter.is_some() || call.over.is_some() {
+            return None;
+        }
+        let args = call
+            .args
+            .iter()
+            .map(|arg| self.value(arg))
+            .collect::<Option<Vec<SqlValue>>>()?;
+        let any_null = args.contains(&SqlValue::Null);
+        match (call.name.as_str(), args.as_slice()) {
+            ("coalesce" | "ifnull", _) => Some(args.into_iter().find(|v| *v != SqlValue::Null).unwrap_or(SqlValue::Null)),
+            ("typeof", [v]) => Some(SqlValue::Text(
+                match v {
+                    SqlValue::Null => "null",
+                    SqlValue::Integer(_) => "integer",
+                    SqlValue::Real(_) => "real",
+                    SqlValue::Text(_) => "text",
+                    SqlValue::Blob(_) => "blob",
+                }
+                .to_string(),
+            )),
+            ("quote", [v]) => Some(SqlValue::Text(match v {
+                SqlValue::Null => "NULL".to_string(),
+                SqlValue::Integer(i) => i.to_string(),
+                // Reals are quoted with up to 20 significant digits
+                SqlValue::Real(_) => return None,
+                SqlValue::Text(t) => format!("'{}'", t.replace('\'', "''")),
+                SqlValue::Blob(b) => format!("X'{}'", hex(b)),
+            })),
+            // hex(NULL) is the empty string
+            ("hex", [v]) => Some(SqlValue::Text(match v {
+                SqlValue::Null => String::new(),
+                SqlValue::Blob(b) => hex(b),
+                v => hex(text_of(v)?.as_bytes()),
+            })),
+            ("nullif", [a, b]) => {
+                let collation = call.args.iter().find_map(collation).unwrap_or(Collation::Binary);
+                Some(if compare_storage(a, b, collation) == Ordering::Equal {
+                    SqlValue::Null
+                } else {
+                    a.clone()
+                })
+            }
+            // An empty pattern returns the first argument as it is, whatever the replacement
+            ("replace", [v, from, to]) if *v != SqlValue::Null && *from != SqlValue::Null => {
+                let from = text_of(from)?;
+                if from.is_empty() {
+                    return Some(v.clone());
+                }
+                if *to == SqlValue::Null {
+                    return Some(SqlValue::Null);
+                }
+                Some(SqlValue::Text(text_of(v)?.replace(&from, &text_of(to)?)))
+            }
+            (_, _) if any_null => Some(SqlValue::Null),
+            ("abs", [SqlValue::Integer(i)]) => i.checked_abs().map(SqlValue::Integer),
+            ("abs", [v]) => Some(SqlValue::Real(real_value(v)?.abs())),
+            ("length", [SqlValue::Blob(b)]) => Some(SqlValue::Integer(b.len() as i64)),
+            ("length", [v]) => Some(SqlValue::Integer(text_of(v)?.chars().count() as i64)),
+            ("instr", [SqlValue::Blob(haystack), SqlValue::Blob(needle)]) => {
+                let pos = if needle.is_empty() {
+                    Some(0)
+                } else {
+                    haystack.windows(needle.len()).position(|w| w == needle.as_slice())
+                };
+                Some(SqlValue::Integer(pos.map_or(0, |p| p as i64 + 1)))
+            }
+            ("instr", [haystack, needle]) => {
+                let (haystack, needle) = (text_of(haystack)?, text_of(needle)?);
+                let pos = haystack.find(&needle).map(|byte| haystack[..byte].chars().count() as i64 + 1);
+                Some(SqlValue::Integer(pos.unwrap_or(0)))
+            }
+            ("unicode", [v]) => Some(
+                text_of(v)?
+                    .chars()
+                    .next()
+                    .map_or(SqlValue::Null, |c| SqlValue::Integer(c as i64)),
+            ),
+            ("max" | "min", [first, rest @ ..]) => {
+                let collation = call.args.iter().find_map(collation).unwrap_or(Collation::Binary);
+                // min() lets later equal arguments win, max() keeps the first
+                let mut best = first;
+                for arg in rest {
+                    let ord = compare_storage(best, arg, collation);
+                    let replace = if call.name == "min" { ord != Ordering::Less } else { ord == Ordering::Less };
+                    if replace {
+                        best = arg;
+                    }
+                }
+                Some(best.clone())
+            }
+            ("round", [v]) => {
+                let r = real_value(v)?;
+                let limit = (i64::MAX - 1) as f64;
+                let rounded = if (0.0..limit).contains(&r) {
+                    ((r + 0.5) as i64) as f64
+                } else if r < 0.0 && -r < limit {
+                    -(((-r + 0.5) as i64) as f64)
+                } else {
+                    return None;
+                };
+                Some(SqlValue::Real(rounded))
+            }
+            // Without ICU, case mapping only touches ASCII letters
+            ("lower", [v]) => Some(SqlValue::Text(text_of(v)?.to_ascii_lowercase())),
+            ("upper", [v]) => Some(SqlValue::Text(text_of(v)?.to_ascii_uppercase())),
+            ("trim", [v]) => Some(SqlValue::Text(text_of(v)?.trim_matches(' ').to_string())),
+            ("ltrim", [v]) => Some(SqlValue::Text(text_of(v)?.trim_start_matches(' ').to_string())),
+            ("rtrim", [v]) => Some(SqlValue::Text(text_of(v)?.trim_end_matches(' ').to_string())),
+            // Only positive starts and non-negative lengths; the rest has subtle rules
+            ("substr", [v, SqlValue::Integer(start), SqlValue::Integer(len)])
+                if (1..=i32::MAX as i64).contains(start) && (0..=i32::MAX as i64).contains(len) =>
+            {
+                let (skip, take) = (*start as usize - 1, *len as usize);
+                Some(match v {
+                    SqlValue::Blob(b) => SqlValue::Blob(b.iter().skip(skip).take(take).copied().collect()),
+                    v => SqlValue::Text(text_of(v)?.chars().skip(skip).take(take).collect()),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn literal_value(lit: &Literal) -> SqlValue {
+    match lit {
+        Literal::Null => SqlValue::Null,
+        Literal::Integer(i) => SqlValue::Integer(*i),
+        // NaN is rendered as NULL
+        Literal::Real(r) if r.is_nan() => SqlValue::Null,
+        Literal::Real(r) => SqlValue::Real(*r),
+        Literal::Text(t) => SqlValue::Text(t.clone()),
+        Literal::Blob(b) => SqlValue::Blob(b.clone()),
+        Literal::Bool(b) => SqlValue::Integer(*b as i64),
+    }
+}
+
+fn truth_value(truth: Option<bool>) -> SqlValue {
+    match truth {
+        Some(b) => SqlValue::Integer(b as i64),
+        None => SqlValue::Null,
+    }
+}
+
+/// Three-valued truth of a value: `Some(None)` for NULL. Text counts by its numeric prefix.
+pub fn truth(value: &SqlValue) -> Option<Option<bool>> {
+    match value {
+        SqlValue::Null => Some(None),
+        SqlValue::Integer(i) => Some(Some(*i != 0)),
+        value => Some(Some(real_value(value)? != 0.0)),
+    }
+}
+
+/// Affinity applied to both sides of a comparison; `None` or `Blob` converts nothing.
+fn comparison_affinity(left: Option<Affinity>, right: Option<Affinity>) -> Option<Affinity> {
+    match (left, right) {
+        (Some(l), Some(r)) => (l == Affinity::Numeric || r == Affinity::Numeric).then_some(Affinity::Numeric),
+        (Some(affinity), None) | (None, Some(affinity)) => Some(affinity),
+        (None, None) => None,
+    }
+}
+
+fn compare_values(
+    op: BinaryOp,
+    l: SqlValue,
+    r: SqlValue,
+    affinity: Option<Affinity>,
+    collation: Collation,
+) -> Option<SqlValue> {
+    if l == SqlValue::Null || r == SqlValue::Null {
+        return Some(match op {
+            BinaryOp::Is => truth_value(Some(l == r)),
+            BinaryOp::IsNot => truth_value(Some(l != r)),
+            _ => SqlValue::Null,
+        });
+    }
+    let (l, r) = match affinity {
+        Some(Affinity::Numeric) => (numeric_affinity(l)?, numeric_affinity(r)?),
+        Some(Affinity::Text) => (text_affinity(l)?, text_affinity(r)?),
+        _ => (l, r),
+    };
+    let ord = compare_storage(&l, &r, collation);
+    let result = match op {
+        BinaryOp::Eq | BinaryOp::Is => ord == Ordering::Equal,
+        BinaryOp::NotEq | BinaryOp::IsNot => ord != Ordering::Equal,
+        BinaryOp::Lt => ord == Ordering::Less,
+        BinaryOp::LtEq => ord != Ordering::Greater,
+        BinaryOp::Gt => ord == Ordering::Greater,
+        BinaryOp::GtEq => ord != Ordering::Less,
+        _ => return None,
+    };
+    Some(truth_value(Some(result)))
+}
+
+/// SQLite's ordering of values: NULL, then numbers, then text by collation, then blobs.
+fn compare_storage(a: &SqlValue, b: &SqlValue, collation: Collation) -> Ordering {
+    let class = |v: &SqlValue| match v {
+        SqlValue::Null => 0,
+        SqlValue::Integer(_) | SqlValue::Real(_) => 1,
+        SqlValue::Text(_) => 2,
+        SqlValue::Blob(_) => 3,
+    };
+    match (a, b) {
+        (SqlValue::Integer(x), SqlValue::Integer(y)) => x.cmp(y),
+        (SqlValue::Real(x), SqlValue::Real(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+        (SqlValue::Integer(i), SqlValue::Real(r)) => int_real_cmp(*i, *r),
+        (SqlValue::Real(r), SqlValue::Integer(i)) => int_real_cmp(*i, *r).reverse(),
+        (SqlValue::Text(x), SqlValue::Text(y)) => match collation {
+            Collation::Binary => x.as_bytes().cmp(y.as_bytes()),
+            Collation::NoCase => x.to_ascii_lowercase().as_bytes().cmp(y.to_ascii_lowercase().as_bytes()),
+            Collation::RTrim => x.trim_end_matches(' ').as_bytes().cmp(y.trim_end_matches(' ').as_bytes()),
+        },
+        (SqlValue::Blob(x), SqlValue::Blob(y)) => x.cmp(y),
+        _ => class(a).cmp(&class(b)),
+    }
+}
+
+/// Exact comparison of an integer with a real.
+fn int_real_cmp(i: i64, r: f64) -> Ordering {
+    if r < -9223372036854775808.0 {
+        return Ordering::Greater;
+    }
+    if r >= 9223372036854775808.0 {
+        return Ordering::Less;
+    }
+    let t = r.trunc();
+    match i.cmp(&(t as i64)) {
+        Ordering::Equal => 0.0.partial_cmp(&(r - t)).unwrap_or(Ordering::Equal),
+        ord => ord,
+    }
+}
+
+/// NUMERIC affinity converts text that is entirely a well-formed number.
+fn numeric_affinity(value: SqlValue) -> Option<SqlValue> {
+    if let SqlValue::Text(t) = &value {
+        let parsed = parse_number(t)?;
+        if parsed.has_digits && parsed.complete {
+            return Some(parsed.value.into_value());
+        }
+    }
+    Some(value)
+}
+
+/// TEXT affinity renders numbers as text.
+fn text_affinity(value: SqlValue) -> Option<SqlValue> {
+    match value {
+        SqlValue::Integer(_) | SqlValue::Real(_) => Some(SqlValue::Text(text_of(&value)?)),
+        value => Some(value),
+    }
+}
+
+/// The text of a non-NULL value, if it can be reproduced exactly.
+fn text_of(value: &SqlValue) -> Option<String> {
+    let text = match value {
+        SqlValue::Null => return None,
+        SqlValue::Integer(i) => i.to_string(),
+        SqlValue::Real(r) => real_to_text(*r)?,
+        SqlValue::Text(t) => t.clone(),
+        SqlValue::Blob(b) => String::from_utf8(b.clone()).ok()?,
+    };
+    // Text functions stop at a NUL
+    (!text.contains('\0')).then_some(text)
+}
+
+/// SQLite prints reals with `%!.15g`. That matches Rust's shortest representation when it
+/// needs at most 15 significant digits and no exponent.
+fn real_to_text(r: f64) -> Option<String> {
+    if r == 0.0 {
+        return (!r.is_sign_negative()).then(|| "0.0".to_string());
+    }
+    if !(1e-4..1e15).contains(&r.abs()) {
+        return None;
+    }
+    let text = format!("{:?}", r);
+    let significant = text.trim_start_matches(['-', '0', '.']).bytes().filter(u8::is_ascii_digit).count();
+    (significant <= 15).then_some(text)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn is_space(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r')
+}
+
+/// Parses the numeric prefix of `text` like `sqlite3AtoF`. Returns `None` for numbers whose
+/// conversion might round differently from SQLite's.
+fn parse_number(text: &str) -> Option<ParsedNumber> {
+    let b = text.as_bytes();
+    let mut i = 0;
+    while i < b.len() && is_space(b[i]) {
+        i += 1;
+    }
+    let start = i;
+    if i < b.len() && (b[i] == b'+' || b[i] == b'-') {
+        i += 1;
+    }
+    let mut digits = 0;
+    while i < b.len() && b[i].is_ascii_digit() {
+        i += 1;
+        digits += 1;
+    }
+    let mut real = false;
+    if i < b.len() && b[i] == b'.' {
+        real = true;
+        i += 1;
+        while i < b.len() && b[i].is_ascii_digit() {
+            i += 1;
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        return Some(ParsedNumber {
+            value: Number::Int(0),
+            has_digits: false,
+            complete: false,
+        });
+    }
+    if i < b.len() && (b[i] == b'e' || b[i] == b'E') {
+        let mut j = i + 1;
+        if j < b.len() && (b[j] == b'+' || b[j] == b'-') {
+            j += 1;
+        }
+        let exponent_start = j;
+        while j < b.len() && b[j].is_ascii_digit() {
+            j += 1;
+        }
+        // A dangling `e` or a huge exponent is not worth modelling
+        if j == exponent_start || j - exponent_start > 2 {
+            return None;
+        }
+        real = true;
+        i = j;
+    }
+    let number = &text[start..i];
+    let mantissa = number.split(['e', 'E']).next().unwrap_or_default();
+    let significant = mantissa
+        .trim_start_matches(['+', '-', '0', '.'])
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .count();
+    let value = if real {
+        if significant > 15 {
+            return None;
+        }
+        Number::Real(number.parse().ok()?)
+    } else {
+        if significant > 18 {
+            return None;
+        }
+        Number::Int(number.parse().ok()?)
+    };
+    Some(ParsedNumber {
+        value,
+        has_digits: true,
+        complete: b[i..].iter().all(|c| is_space(*c)),
+    })
+}
+
+/// The integer prefix of `text`, as CAST(.. AS INTEGER) and bitwise operators read it.
+fn int_prefix(text: &str) -> Option<i64> {
+    let trimmed = text.trim_start_matches(|c: char| c.is_ascii() && is_space(c as u8));
+    let unsigned = trimmed.strip_prefix(['+', '-']).unwrap_or(trimmed);
+    let digits = unsigned.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return Some(0);
+    }
+    if unsigned[..digits].trim_start_matches('0').len() > 18 {
+        return None;
+    }
+    trimmed[..trimmed.len() - unsigned.len() + digits].parse().ok()
+}
+
+/// A non-NULL value as an integer, like `sqlite3VdbeIntValue`.
+fn int_value(value: &SqlValue) -> Option<i64> {
+    match value {
+        SqlValue::Null => Some(0),
+        SqlValue::Integer(i) => Some(*i),
+        // Saturates, like SQLite
+        SqlValue::Real(r) => Some(*r as i64),
+        value => int_prefix(&text_of(value)?),
+    }
+}
+
+/// A non-NULL value as a real, like `sqlite3VdbeRealValue`.
+fn real_value(value: &SqlValue) -> Option<f64> {
+    match value {
+        SqlValue::Null => Some(0.0),
+        SqlValue::Integer(i) => Some(*i as f64),
+        SqlValue::Real(r) => Some(*r),
+        value => Some(parse_number(&text_of(value)?)?.value.as_f64()),
+    }
+}
+
+/// How an arithmetic operand is treated: text becomes the number its prefix spells.
+fn numeric_operand(value: &SqlValue) -> Option<Number> {
+    match value {
+        SqlValue::Integer(i) => Some(Number::Int(*i)),
+        SqlValue::Real(r) => Some(Number::Real(*r)),
+        value => Some(parse_number(&text_of(value)?)?.value),
+    }
+}
+
+fn arithmetic(op: BinaryOp, l: &SqlValue, r: &SqlValue) -> Option<SqlValue> {
+    if *l == SqlValue::Null || *r == SqlValue::Null {
+        return Some(SqlValue::Null);
+    }
+    let (a, b) = (numeric_operand(l)?, numeric_operand(r)?);
+    if let (Number::Int(a), Number::Int(b)) = (a, b) {
+        let result = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Sub => a.checked_sub(b),
+            BinaryOp::Mul => a.checked_mul(b),
+            BinaryOp::Div if b == 0 => return Some(SqlValue::Null),
+            BinaryOp::Div => a.checked_div(b),
+            BinaryOp::Mod if b == 0 => return Some(SqlValue::Null),
+            BinaryOp::Mod => Some(a.wrapping_rem(b)),
+            _ => return None,
+        };
+        // Integer overflow falls back to floating point
+        if let Some(result) = result {
+            return Some(SqlValue::Integer(result));
+        }
+    }
+    let (a, b) = (a.as_f64(), b.as_f64());
+    let result = match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Sub => a - b,
+        BinaryOp::Mul => a * b,
+        BinaryOp::Div if b == 0.0 => return Some(SqlValue::Null),
+        BinaryOp::Div => a / b,
+        // Remainders of reals are taken on the truncated integers
+        BinaryOp::Mod => {
+            let limit = 9223372036854775807.0;
+            if a.abs() >= limit || b.abs() >= limit {
+                return None;
+            }
+            match b as i64 {
+                0 => return Some(SqlValue::Null),
+                -1 => 0.0,
+                divisor => ((a as i64) % divisor) as f64,
+            }
+        }
+        _ => return None,
+    };
+    Some(if result.is_nan() { SqlValue::Null } else { SqlValue::Real(result) })
+}
+
+fn bitwise(op: BinaryOp, l: &SqlValue, r: &SqlValue) -> Option<SqlValue> {
+    if *l == SqlValue::Null || *r == SqlValue::Null {
+        return Some(SqlValue::Null);
+    }
+    let (a, b) = (int_value(l)?, int_value(r)?);
+    let result = match op {
+        BinaryOp::BitAnd => a & b,
+        BinaryOp::BitOr => a | b,
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => {
+            // A negative amount shifts the other way
+            let left = (op == BinaryOp::ShiftLeft) == (b >= 0);
+            let amount = b.unsigned_abs();
+            if amount >= 64 {
+                if left || a >= 0 { 0 } else { -1 }
+            } else if left {
+                ((a as u64) << amount) as i64
+            } else {
+                a >> amount
+            }
+        }
+        _ => return None,
+    };
+    Some(SqlValue::Integer(result))
+}
+
+fn cast(value: SqlValue, type_name: &str) -> Option<SqlValue> {
+    if value == SqlValue::Null {
+        return Some(SqlValue::Null);
+    }
+    Some(match ExprType::from_decl(type_name) {
+        ExprType::Integer => match value {
+            SqlValue::Real(r) if r.abs() >= 9223372036854775807.0 => return None,
+            value => SqlValue::Integer(int_value(&value)?),
+        },
+        ExprType::Real => SqlValue::Real(real_value(&value)?),
+        ExprType::Text => SqlValue::Text(text_of(&value)?),
+        ExprType::Blob => match value {
+            SqlValue::Blob(b) => SqlValue::Blob(b),
+            value => SqlValue::Blob(text_of(&value)?.into_bytes()),
+        },
+        // Numbers stay as they are; text keeps an integer prefix or a real that is not an
+        // exact small integer
+        ExprType::Numeric | ExprType::Bool => match value {
+            SqlValue::Integer(_) | SqlValue::Real(_) => value,
+            value => match parse_number(&text_of(&value)?)?.value {
+                Number::Real(r) if r.fract() == 0.0 && r.abs() < 2251799813685248.0 => SqlValue::Integer(r as i64),
+                number => number.into_value(),
+            },
+        },
+    })
+}
+
+/// LIKE/GLOB matching with `many` and `one` wildcards; `fold` ignores ASCII case.
+fn wildcard_match(text: &str, pattern: &str, many: char, one: char, fold: bool) -> bool {
+    let eq = |a: char, b: char| if fold { a.eq_ignore_ascii_case(&b) } else { a == b };
+    let (s, p): (Vec<char>, Vec<char>) = (text.chars().collect(), pattern.chars().collect());
+    let (mut i, mut j) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while i < s.len() {
+        if j < p.len() && p[j] == many {
+            backtrack = Some((j, i));
+            j += 1;
+        } else if j < p.len() && (p[j] == one || eq(p[j], s[i])) {
+            i += 1;
+            j += 1;
+        } else if let Some((star, from)) = backtrack {
+            j = star + 1;
+            i = from + 1;
+            backtrack = Some((star, from + 1));
+        } else {
+            return false;
+        }
+    }
+    p[j..].iter().all(|c| *c == many)
+}
+
+/// Direct subexpressions, in the order SQLite stores them: the left operand, then any
+/// argument list, then the right operand.
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Literal(_) | Expr::Column(_) | Expr::Subquery(_) | Expr::Exists { .. } | Expr::Raise { .. } => vec![],
+        Expr::Unary { expr, .. }
+        | Expr::Cast { expr, .. }
+        | Expr::IsNull { expr, .. }
+        | Expr::Collate { expr, .. }
+        | Expr::InSubquery { expr, .. } => vec![&**expr],
+        Expr::Binary { left, right, .. } => vec![&**left, &**right],
+        Expr::Case {
+            operand,
+            when_then,
+            else_expr,
+        } => operand
+            .iter()
+            .map(|e| &**e)
+            .chain(when_then.iter().flat_map(|(when, then)| [when, then]))
+            .chain(else_expr.iter().map(|e| &**e))
+            .collect(),
+        Expr::Between { expr, low, high, .. } => vec![&**expr, &**low, &**high],
+        Expr::InList { expr, list, .. } => std::iter::once(&**expr).chain(list).collect(),
+        // `x LIKE p` is the function call like(p, x)
+        Expr::Like { expr, pattern, .. } => vec![&**pattern, &**expr],
+        Expr::Function(call) => call.args.iter().collect(),
+    }
+}
+
+/// Whether a COLLATE operator inside `expr` makes SQLite flag it as carrying a collation.
+/// BETWEEN only inherits the flag from its left operand.
+fn has_collate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Collate { .. } => true,
+        Expr::Between { expr, .. } => has_collate(expr),
+        expr => children(expr).into_iter().any(has_collate),
+    }
+}
+
+/// The explicit collation of an expression, found the way `sqlite3ExprCollSeq` searches.
+fn collation(expr: &Expr) -> Option<Collation> {
+    match expr {
+        Expr::Collate { collation, .. } => Some(*collation),
+        Expr::Cast { expr, .. }
+        | Expr::Unary {
+            op: UnaryOp::Plus,
+            expr,
+        } => collation(expr),
+        expr if has_collate(expr) => {
+            let next = match expr {
+                Expr::Binary { left, right, .. } => {
+                    if has_collate(left) {
+                        left
+                    } else {
+                        right
+                    }
+                }
+                expr => children(expr).into_iter().find(|child| has_collate(child))?,
+            };
+            collation(next)
+        }
+        _ => None,
+    }
+}
+
+/// COLLATE inside a BETWEEN bound or IN list is handled by SQLite code paths that are not
+/// modelled here.
+fn has_ambiguous_collation(expr: &Expr) -> bool {
+    let ambiguous = match expr {
+        Expr::Between { low, high, .. } => has_collate(low) || has_collate(high),
+        Expr::InList { list, .. } => list.iter().any(has_collate),
+        _ => false,
+    };
+    ambiguous || children(expr).into_iter().any(has_ambiguous_collation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::{Dialect, FromClause, SelectCore, SelectItem, SelectStmt, Stmt, TableRef};
+    use crate::generators::common::expr_common::ExprGenerator;
+    use sqlsmith_rs_common::rand_by_seed::LcgRng;
+
+    #[test]
+    fn test_evaluator_agrees_with_sqlite() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE t (a INTEGER, b REAL, c TEXT, d BLOB, e NUMERIC, f);
+             INSERT INTO t VALUES (1, 2.5, 'val1', X'0102', '2021-03-04', NULL);
+             INSERT INTO t VALUES (-7, 0.0, ' 12 ', 'abc', 3.75, 'VAL1');
+             INSERT INTO t VALUES (NULL, -1e3, '4.5e1x', NULL, 10, 7);
+             INSERT INTO t VALUES (9223372036854775807, 1e-3, 'Val1  ', X'76616c31', NULL, 2.0);",
+        )
+        .unwrap();
+        let columns: Vec<(String, String)> =
+            [("a", "INTEGER"), ("b", "REAL"), ("c", "TEXT"), ("d", "BLOB"), ("e", "NUMERIC"), ("f", "")]
+                .iter()
+                .map(|(n, t)| (n.to_string(), t.to_string()))
+                .collect();
+        let rows: Vec<Vec<SqlValue>> = conn
+            .prepare("SELECT a, b, c, d, e, f FROM t ORDER BY rowid")
+            .unwrap()
+            .query_map([], |row| {
+                (0..6)
+                    .map(|i| {
+                        Ok(match row.get_ref(i)? {
+                            rusqlite::types::ValueRef::Null => SqlValue::Null,
+                            rusqlite::types::ValueRef::Integer(v) => SqlValue::Integer(v),
+                            rusqlite::types::ValueRef::Real(v) => SqlValue::Real(v),
+                            rusqlite::types::ValueRef::Text(v) => SqlValue::Text(String::from_utf8_lossy(v).into()),
+                            rusqlite::types::ValueRef::Blob(v) => SqlValue::Blob(v.to_vec()),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        let expr_gen = ExprGenerator::new(3).with_columns(None, &columns);
+        let mut rng = LcgRng::new(13);
+        let mut modelled = 0;
+        for n in 0..3000 {
+            let expr = if n % 2 == 0 { expr_gen.gen_predicate(&mut rng) } else { expr_gen.gen_any(&mut rng) };
+            let sql = Stmt::Select(SelectStmt::new(SelectCore {
+                distinct: false,
+                columns: vec![SelectItem {
+                    expr: expr.clone(),
+                    alias: None,
+                }],
+                from: Some(FromClause::new(TableRef::new("t"))),
+                where_clause: None,
+                group_by: vec![],
+                having: None,
+                windows: vec![],
+            }))
+            .to_sql(Dialect::Sqlite);
+            let Ok(mut stmt) = conn.prepare(&format!("{} ORDER BY rowid", sql.trim_end_matches(';'))) else {
+                continue;
+            };
+            let Ok(results) = stmt
+                .query_map([], |row| {
+                    Ok(match row.get_ref(0)? {
+                        rusqlite::types::ValueRef::Null => SqlValue::Null,
+                        rusqlite::types::ValueRef::Integer(v) => SqlValue::Integer(v),
+                        rusqlite::types::ValueRef::Real(v) => SqlValue::Real(v),
+                        rusqlite::types::ValueRef::Text(v) => SqlValue::Text(String::from_utf8_lossy(v).into()),
+                        rusqlite::types::ValueRef::Blob(v) => SqlValue::Blob(v.to_vec()),
+                    })
+                })
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            else {
+                continue;
+            };
+            for (row, expected) in rows.iter().zip(results) {
+                if let Some(actual) = RowEvaluator::new(&columns, row).eval(&expr) {
+                    modelled += 1;
+                    assert_eq!(actual, expected, "{} on row {:?}", sql, row);
+                }
+            }
+        }
+        assert!(modelled > 3000, "too few expressions modelled: {}", modelled);
+    }
+}
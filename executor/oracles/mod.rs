@@ -0,0 +1,98 @@
+// Logic bug oracles: each one rewrites a generated query into queries that must return the
+// same result, and reports when they do not.
+use crate::generators::GeneratorOptions;
+use sqlsmith_rs_common::profile::OracleKind;
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::{DatabaseDriver, SqlValue};
+
+mod eval;
+
+mod noopt;
+pub use noopt::NoOptOracle;
+
+mod norec;
+pub use norec::NoRecOracle;
+
+mod pqs;
+pub use pqs::PqsOracle;
+
+mod tlp;
+pub use tlp::TlpOracle;
+
+/// A query whose result disagrees with an equivalent rewrite.
+pub struct Mismatch {
+    /// The original query
+    pub sql: String,
+    /// What differed, including the rewritten query
+    pub detail: String,
+    /// Runs the same queries again on another database and reports whether they still
+    /// disagree, so the statements leading up to the mismatch can be reduced
+    pub recheck: Recheck,
+}
+
+/// Reports whether a mismatch still shows on the given database.
+pub type Recheck = Box<dyn Fn(&dyn DatabaseDriver) -> bool>;
+
+pub trait Oracle {
+    /// Short name, used as the kind of the recorded finding.
+    fn name(&self) -> &'static str;
+    /// Generates one query against the current schema and checks it. Queries that fail to
+    /// run are skipped, not reported.
+    fn check(&self, driver: &dyn DatabaseDriver, rng: &mut LcgRng) -> Option<Mismatch>;
+}
+
+pub fn new_oracle(kind: OracleKind) -> Box<dyn Oracle> {
+    // A subquery that yields several rows where one is expected may return any of them, and
+    // which one depends on the plan and on how the query was rewritten, so the queries the
+    // oracles check are generated without nesting
+    let options = GeneratorOptions { subquery_depth: 0 };
+    match kind {
+        OracleKind::Tlp => Box::new(TlpOracle::new(options)),
+        OracleKind::NoRec => Box::new(NoRecOracle::new(options)),
+        OracleKind::Pqs => Box::new(PqsOracle::new()),
+        OracleKind::NoOpt => Box::new(NoOptOracle::new(options)),
+    }
+}
+
+/// The CREATE statements of every schema object, kept with a finding so it can be replayed.
+pub fn schema_state(driver: &dyn DatabaseDriver) -> Vec<String> {
+    match driver.query_rows("SELECT sql FROM sqlite_schema WHERE sql IS NOT NULL;") {
+        Ok(rows) => rows
+            .into_iter()
+            .filter_map(|row| match row.into_iter().next() {
+                Some(SqlValue::Text(sql)) => Some(sql),
+                _ => None,
+            })
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to read schema state: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Canonical text of a row, with reals rounded so summation order does not matter.
+pub(crate) fn row_key(row: &[SqlValue]) -> String {
+    row.iter()
+        .map(|v| match v {
+            SqlValue::Null => "NULL".to_string(),
+            SqlValue::Integer(i) => format!("i:{}", i),
+            SqlValue::Real(f) => format!("r:{:.9e}", f),
+            SqlValue::Text(t) => format!("t:{:?}", t),
+            SqlValue::Blob(b) => format!("b:{:02x?}", b),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Compares two result sets as multisets.
+pub(crate) fn same_rows(a: &[Vec<SqlValue>], b: &[Vec<SqlValue>]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut ka: Vec<String> = a.iter().map(|r| row_key(r)).collect();
+    let mut kb: Vec<String> = b.iter().map(|r| row_key(r)).collect();
+    ka.sort();
+    kb.sort();
+    ka == kb
+}
@@ -0,0 +1,61 @@
+// Optimization toggling: the same query over the same database must return the same rows
+// whether or not the query optimizer is allowed to rewrite it.
+use super::{Mismatch, Oracle, same_rows};
+use crate::generators::ast::{Dialect, SelectCore, SelectStmt, Stmt};
+use crate::generators::{GeneratorOptions, get_oracle_query};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::DatabaseDriver;
+
+pub struct NoOptOracle {
+    options: GeneratorOptions,
+}
+
+impl NoOptOracle {
+    pub fn new(options: GeneratorOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Oracle for NoOptOracle {
+    fn name(&self) -> &'static str {
+        "noopt"
+    }
+
+    fn check(&self, driver: &dyn DatabaseDriver, rng: &mut LcgRng) -> Option<Mismatch> {
+        let (query, predicate) = get_oracle_query(driver, rng, &self.options)?;
+        let query = SelectStmt::new(SelectCore {
+            where_clause: Some(predicate),
+            ..query.core
+        });
+        let sql = Stmt::Select(query).to_sql(Dialect::from(driver.kind()));
+
+        let detail = disagreement(driver, &sql)?;
+        Some(Mismatch {
+            sql: sql.clone(),
+            detail,
+            recheck: Box::new(move |driver| disagreement(driver, &sql).is_some()),
+        })
+    }
+}
+
+/// Runs `sql` with and without optimizations and describes the difference, if any.
+fn disagreement(driver: &dyn DatabaseDriver, sql: &str) -> Option<String> {
+    let expected = driver.query_rows(sql).ok()?;
+    if let Err(e) = driver.set_optimizations(false) {
+        log::warn!("Cannot disable optimizations: {}", e);
+        return None;
+    }
+    let actual = driver.query_rows(sql);
+    if let Err(e) = driver.set_optimizations(true) {
+        log::warn!("Failed to re-enable optimizations: {}", e);
+    }
+    let actual = actual.ok()?;
+
+    (!same_rows(&expected, &actual)).then(|| {
+        format!(
+            "Optimization mismatch: {} rows with optimizations enabled, {} with them disabled",
+            expected.len(),
+            actual.len()
+        )
+    })
+}
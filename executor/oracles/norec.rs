@@ -0,0 +1,131 @@
+// Non-optimizing reference engine construction: `SELECT .. FROM t WHERE p` goes through the
+// query optimizer, while evaluating `p` as a plain expression on every row of `t` does not.
+// Both must agree on how many rows satisfy `p`.
+use super::{Mismatch, Oracle};
+use crate::generators::ast::{
+    BinaryOp, Dialect, Expr, FunctionCall, Literal, SelectCore, SelectItem, SelectStmt, Stmt,
+};
+use crate::generators::{GeneratorOptions, get_oracle_query};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::{DatabaseDriver, SqlValue};
+
+pub struct NoRecOracle {
+    options: GeneratorOptions,
+}
+
+impl NoRecOracle {
+    pub fn new(options: GeneratorOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Oracle for NoRecOracle {
+    fn name(&self) -> &'static str {
+        "norec"
+    }
+
+    fn check(&self, driver: &dyn DatabaseDriver, rng: &mut LcgRng) -> Option<Mismatch> {
+        let (query, predicate) = get_oracle_query(driver, rng, &self.options)?;
+        let dialect = Dialect::from(driver.kind());
+        let summed = rng.rand().unsigned_abs().is_multiple_of(2);
+
+        let optimized = SelectStmt::new(SelectCore {
+            where_clause: Some(predicate.clone()),
+            ..query.core.clone()
+        });
+        let unoptimized = SelectStmt::new(SelectCore {
+            columns: vec![SelectItem {
+                expr: if summed { sum_of_matches(predicate) } else { is_true(predicate) },
+                alias: None,
+            }],
+            ..query.core
+        });
+        let optimized = Stmt::Select(optimized).to_sql(dialect);
+        let unoptimized = Stmt::Select(unoptimized).to_sql(dialect);
+
+        let detail = disagreement(driver, &optimized, &unoptimized, summed)?;
+        Some(Mismatch {
+            sql: optimized.clone(),
+            detail,
+            recheck: Box::new(move |driver| disagreement(driver, &optimized, &unoptimized, summed).is_some()),
+        })
+    }
+}
+
+/// Runs both queries and describes how many rows each counted, if the counts differ.
+fn disagreement(driver: &dyn DatabaseDriver, optimized: &str, unoptimized: &str, summed: bool) -> Option<String> {
+    // Errors on either side are skipped; `p` may fail on rows the optimizer never reaches
+    let expected = driver.query_rows(optimized).ok()?.len() as i64;
+    let rows = driver.query_rows(unoptimized).ok()?;
+    let actual = if summed {
+        // SUM over no rows is NULL
+        match rows.first().and_then(|row| row.first()) {
+            Some(SqlValue::Integer(n)) => *n,
+            _ => 0,
+        }
+    } else {
+        rows.iter().filter(|row| matches!(row.first(), Some(SqlValue::Integer(1)))).count() as i64
+    };
+
+    (expected != actual).then(|| {
+        format!(
+            "NoREC mismatch: WHERE matched {} rows, evaluating the predicate per row matched {}; unoptimized query: {}",
+            expected, actual, unoptimized
+        )
+    })
+}
+
+/// `SUM(CASE WHEN p THEN 1 ELSE 0 END)`
+fn sum_of_matches(predicate: Expr) -> Expr {
+    let case = Expr::Case {
+        operand: None,
+        when_then: vec![(predicate, Expr::Literal(Literal::Integer(1)))],
+        else_expr: Some(Box::new(Expr::Literal(Literal::Integer(0)))),
+    };
+    Expr::function(FunctionCall::new("sum", vec![case]))
+}
+
+/// `(p) IS TRUE`, which is 0 rather than NULL when `p` is NULL.
+fn is_true(predicate: Expr) -> Expr {
+    Expr::binary(BinaryOp::Is, predicate, Expr::Literal(Literal::Bool(true)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::{FromClause, TableRef};
+
+    #[test]
+    fn test_rewrites_count_rows_matching_the_predicate() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (a INTEGER); INSERT INTO t VALUES (1), (2), (NULL), (3);")
+            .unwrap();
+        let predicate = Expr::binary(BinaryOp::Gt, Expr::column("a"), Expr::Literal(Literal::Integer(1)));
+        let query = |expr: Expr| {
+            let core = SelectCore {
+                distinct: false,
+                columns: vec![SelectItem { expr, alias: None }],
+                from: Some(FromClause::new(TableRef::new("t"))),
+                where_clause: None,
+                group_by: vec![],
+                having: None,
+                windows: vec![],
+            };
+            Stmt::Select(SelectStmt::new(core)).to_sql(Dialect::Sqlite)
+        };
+
+        let summed: i64 = conn
+            .query_row(&query(sum_of_matches(predicate.clone())), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(summed, 2);
+
+        let flags: Vec<i64> = conn
+            .prepare(&query(is_true(predicate)))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(flags, vec![0, 1, 0, 1], "NULL predicates must count as not matching");
+    }
+}
@@ -0,0 +1,111 @@
+// Pivoted query synthesis: pick a row of a table, generate a predicate, evaluate it on that
+// row in Rust and rectify it so it holds. Querying the table with the rectified predicate
+// must then return the pivot row.
+use super::eval::{RowEvaluator, truth};
+use super::{Mismatch, Oracle, row_key};
+use crate::generators::ast::{Dialect, Expr, FromClause, SelectCore, SelectItem, SelectStmt, Stmt, TableRef, UnaryOp};
+use crate::generators::common::expr_common::{DEFAULT_EXPR_DEPTH, ExprGenerator};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::{DatabaseDriver, SqlValue};
+
+pub struct PqsOracle;
+
+impl PqsOracle {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Oracle for PqsOracle {
+    fn name(&self) -> &'static str {
+        "pqs"
+    }
+
+    fn check(&self, driver: &dyn DatabaseDriver, rng: &mut LcgRng) -> Option<Mismatch> {
+        let tables = driver.get_tables().ok()?;
+        if tables.is_empty() {
+            return None;
+        }
+        let table = &tables[(rng.rand().unsigned_abs() as usize) % tables.len()];
+        if table.columns.is_empty() || has_collations(driver, &table.name)? {
+            return None;
+        }
+        let dialect = Dialect::from(driver.kind());
+        let select = |where_clause: Option<Expr>| {
+            let core = SelectCore {
+                distinct: false,
+                columns: table
+                    .columns
+                    .iter()
+                    .map(|(name, _)| SelectItem {
+                        expr: Expr::column(name),
+                        alias: None,
+                    })
+                    .collect(),
+                from: Some(FromClause::new(TableRef::new(&table.name))),
+                where_clause,
+                group_by: vec![],
+                having: None,
+                windows: vec![],
+            };
+            Stmt::Select(SelectStmt::new(core)).to_sql(dialect)
+        };
+
+        // Columns are listed explicitly: generated columns are not part of the schema we read
+        let all = select(None);
+        let rows = driver.query_rows(&all).ok()?;
+        if rows.is_empty() {
+            return None;
+        }
+        let pivot = &rows[(rng.rand().unsigned_abs() as usize) % rows.len()];
+
+        let predicate = ExprGenerator::new(DEFAULT_EXPR_DEPTH)
+            .with_columns(None, &table.columns)
+            .gen_predicate(rng);
+        // Predicates the evaluator cannot model are skipped
+        let value = RowEvaluator::new(&table.columns, pivot).eval(&predicate)?;
+        let rectified = match truth(&value)? {
+            Some(true) => predicate,
+            Some(false) => Expr::unary(UnaryOp::Not, predicate),
+            None => Expr::IsNull {
+                expr: Box::new(predicate),
+                negated: false,
+            },
+        };
+
+        let sql = select(Some(rectified));
+        let pivot_key = row_key(pivot);
+        let returned = missing_pivot(driver, &all, &sql, &pivot_key)?;
+        Some(Mismatch {
+            sql: sql.clone(),
+            detail: format!(
+                "PQS mismatch: pivot row {} of {} is missing from the {} rows returned",
+                pivot_key, table.name, returned
+            ),
+            recheck: Box::new(move |driver| missing_pivot(driver, &all, &sql, &pivot_key).is_some()),
+        })
+    }
+}
+
+/// The number of rows `sql` returned, if the pivot row is in the table (`all`) but not among them.
+fn missing_pivot(driver: &dyn DatabaseDriver, all: &str, sql: &str, pivot_key: &str) -> Option<usize> {
+    if !driver.query_rows(all).ok()?.iter().any(|row| row_key(row) == pivot_key) {
+        return None;
+    }
+    let result = driver.query_rows(sql).ok()?;
+    (!result.iter().any(|row| row_key(row) == pivot_key)).then_some(result.len())
+}
+
+/// Whether the table declares column collations, which the evaluator does not model.
+fn has_collations(driver: &dyn DatabaseDriver, table: &str) -> Option<bool> {
+    let rows = driver
+        .query_rows(&format!(
+            "SELECT sql FROM sqlite_schema WHERE type='table' AND name='{}';",
+            table.replace('\'', "''")
+        ))
+        .ok()?;
+    match rows.first().and_then(|row| row.first()) {
+        Some(SqlValue::Text(sql)) => Some(sql.to_uppercase().contains("COLLATE")),
+        _ => None,
+    }
+}
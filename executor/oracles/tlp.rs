@@ -0,0 +1,271 @@
+// Ternary logic partitioning: every row makes a predicate `p` either true, false or NULL, so
+// `WHERE p`, `WHERE NOT p` and `WHERE p IS NULL` split a query into three parts whose union
+// is the query itself.
+use super::{Mismatch, Oracle, same_rows};
+use crate::generators::ast::{
+    Collation, CompoundOp, Dialect, Expr, FromClause, FunctionCall, SelectCore, SelectItem, SelectStmt, Stmt,
+    TableOrSubquery, UnaryOp,
+};
+use crate::generators::{GeneratorOptions, get_oracle_query};
+use sqlsmith_rs_common::rand_by_seed::LcgRng;
+use sqlsmith_rs_drivers::{DatabaseDriver, SqlValue};
+
+/// Aggregates whose result over a union can be computed from the per-partition results,
+/// paired with the aggregate that combines them.
+const AGGREGATES: &[(&str, &str)] = &[("min", "min"), ("max", "max"), ("sum", "sum"), ("count", "sum")];
+
+/// Name of the per-partition aggregate column and of the derived table holding them.
+const PARTIAL_NAME: &str = "partial";
+
+pub struct TlpOracle {
+    options: GeneratorOptions,
+}
+
+impl TlpOracle {
+    pub fn new(options: GeneratorOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Oracle for TlpOracle {
+    fn name(&self) -> &'static str {
+        "tlp"
+    }
+
+    fn check(&self, driver: &dyn DatabaseDriver, rng: &mut LcgRng) -> Option<Mismatch> {
+        let (query, predicate) = get_oracle_query(driver, rng, &self.options)?;
+        let dialect = Dialect::from(driver.kind());
+        let mut collations = vec![];
+        let (original, partitioned, variant) = match rng.rand().unsigned_abs() % 3 {
+            0 => {
+                let partitioned = union(partitions(&query.core, &predicate), CompoundOp::UnionAll);
+                (query, partitioned, "multiset")
+            }
+            1 => {
+                let mut core = query.core;
+                core.distinct = true;
+                collations = projected_collations(&core);
+                let partitioned = union(partitions(&core, &predicate), CompoundOp::Union);
+                (SelectStmt::new(core), partitioned, "distinct")
+            }
+            _ => {
+                let (name, combine) = AGGREGATES[(rng.rand().unsigned_abs() as usize) % AGGREGATES.len()];
+                let (original, partitioned) = aggregate_queries(query.core, &predicate, name, combine);
+                (original, partitioned, name)
+            }
+        };
+        let original = Stmt::Select(original).to_sql(dialect);
+        let partitioned = Stmt::Select(partitioned).to_sql(dialect);
+
+        let detail = disagreement(driver, &original, &partitioned, variant, &collations)?;
+        Some(Mismatch {
+            sql: original.clone(),
+            detail,
+            recheck: Box::new(move |driver| disagreement(driver, &original, &partitioned, variant, &collations).is_some()),
+        })
+    }
+}
+
+/// Runs both queries and describes how their results differ, if they do. `collations` are
+/// those of the projected columns, for the distinct variant.
+fn disagreement(
+    driver: &dyn DatabaseDriver,
+    original: &str,
+    partitioned: &str,
+    variant: &str,
+    collations: &[Option<Collation>],
+) -> Option<String> {
+    // A predicate can fail on rows the original query never evaluates it on, so errors
+    // on either side are not logic bugs
+    let expected = driver.query_rows(original).ok()?;
+    let actual = driver.query_rows(partitioned).ok()?;
+    let agree = match variant {
+        // DISTINCT may keep any of several rows it treats as equal, e.g. 1 and 1.0, or text under a collation
+        "distinct" => same_rows(&fold_distinct(&expected, collations), &fold_distinct(&actual, collations)),
+        "multiset" => same_rows(&expected, &actual),
+        _ => same_aggregate(&expected, &actual),
+    };
+    (!agree).then(|| {
+        format!(
+            "TLP {} mismatch: query returned {}, partitions returned {}; partitioned query: {}",
+            variant,
+            describe(&expected),
+            describe(&actual),
+            partitioned
+        )
+    })
+}
+
+/// The collation each projected column names with a top-level COLLATE, or None where it
+/// comes from elsewhere, e.g. a column's declaration.
+fn projected_collations(core: &SelectCore) -> Vec<Option<Collation>> {
+    core.columns
+        .iter()
+        .map(|item| match &item.expr {
+            Expr::Collate { collation, .. } => Some(*collation),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Replaces values by one representative of those DISTINCT treats as equal: integral reals by
+/// integers, and text by what its column's collation compares. Where the collation is not
+/// known, text is folded under both NOCASE and RTRIM, the only other collations generated.
+fn fold_distinct(rows: &[Vec<SqlValue>], collations: &[Option<Collation>]) -> Vec<Vec<SqlValue>> {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(i, v)| match (v, collations.get(i).copied().flatten()) {
+                    (SqlValue::Real(f), _) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => SqlValue::Integer(*f as i64),
+                    (SqlValue::Text(t), Some(Collation::NoCase)) => SqlValue::Text(t.to_ascii_lowercase()),
+                    (SqlValue::Text(t), Some(Collation::RTrim)) => SqlValue::Text(t.trim_end_matches(' ').to_string()),
+                    (SqlValue::Text(t), None) => SqlValue::Text(t.trim_end_matches(' ').to_ascii_lowercase()),
+                    (other, _) => other.clone(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `core` restricted by `WHERE p`, `WHERE NOT p` and `WHERE p IS NULL`.
+fn partitions(core: &SelectCore, predicate: &Expr) -> [SelectCore; 3] {
+    let conditions = [
+        predicate.clone(),
+        Expr::unary(UnaryOp::Not, predicate.clone()),
+        Expr::IsNull {
+            expr: Box::new(predicate.clone()),
+            negated: false,
+        },
+    ];
+    conditions.map(|condition| SelectCore {
+        where_clause: Some(condition),
+        ..core.clone()
+    })
+}
+
+fn union([first, second, third]: [SelectCore; 3], op: CompoundOp) -> SelectStmt {
+    SelectStmt {
+        compounds: vec![(op, second), (op, third)],
+        ..SelectStmt::new(first)
+    }
+}
+
+/// `SELECT name(e) FROM ..` over the whole query, and the same aggregate computed per
+/// partition and combined with `combine`. MIN and MAX compare under BINARY on both sides,
+/// since the per-partition results lose the column's collation.
+fn aggregate_queries(core: SelectCore, predicate: &Expr, name: &str, combine: &str) -> (SelectStmt, SelectStmt) {
+    let mut arg = core.columns[0].expr.clone();
+    if name == "min" || name == "max" {
+        arg = Expr::Collate {
+            expr: Box::new(arg),
+            collation: Collation::Binary,
+        };
+    }
+    let aggregate = SelectCore {
+        columns: vec![SelectItem {
+            expr: Expr::function(FunctionCall::new(name, vec![arg])),
+            alias: Some(PARTIAL_NAME.to_string()),
+        }],
+        ..core
+    };
+
+    let combined = SelectCore {
+        distinct: false,
+        columns: vec![SelectItem {
+            expr: Expr::function(FunctionCall::new(combine, vec![Expr::column(PARTIAL_NAME)])),
+            alias: None,
+        }],
+        from: Some(FromClause {
+            table: TableOrSubquery::Subquery {
+                query: Box::new(union(partitions(&aggregate, predicate), CompoundOp::UnionAll)),
+                alias: PARTIAL_NAME.to_string(),
+            },
+            joins: vec![],
+        }),
+        where_clause: None,
+        group_by: vec![],
+        having: None,
+        windows: vec![],
+    };
+    (SelectStmt::new(aggregate), SelectStmt::new(combined))
+}
+
+/// Compares single-value results, treating integers and reals of the same value as equal
+/// since a sum over partitions may come out as either.
+fn same_aggregate(a: &[Vec<SqlValue>], b: &[Vec<SqlValue>]) -> bool {
+    let numeric = |rows: &[Vec<SqlValue>]| -> Vec<Vec<SqlValue>> {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|v| match v {
+                        SqlValue::Integer(i) => SqlValue::Real(*i as f64),
+                        other => other.clone(),
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+    same_rows(&numeric(a), &numeric(b))
+}
+
+fn describe(rows: &[Vec<SqlValue>]) -> String {
+    match rows {
+        [row] if row.len() == 1 => format!("{:?}", row[0]),
+        _ => format!("{} rows", rows.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::ast::{BinaryOp, Literal, TableRef};
+
+    #[test]
+    fn test_partitions_cover_every_row_once() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (a INTEGER); INSERT INTO t VALUES (1), (2), (NULL), (2);")
+            .unwrap();
+        let core = SelectCore {
+            distinct: false,
+            columns: vec![SelectItem {
+                expr: Expr::column("a"),
+                alias: None,
+            }],
+            from: Some(FromClause::new(TableRef::new("t"))),
+            where_clause: None,
+            group_by: vec![],
+            having: None,
+            windows: vec![],
+        };
+        let predicate = Expr::binary(BinaryOp::Gt, Expr::column("a"), Expr::Literal(Literal::Integer(1)));
+        let count = |query: SelectStmt| {
+            let sql = Stmt::Select(query).to_sql(Dialect::Sqlite);
+            conn.prepare(&sql).unwrap().query_map([], |_| Ok(())).unwrap().count()
+        };
+
+        assert_eq!(count(union(partitions(&core, &predicate), CompoundOp::UnionAll)), 4);
+
+        let (original, partitioned) = aggregate_queries(core, &predicate, "count", "sum");
+        let value = |query: SelectStmt| -> i64 {
+            let sql = Stmt::Select(query).to_sql(Dialect::Sqlite);
+            conn.query_row(&sql, [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(value(original), 3);
+        assert_eq!(value(partitioned), 3);
+    }
+
+    #[test]
+    fn test_distinct_rows_compare_as_distinct_does() {
+        let text = |t: &str| vec![SqlValue::Text(t.to_string())];
+        let nocase = [Some(Collation::NoCase)];
+        assert!(same_rows(&fold_distinct(&[text("A")], &nocase), &fold_distinct(&[text("a")], &nocase)));
+        assert!(!same_rows(&fold_distinct(&[text("A")], &nocase), &fold_distinct(&[text("b")], &nocase)));
+        let binary = [Some(Collation::Binary)];
+        assert!(!same_rows(&fold_distinct(&[text("A")], &binary), &fold_distinct(&[text("a")], &binary)));
+        assert!(same_rows(&fold_distinct(&[text("a ")], &[None]), &fold_distinct(&[text("A")], &[None])));
+        let numbers = [vec![SqlValue::Integer(1)], vec![SqlValue::Real(2.5)]];
+        let reals = [vec![SqlValue::Real(1.0)], vec![SqlValue::Real(2.5)]];
+        assert!(same_rows(&fold_distinct(&numbers, &[None]), &fold_distinct(&reals, &[None])));
+    }
+}
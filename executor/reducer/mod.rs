@@ -0,0 +1,204 @@
+// Test-case reduction: a failing statement sequence is replayed on fresh connections while
+// statements are dropped (delta debugging) and then simplified through their syntax trees,
+// keeping every change that still reproduces the failure.
+use crate::generators::GeneratedStmt;
+use crate::generators::ast::{Dialect, Stmt};
+use sqlsmith_rs_drivers::{DRIVER_KIND, DatabaseDriver, new_conn};
+
+mod shrink;
+
+/// Replays one reduction may spend; each one rebuilds the database from scratch.
+const MAX_REPLAYS: usize = 500;
+
+/// One executed statement, with its syntax tree when it was generated as one.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub sql: String,
+    pub stmt: Option<Stmt>,
+}
+
+impl Step {
+    pub fn new(generated: GeneratedStmt, dialect: Dialect) -> Self {
+        let sql = generated.to_sql(dialect);
+        match generated {
+            GeneratedStmt::Ast(stmt) => Self { sql, stmt: Some(*stmt) },
+            GeneratedStmt::Text(_) => Self { sql, stmt: None },
+        }
+    }
+
+    pub fn text(sql: &str) -> Self {
+        Self {
+            sql: sql.to_string(),
+            stmt: None,
+        }
+    }
+}
+
+/// What a reduced sequence must still do.
+pub enum Target<'a> {
+    /// The last statement fails with this [`error_signature`]
+    Error(String),
+    /// The check reports the failure once every statement has run
+    Check(&'a dyn Fn(&dyn DatabaseDriver) -> bool),
+}
+
+/// Error code and message of a failed statement, which a reduced sequence must reproduce exactly.
+pub fn error_signature(e: &anyhow::Error) -> String {
+    match e.downcast_ref::<rusqlite::Error>() {
+        Some(rusqlite::Error::SqliteFailure(code, message)) => {
+            format!("{:?}: {}", code.code, message.as_deref().unwrap_or_default())
+        }
+        // Errors pointing at a token repeat the statement, which a reduced one no longer matches
+        Some(rusqlite::Error::SqlInputError { error, msg, .. }) => format!("{:?}: {}", error.code, msg),
+        _ => e.to_string(),
+    }
+}
+
+/// Shrinks `steps` to a smaller sequence that still hits `target` on a fresh SQLite database.
+/// Returns a copy of the input if it does not reproduce at all.
+pub fn reduce(steps: &[Step], target: &Target) -> Vec<Step> {
+    let mut reducer = Reducer { target, replays: 0 };
+    if !reducer.reproduces(steps) {
+        log::warn!("Failure does not reproduce on a fresh database, keeping all {} statements", steps.len());
+        return steps.to_vec();
+    }
+    let before = steps.len();
+    let steps = reducer.drop_statements(steps.to_vec());
+    let steps = reducer.shrink_statements(steps);
+    log::info!(
+        "Reduced {} statements to {} in {} replays",
+        before,
+        steps.len(),
+        reducer.replays
+    );
+    steps
+}
+
+struct Reducer<'a> {
+    target: &'a Target<'a>,
+    replays: usize,
+}
+
+impl Reducer<'_> {
+    fn reproduces(&mut self, steps: &[Step]) -> bool {
+        if self.replays >= MAX_REPLAYS {
+            return false;
+        }
+        self.replays += 1;
+        let driver = match new_conn(DRIVER_KIND::SQLITE_IN_MEM) {
+            Ok(driver) => driver,
+            Err(e) => {
+                log::warn!("Failed to open a database to replay on: {}", e);
+                return false;
+            }
+        };
+        match self.target {
+            Target::Error(signature) => {
+                let Some((last, prefix)) = steps.split_last() else {
+                    return false;
+                };
+                for step in prefix {
+                    let _ = driver.exec(&step.sql);
+                }
+                matches!(driver.exec(&last.sql), Err(e) if error_signature(&e) == *signature)
+            }
+            Target::Check(check) => {
+                for step in steps {
+                    let _ = driver.exec(&step.sql);
+                }
+                check(&*driver)
+            }
+        }
+    }
+
+    /// Removes statements; the failing statement of an error target always stays last.
+    fn drop_statements(&mut self, mut steps: Vec<Step>) -> Vec<Step> {
+        let last = match self.target {
+            Target::Error(_) => steps.pop(),
+            Target::Check(_) => None,
+        };
+        let with_last = |prefix: &[Step]| {
+            let mut steps = prefix.to_vec();
+            steps.extend(last.clone());
+            steps
+        };
+        let mut steps = ddmin(steps, &mut |prefix| self.reproduces(&with_last(prefix)));
+        steps.extend(last.clone());
+        steps
+    }
+
+    /// Simplifies each statement in turn, taking the first candidate that still reproduces
+    /// until none does.
+    fn shrink_statements(&mut self, mut steps: Vec<Step>) -> Vec<Step> {
+        for i in 0..steps.len() {
+            'shrink: while let Some(stmt) = steps[i].stmt.clone() {
+                for candidate in shrink::candidates(&stmt) {
+                    if self.replays >= MAX_REPLAYS {
+                        return steps;
+                    }
+                    let previous = std::mem::replace(
+                        &mut steps[i],
+                        Step {
+                            sql: candidate.clone().to_sql(Dialect::Sqlite),
+                            stmt: Some(candidate),
+                        },
+                    );
+                    if self.reproduces(&steps) {
+                        continue 'shrink;
+                    }
+                    steps[i] = previous;
+                }
+                break;
+            }
+        }
+        steps
+    }
+}
+
+/// Delta debugging: the smallest subsequence of `items` found for which `test` still holds,
+/// trying an empty sequence first, then removing ever smaller chunks.
+fn ddmin<T: Clone>(items: Vec<T>, test: &mut dyn FnMut(&[T]) -> bool) -> Vec<T> {
+    if test(&[]) {
+        return vec![];
+    }
+    let mut items = items;
+    let mut chunks = 2;
+    while items.len() >= 2 {
+        let size = items.len().div_ceil(chunks);
+        let mut reduced = false;
+        for start in (0..items.len()).step_by(size) {
+            let end = (start + size).min(items.len());
+            let complement: Vec<T> = items[..start].iter().chain(&items[end..]).cloned().collect();
+            if test(&complement) {
+                items = complement;
+                chunks = (chunks - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+        if !reduced {
+            if chunks >= items.len() {
+                break;
+            }
+            chunks = (chunks * 2).min(items.len());
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ddmin_keeps_only_the_needed_items() {
+        let items: Vec<u32> = (0..40).collect();
+        let mut tests = 0;
+        let reduced = ddmin(items, &mut |subset| {
+            tests += 1;
+            subset.contains(&7) && subset.contains(&31)
+        });
+        assert_eq!(reduced, vec![7, 31]);
+        assert!(tests < 100, "took {} tests", tests);
+    }
+}
@@ -0,0 +1,336 @@
+// Simplifications of a single statement: clauses and list entries dropped, expressions
+// replaced by NULL or by one of their operands. Candidates are listed biggest cut first.
+use crate::generators::ast::{
+    ColumnDef, CreateIndexStmt, CreateTableStmt, CreateTriggerStmt, CreateViewStmt, DeleteStmt, Expr, FunctionCall,
+    InsertSource, InsertStmt, JoinConstraint, Literal, SelectCore, SelectStmt, Stmt, TableOrSubquery, UpdateStmt,
+};
+
+pub fn candidates(stmt: &Stmt) -> Vec<Stmt> {
+    match stmt {
+        Stmt::Select(query) => select(query).into_iter().map(Stmt::Select).collect(),
+        Stmt::Insert(insert_stmt) => insert(insert_stmt).into_iter().map(Stmt::Insert).collect(),
+        Stmt::Update(update_stmt) => update(update_stmt).into_iter().map(Stmt::Update).collect(),
+        Stmt::Delete(delete_stmt) => delete(delete_stmt).into_iter().map(Stmt::Delete).collect(),
+        Stmt::CreateTable(create) => create_table(create).into_iter().map(Stmt::CreateTable).collect(),
+        Stmt::CreateIndex(create) => create_index(create).into_iter().map(Stmt::CreateIndex).collect(),
+        Stmt::CreateView(create) => create_view(create).into_iter().map(Stmt::CreateView).collect(),
+        Stmt::CreateTrigger(create) => create_trigger(create).into_iter().map(Stmt::CreateTrigger).collect(),
+        Stmt::DropTrigger(_) | Stmt::AlterTable(_) | Stmt::Drop(_) => vec![],
+    }
+}
+
+/// A copy of `value` changed by `f`.
+fn with<T: Clone>(value: &T, f: impl FnOnce(&mut T)) -> T {
+    let mut value = value.clone();
+    f(&mut value);
+    value
+}
+
+/// `items` with one entry removed, for each entry; nothing if that would leave it empty.
+fn without_each<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.len() < 2 {
+        return vec![];
+    }
+    (0..items.len())
+        .map(|i| with(&items.to_vec(), |items| drop(items.remove(i))))
+        .collect()
+}
+
+/// `items` with one entry simplified, for each simplification of each entry.
+fn shrink_each<T: Clone>(items: &[T], shrink: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut out = vec![];
+    for (i, item) in items.iter().enumerate() {
+        for candidate in shrink(item) {
+            out.push(with(&items.to_vec(), |items| items[i] = candidate));
+        }
+    }
+    out
+}
+
+/// An optional clause removed, or simplified.
+fn optional_expr(expr: &Option<Expr>) -> Vec<Option<Expr>> {
+    match expr {
+        Some(e) => std::iter::once(None).chain(shrink_expr(e).into_iter().map(Some)).collect(),
+        None => vec![],
+    }
+}
+
+fn select(query: &SelectStmt) -> Vec<SelectStmt> {
+    let mut out = vec![];
+    if query.with.is_some() {
+        out.push(with(query, |q| q.with = None));
+    }
+    for i in 0..query.compounds.len() {
+        out.push(with(query, |q| drop(q.compounds.remove(i))));
+    }
+    if !query.order_by.is_empty() {
+        out.push(with(query, |q| q.order_by.clear()));
+    }
+    if query.limit.is_some() {
+        out.push(with(query, |q| q.limit = None));
+    }
+    if let Some(with_clause) = &query.with {
+        for (i, cte) in with_clause.ctes.iter().enumerate() {
+            for candidate in select(&cte.query) {
+                out.push(with(query, |q| *q.with.as_mut().unwrap().ctes[i].query = candidate));
+            }
+        }
+    }
+    out.extend(core(&query.core).into_iter().map(|c| with(query, |q| q.core = c)));
+    for (i, (_, compound)) in query.compounds.iter().enumerate() {
+        out.extend(core(compound).into_iter().map(|c| with(query, |q| q.compounds[i].1 = c)));
+    }
+    out
+}
+
+fn core(core: &SelectCore) -> Vec<SelectCore> {
+    let mut out = vec![];
+    if core.distinct {
+        out.push(with(core, |c| c.distinct = false));
+    }
+    if !core.group_by.is_empty() {
+        out.push(with(core, |c| {
+            c.group_by.clear();
+            c.having = None;
+        }));
+    }
+    for columns in without_each(&core.columns) {
+        out.push(with(core, |c| c.columns = columns));
+    }
+    if let Some(from) = &core.from {
+        for i in 0..from.joins.len() {
+            out.push(with(core, |c| drop(c.from.as_mut().unwrap().joins.remove(i))));
+        }
+        for table in table_or_subquery(&from.table) {
+            out.push(with(core, |c| c.from.as_mut().unwrap().table = table));
+        }
+        for (i, join) in from.joins.iter().enumerate() {
+            for table in table_or_subquery(&join.table) {
+                out.push(with(core, |c| c.from.as_mut().unwrap().joins[i].table = table));
+            }
+        }
+        for (i, join) in from.joins.iter().enumerate() {
+            if let Some(JoinConstraint::On(on)) = &join.constraint {
+                for candidate in shrink_expr(on) {
+                    out.push(with(core, |c| {
+                        c.from.as_mut().unwrap().joins[i].constraint = Some(JoinConstraint::On(candidate))
+                    }));
+                }
+            }
+        }
+    }
+    for where_clause in optional_expr(&core.where_clause) {
+        out.push(with(core, |c| c.where_clause = where_clause));
+    }
+    for having in optional_expr(&core.having) {
+        out.push(with(core, |c| c.having = having));
+    }
+    for (i, item) in core.columns.iter().enumerate() {
+        for candidate in shrink_expr(&item.expr) {
+            out.push(with(core, |c| c.columns[i].expr = candidate));
+        }
+    }
+    for group_by in shrink_each(&core.group_by, shrink_expr) {
+        out.push(with(core, |c| c.group_by = group_by));
+    }
+    out
+}
+
+fn table_or_subquery(table: &TableOrSubquery) -> Vec<TableOrSubquery> {
+    match table {
+        TableOrSubquery::Table(_) => vec![],
+        TableOrSubquery::Subquery { query, alias } => select(query)
+            .into_iter()
+            .map(|query| TableOrSubquery::Subquery {
+                query: Box::new(query),
+                alias: alias.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn insert(insert_stmt: &InsertStmt) -> Vec<InsertStmt> {
+    match &insert_stmt.source {
+        InsertSource::DefaultValues => vec![],
+        InsertSource::Values(rows) => {
+            let mut sources: Vec<Vec<Vec<Expr>>> = without_each(rows);
+            sources.extend(shrink_each(rows, |row| shrink_each(row, shrink_expr)));
+            sources
+                .into_iter()
+                .map(|rows| with(insert_stmt, |s| s.source = InsertSource::Values(rows)))
+                .collect()
+        }
+        InsertSource::Select(query) => select(query)
+            .into_iter()
+            .map(|query| with(insert_stmt, |s| s.source = InsertSource::Select(Box::new(query))))
+            .collect(),
+    }
+}
+
+fn update(update_stmt: &UpdateStmt) -> Vec<UpdateStmt> {
+    let mut out = vec![];
+    if update_stmt.from.is_some() {
+        out.push(with(update_stmt, |s| s.from = None));
+    }
+    if update_stmt.limit.is_some() {
+        out.push(with(update_stmt, |s| s.limit = None));
+    }
+    for set in without_each(&update_stmt.set) {
+        out.push(with(update_stmt, |s| s.set = set));
+    }
+    for where_clause in optional_expr(&update_stmt.where_clause) {
+        out.push(with(update_stmt, |s| s.where_clause = where_clause));
+    }
+    let shrink_item = |(column, value): &(String, Expr)| {
+        shrink_expr(value).into_iter().map(|value| (column.clone(), value)).collect()
+    };
+    for set in shrink_each(&update_stmt.set, shrink_item) {
+        out.push(with(update_stmt, |s| s.set = set));
+    }
+    out
+}
+
+fn delete(delete_stmt: &DeleteStmt) -> Vec<DeleteStmt> {
+    let mut out = vec![];
+    if !delete_stmt.returning.is_empty() {
+        out.push(with(delete_stmt, |s| s.returning.clear()));
+    }
+    if delete_stmt.limit.is_some() {
+        out.push(with(delete_stmt, |s| s.limit = None));
+    }
+    for where_clause in optional_expr(&delete_stmt.where_clause) {
+        out.push(with(delete_stmt, |s| s.where_clause = where_clause));
+    }
+    out
+}
+
+fn create_table(create: &CreateTableStmt) -> Vec<CreateTableStmt> {
+    let mut out = vec![];
+    for columns in without_each(&create.columns) {
+        out.push(with(create, |s| s.columns = columns));
+    }
+    for i in 0..create.constraints.len() {
+        out.push(with(create, |s| drop(s.constraints.remove(i))));
+    }
+    let drop_constraints = |column: &ColumnDef| {
+        (0..column.constraints.len())
+            .map(|i| with(column, |c| drop(c.constraints.remove(i))))
+            .collect()
+    };
+    for columns in shrink_each(&create.columns, drop_constraints) {
+        out.push(with(create, |s| s.columns = columns));
+    }
+    if create.without_rowid {
+        out.push(with(create, |s| s.without_rowid = false));
+    }
+    if create.strict {
+        out.push(with(create, |s| s.strict = false));
+    }
+    out
+}
+
+fn create_index(create: &CreateIndexStmt) -> Vec<CreateIndexStmt> {
+    let mut out = vec![];
+    if create.unique {
+        out.push(with(create, |s| s.unique = false));
+    }
+    for columns in without_each(&create.columns) {
+        out.push(with(create, |s| s.columns = columns));
+    }
+    for where_clause in optional_expr(&create.where_clause) {
+        out.push(with(create, |s| s.where_clause = where_clause));
+    }
+    out
+}
+
+fn create_view(create: &CreateViewStmt) -> Vec<CreateViewStmt> {
+    select(&create.query)
+        .into_iter()
+        .map(|query| with(create, |s| *s.query = query))
+        .collect()
+}
+
+fn create_trigger(create: &CreateTriggerStmt) -> Vec<CreateTriggerStmt> {
+    let mut out = vec![];
+    for body in without_each(&create.body) {
+        out.push(with(create, |s| s.body = body));
+    }
+    for when in optional_expr(&create.when) {
+        out.push(with(create, |s| s.when = when));
+    }
+    for body in shrink_each(&create.body, candidates) {
+        out.push(with(create, |s| s.body = body));
+    }
+    out
+}
+
+/// NULL, each operand in place of the whole expression, then each operand simplified.
+fn shrink_expr(expr: &Expr) -> Vec<Expr> {
+    let mut out = vec![];
+    if !matches!(expr, Expr::Literal(_)) {
+        out.push(Expr::Literal(Literal::Null));
+    }
+    let mut scratch = expr.clone();
+    let operands: Vec<Expr> = operands_mut(&mut scratch).into_iter().map(|e| e.clone()).collect();
+    out.extend(operands.iter().cloned());
+    for (i, operand) in operands.iter().enumerate() {
+        for candidate in shrink_expr(operand) {
+            out.push(with(expr, |e| *operands_mut(e).swap_remove(i) = candidate));
+        }
+    }
+    match expr {
+        Expr::Subquery(query) => {
+            out.extend(select(query).into_iter().map(|q| Expr::Subquery(Box::new(q))));
+        }
+        Expr::Exists { negated, subquery } => {
+            out.extend(select(subquery).into_iter().map(|q| Expr::Exists {
+                negated: *negated,
+                subquery: Box::new(q),
+            }));
+        }
+        Expr::InSubquery { subquery, .. } => {
+            for candidate in select(subquery) {
+                out.push(with(expr, |e| {
+                    if let Expr::InSubquery { subquery, .. } = e {
+                        **subquery = candidate
+                    }
+                }));
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+/// The scalar operands of `expr`, in order; subqueries are not included.
+fn operands_mut(expr: &mut Expr) -> Vec<&mut Expr> {
+    match expr {
+        Expr::Literal(_) | Expr::Column(_) | Expr::Subquery(_) | Expr::Exists { .. } | Expr::Raise { .. } => vec![],
+        Expr::Unary { expr, .. }
+        | Expr::Cast { expr, .. }
+        | Expr::IsNull { expr, .. }
+        | Expr::Collate { expr, .. }
+        | Expr::InSubquery { expr, .. } => vec![&mut **expr],
+        Expr::Binary { left, right, .. } => vec![&mut **left, &mut **right],
+        Expr::Case {
+            operand,
+            when_then,
+            else_expr,
+        } => {
+            let mut out: Vec<&mut Expr> = operand.iter_mut().map(|e| &mut **e).collect();
+            for (when, then) in when_then {
+                out.push(when);
+                out.push(then);
+            }
+            out.extend(else_expr.iter_mut().map(|e| &mut **e));
+            out
+        }
+        Expr::Between { expr, low, high, .. } => vec![&mut **expr, &mut **low, &mut **high],
+        Expr::InList { expr, list, .. } => std::iter::once(&mut **expr).chain(list.iter_mut()).collect(),
+        Expr::Like { expr, pattern, .. } => vec![&mut **expr, &mut **pattern],
+        Expr::Function(call) => {
+            let FunctionCall { args, filter, .. } = &mut **call;
+            args.iter_mut().chain(filter.iter_mut().map(|e| &mut **e)).collect()
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Replay logs: every statement a thread executed and how it ended, so a run can be executed
+// again on another machine and checked to behave the same.
+use crate::reducer::{self, Step, Target};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlsmith_rs_common::profile::Profile;
+use sqlsmith_rs_drivers::{DRIVER_KIND, new_conn};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::Path;
+
+const REPLAY_DIR: &str = "replays";
+
+/// First line of a replay log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayHeader {
+    pub seed: u64,
+    pub driver: DRIVER_KIND,
+    /// [`profile_hash`] of the profile the run was generated from
+    pub profile_hash: String,
+    pub timestamp: String,
+}
+
+/// How a statement ended.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "outcome", rename_all = "lowercase")]
+pub enum Outcome {
+    Ok { affected: usize },
+    Error { error: String },
+    /// The driver panicked; only Limbo does this
+    Panic,
+    /// Not a statement: the thread started over on a fresh database here
+    Restart,
+}
+
+impl Outcome {
+    pub fn of(result: &Result<usize>) -> Self {
+        match result {
+            Ok(affected) => Outcome::Ok { affected: *affected },
+            Err(e) => Outcome::Error {
+                error: reducer::error_signature(e),
+            },
+        }
+    }
+}
+
+/// Every line after the header.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayEntry {
+    pub sql: String,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/// Identifies a profile, so a replay can warn when it runs under different settings.
+pub fn profile_hash(profile: &Profile) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(profile).unwrap_or_default().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Appends a thread's statements to `replays/<seed>-<pid>.jsonl`, one line per statement so
+/// a log stays readable when the executor dies mid-run.
+pub struct ReplayWriter {
+    file: LineWriter<File>,
+}
+
+impl ReplayWriter {
+    pub fn create(header: &ReplayHeader) -> Result<Self> {
+        fs::create_dir_all(REPLAY_DIR)?;
+        let path = Path::new(REPLAY_DIR).join(format!("{}-{}.jsonl", header.seed, std::process::id()));
+        let mut file = LineWriter::new(File::create(&path)?);
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        log::info!("Recording replay log to {}", path.display());
+        Ok(Self { file })
+    }
+
+    pub fn record(&mut self, sql: &str, outcome: Outcome) {
+        let entry = ReplayEntry {
+            sql: sql.to_string(),
+            outcome,
+        };
+        let written = serde_json::to_string(&entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| Ok(writeln!(self.file, "{}", line)?));
+        if let Err(e) = written {
+            log::warn!("Failed to write replay log: {}", e);
+        }
+    }
+
+    /// Marks that the thread continues on a fresh database, which a replay does as well.
+    pub fn restart(&mut self) {
+        self.record("", Outcome::Restart);
+    }
+}
+
+/// Opens a replay log for a thread if recording is on, logging instead of failing.
+pub fn start(profile_hash: Option<&str>, seed: u64, driver: DRIVER_KIND) -> Option<ReplayWriter> {
+    let header = ReplayHeader {
+        seed,
+        driver,
+        profile_hash: profile_hash?.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    ReplayWriter::create(&header)
+        .map_err(|e| log::warn!("Failed to create replay log for seed {}: {}", seed, e))
+        .ok()
+}
+
+/// The header and entries of a replay log; a last line cut short by a crash is skipped.
+fn read_log(path: &str) -> Result<(ReplayHeader, Vec<ReplayEntry>)> {
+    let mut lines = BufReader::new(File::open(path).with_context(|| format!("cannot open {}", path))?).lines();
+    let header: ReplayHeader = serde_json::from_str(&lines.next().context("replay log is empty")??)
+        .context("invalid replay log header")?;
+    let lines: Vec<String> = lines.collect::<std::io::Result<_>>()?;
+    let mut entries = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) if i + 1 == lines.len() => log::warn!("Skipping truncated last line of {}", path),
+            Err(e) => return Err(e).with_context(|| format!("invalid entry: {}", line)),
+        }
+    }
+    Ok((header, entries))
+}
+
+/// Executes the statements of a replay log on a fresh driver and compares every outcome.
+/// Returns how many statements ended differently.
+pub fn replay(path: &str, profile: &Profile) -> Result<usize> {
+    let (header, entries) = read_log(path)?;
+    log::info!(
+        "Replaying {} (seed {}, {:?}, recorded {})",
+        path,
+        header.seed,
+        header.driver,
+        header.timestamp
+    );
+    if header.profile_hash != profile_hash(profile) {
+        log::warn!("profile.json differs from the one the log was recorded with; outcomes may not match");
+    }
+
+    let mut driver = new_conn(header.driver)?;
+    let (mut total, mut diverged) = (0, 0);
+    for entry in entries {
+        if entry.outcome == Outcome::Restart {
+            driver = new_conn(header.driver)?;
+            continue;
+        }
+        total += 1;
+        let outcome = match catch_unwind(AssertUnwindSafe(|| driver.exec(&entry.sql))) {
+            Ok(result) => Outcome::of(&result),
+            Err(_) => {
+                // The connection is unusable after a panic, as in the engine
+                driver = new_conn(header.driver)?;
+                Outcome::Panic
+            }
+        };
+        if outcome != entry.outcome {
+            diverged += 1;
+            log::warn!(
+                "Statement {} diverged: recorded {:?}, replayed {:?}: {}",
+                total,
+                entry.outcome,
+                outcome,
+                entry.sql
+            );
+        }
+    }
+    log::info!("Replayed {} statements, {} diverged", total, diverged);
+    Ok(diverged)
+}
+
+/// Reduces a SQLite replay log to the statements needed for its last failing statement to
+/// fail the same way. Logs carry no syntax trees, so statements are only dropped, not shrunk.
+pub fn reduce_log(path: &str) -> Result<Vec<String>> {
+    let (header, mut entries) = read_log(path)?;
+    if header.driver != DRIVER_KIND::SQLITE_IN_MEM {
+        anyhow::bail!("only SQLite replay logs can be reduced, {} is {:?}", path, header.driver);
+    }
+    let (last_error, error) = entries
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, entry)| match &entry.outcome {
+            Outcome::Error { error } => Some((i, error.clone())),
+            _ => None,
+        })
+        .with_context(|| format!("no statement failed in {}", path))?;
+    entries.truncate(last_error + 1);
+    // Statements before the last restart ran on a database the error never saw
+    if let Some(restart) = entries.iter().rposition(|entry| entry.outcome == Outcome::Restart) {
+        entries.drain(..=restart);
+    }
+    log::info!("Reducing {} statements ending in: {}", entries.len(), error);
+    let steps: Vec<Step> = entries.iter().map(|entry| Step::text(&entry.sql)).collect();
+    Ok(reducer::reduce(&steps, &Target::Error(error))
+        .into_iter()
+        .map(|step| step.sql)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_round_trip() {
+        let entries = [
+            ReplayEntry {
+                sql: "INSERT INTO t VALUES (1);".to_string(),
+                outcome: Outcome::Ok { affected: 1 },
+            },
+            ReplayEntry {
+                sql: "SELECT x FROM t;".to_string(),
+                outcome: Outcome::Error {
+                    error: "Unknown: no such column: x".to_string(),
+                },
+            },
+            ReplayEntry {
+                sql: "VACUUM;".to_string(),
+                outcome: Outcome::Panic,
+            },
+            ReplayEntry {
+                sql: String::new(),
+                outcome: Outcome::Restart,
+            },
+        ];
+        for entry in entries {
+            let line = serde_json::to_string(&entry).unwrap();
+            let parsed: ReplayEntry = serde_json::from_str(&line).unwrap();
+            assert_eq!(parsed.sql, entry.sql);
+            assert_eq!(parsed.outcome, entry.outcome, "{}", line);
+        }
+    }
+}
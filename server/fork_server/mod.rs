@@ -1,8 +1,99 @@
+use crate::runs::Tracker;
+use sqlsmith_rs_common::findings::{FINDINGS_DB, Finding, FindingStore, message_template};
 use sqlsmith_rs_common::profile::Profile;
+use std::collections::VecDeque;
 use std::env;
 use std::path::Path;
-use std::process::exit;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
+use tokio::sync::watch;
+
+/// Output lines kept per executor to show what it was doing when it died.
+const TAIL_LINES: usize = 50;
+/// Restarts per executor slot, so a seed range that always crashes does not loop forever.
+const MAX_RESTARTS: usize = 16;
+/// Directory the profile of each run is written to, for its executors to read with `--profile`.
+const RUN_PROFILE_DIR: &str = "run_profiles";
+
+/// How an executor process ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutorExit {
+    Finished,
+    /// Non-zero exit code, e.g. 101 when a thread panicked
+    Failed(i32),
+    /// Killed by a signal, e.g. SIGSEGV or SIGABRT from the bundled database
+    Signaled(i32),
+    /// Ran past `executor_timeout` and was killed
+    TimedOut(Duration),
+    /// Killed because its run was stopped
+    Stopped,
+}
+
+impl ExecutorExit {
+    fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return ExecutorExit::Signaled(signal);
+            }
+        }
+        match status.code() {
+            Some(0) => ExecutorExit::Finished,
+            Some(code) => ExecutorExit::Failed(code),
+            None => ExecutorExit::Failed(-1),
+        }
+    }
+
+    /// The finding kind it is recorded as, None for a clean exit.
+    fn finding_kind(&self) -> Option<&'static str> {
+        match self {
+            ExecutorExit::Finished | ExecutorExit::Stopped => None,
+            ExecutorExit::Failed(_) | ExecutorExit::Signaled(_) => Some("crash"),
+            ExecutorExit::TimedOut(_) => Some("hang"),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ExecutorExit::Finished => "finished".to_string(),
+            ExecutorExit::Failed(code) => format!("exited with code {}", code),
+            ExecutorExit::Signaled(signal) => format!("killed by signal {} ({})", signal, signal_name(*signal)),
+            ExecutorExit::TimedOut(timeout) => format!("hung for more than {}s", timeout.as_secs()),
+            ExecutorExit::Stopped => "stopped".to_string(),
+        }
+    }
+}
+
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGILL => "SIGILL",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGTERM => "SIGTERM",
+        _ => "unknown",
+    }
+}
+
+/// One executor process that ran to the end, crashed or hung.
+#[derive(Debug, Clone)]
+pub struct ExecutorReport {
+    pub seed: u64,
+    pub exit: ExecutorExit,
+    /// Last lines the executor printed
+    pub tail: Vec<String>,
+}
+
+/// Seed of the `n`th executor of a run; executors start their threads at seed + thread index.
+pub fn executor_seed(base_seed: u64, n: usize) -> u64 {
+    (base_seed << 8) + n as u64
+}
 
 fn get_executor_path() -> Option<String> {
     env::current_exe().ok().and_then(|mut path| {
@@ -17,57 +108,57 @@ fn can_execute(path: &str) -> bool {
 }
 
 /// fork_server 的主函数，用于生成多个进程
-pub async fn fork_server_main(profile: &Profile) {
-    let executor_count = profile.executor_count.unwrap();
+pub async fn fork_server_main(profile: &Profile, tracker: &Tracker) -> Result<(), String> {
+    let executor_count = profile.executor_count.ok_or("profile has no executor_count")?;
     let base_seed = profile.seed.unwrap_or(0);
+    let timeout = profile.executor_timeout.map(Duration::from_secs);
+    let restart = profile.restart_on_crash.unwrap_or(false);
     println!("Using executor count: {}", executor_count);
 
     let executor_path = match get_executor_path() {
         Some(path) if can_execute(&path) => path,
-        _ => {
-            eprintln!("Cannot find or execute the executor binary");
-            exit(1);
-        }
+        _ => return Err("Cannot find or execute the executor binary".to_string()),
+    };
+    let command = ExecutorCommand {
+        path: executor_path,
+        profile_path: write_run_profile(profile, tracker.run_id)?,
     };
 
     let mut handles = Vec::new();
     for n in 0..executor_count {
-        let path = executor_path.clone();
-        let process_name = format!("exec_{}", n);
-        let seed = (base_seed << 8) + n as u64;
+        let command = command.clone();
+        let mut seed = executor_seed(base_seed, n);
+        let tracker = tracker.clone();
         let handle = tokio::spawn(async move {
-            let mut cmd = Command::new(&path);
-            cmd.env("EXEC_PARAM_SEED", seed.to_string());
-            
-            #[cfg(unix)]
-            {
-                use std::os::unix::process::CommandExt;
-                cmd.arg0(&process_name);
-                
-                // Set up prctl to kill child when parent dies
-                unsafe {
-                    cmd.pre_exec(|| {
-                        // PR_SET_PDEATHSIG = 1
-                        // SIGTERM = 15
-                        let ret = libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
-                        if ret != 0 {
-                            eprintln!("Failed to set PR_SET_PDEATHSIG");
-                        }
-                        Ok(())
-                    });
+            let mut stop = tracker.stop_signal();
+            for restarts in 0.. {
+                if *stop.borrow() {
+                    break;
                 }
-            }
-            
-            match cmd.spawn() {
-                Ok(mut child) => {
-                    if let Err(e) = child.wait().await {
-                        eprintln!("Executor failed: {}", e);
+                let report = match run_executor(&command, n, seed, timeout, &mut stop, &tracker, restarts).await {
+                    Ok(report) => report,
+                    Err(e) => {
+                        log::error!("Failed to execute {}: {}", command.path, e);
+                        tracker.executor_failed(n);
+                        break;
                     }
+                };
+                tracker.executor_exited(n, &report.exit);
+                crate::stats::executor_exited(tracker.run_id, &seed.to_string());
+                let Some(kind) = report.exit.finding_kind() else {
+                    break;
+                };
+                log::warn!("Executor exec_{} with seed {} {}", n, seed, report.exit.describe());
+                for line in &report.tail {
+                    log::warn!("  | {}", line);
                 }
-                Err(e) => {
-                    eprintln!("Failed to execute {}: {}", path, e);
-                    exit(1);
+                record_crash(kind, &report, tracker.run_id).await;
+                if !restart || restarts == MAX_RESTARTS {
+                    break;
                 }
+                // Step by the executor count so restarted slots never reuse each other's seeds
+                seed += executor_count as u64;
+                log::info!("Restarting exec_{} with seed {}", n, seed);
             }
         });
         handles.push(handle);
@@ -75,4 +166,157 @@ pub async fn fork_server_main(profile: &Profile) {
     for handle in handles {
         let _ = handle.await;
     }
+    Ok(())
+}
+
+/// Writes the run's profile where its executors read it, so they run what was posted rather
+/// than whatever profile.json holds.
+fn write_run_profile(profile: &Profile, run_id: u64) -> Result<String, String> {
+    let path = Path::new(RUN_PROFILE_DIR).join(format!("{}.json", run_id));
+    let json = serde_json::to_string_pretty(profile).map_err(|e| format!("Cannot serialize the profile: {}", e))?;
+    std::fs::create_dir_all(RUN_PROFILE_DIR)
+        .and_then(|_| std::fs::write(&path, json))
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// The executor binary, and the profile every executor of a run reads.
+#[derive(Clone)]
+struct ExecutorCommand {
+    path: String,
+    profile_path: String,
+}
+
+/// Runs one executor to completion, killing it once it runs past `timeout` or the run is stopped.
+async fn run_executor(
+    command: &ExecutorCommand,
+    n: usize,
+    seed: u64,
+    timeout: Option<Duration>,
+    stop: &mut watch::Receiver<bool>,
+    tracker: &Tracker,
+    restarts: usize,
+) -> std::io::Result<ExecutorReport> {
+    let mut cmd = Command::new(&command.path);
+    cmd.arg("--profile")
+        .arg(&command.profile_path)
+        .env("EXEC_PARAM_SEED", seed.to_string())
+        .env("EXEC_PARAM_RUN_ID", tracker.run_id.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    #[cfg(unix)]
+    {
+        cmd.arg0(format!("exec_{}", n));
+
+        // Set up prctl to kill child when parent dies
+        unsafe {
+            cmd.pre_exec(|| {
+                // PR_SET_PDEATHSIG = 1
+                // SIGTERM = 15
+                let ret = libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+                if ret != 0 {
+                    log::warn!("Failed to set PR_SET_PDEATHSIG");
+                }
+                Ok(())
+            });
+        }
+    }
+
+    let mut child = cmd.spawn()?;
+    tracker.executor_started(n, child.id(), seed, restarts);
+
+    // Output is passed through as before, keeping the last lines for a crash report
+    let tail = Arc::new(Mutex::new(VecDeque::with_capacity(TAIL_LINES)));
+    let readers = [
+        child.stdout.take().map(|out| tokio::spawn(keep_tail(out, Arc::clone(&tail), false))),
+        child.stderr.take().map(|err| tokio::spawn(keep_tail(err, Arc::clone(&tail), true))),
+    ];
+
+    let status = tokio::select! {
+        status = child.wait() => status.map(ExecutorExit::from_status),
+        _ = deadline(timeout) => Ok(ExecutorExit::TimedOut(timeout.unwrap_or_default())),
+        // A dropped sender means the run is gone, which stops it as well
+        _ = stop.wait_for(|stop| *stop) => Ok(ExecutorExit::Stopped),
+    };
+    if matches!(status, Ok(ExecutorExit::TimedOut(_) | ExecutorExit::Stopped))
+        && let Err(e) = child.kill().await
+    {
+        log::warn!("Failed to kill executor exec_{}: {}", n, e);
+    }
+    let exit = status.unwrap_or_else(|e| {
+        log::error!("Executor exec_{} failed: {}", n, e);
+        ExecutorExit::Failed(-1)
+    });
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.await;
+    }
+
+    let tail = tail.lock().map(|tail| tail.iter().cloned().collect()).unwrap_or_default();
+    Ok(ExecutorReport { seed, exit, tail })
+}
+
+async fn deadline(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn keep_tail(output: impl AsyncRead + Unpin, tail: Arc<Mutex<VecDeque<String>>>, stderr: bool) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+        if let Ok(mut tail) = tail.lock() {
+            if tail.len() == TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+}
+
+/// Where the executor went wrong, for the crash's signature: the location of the first panic
+/// in its output, since a panicking thread brings down the main thread joining it, or else the
+/// last line printed outside the logger, e.g. a failed assertion in the bundled database, with
+/// the varying parts left out.
+fn crash_cause(tail: &[String]) -> String {
+    match tail.iter().find_map(|line| line.split_once(" panicked at ")) {
+        Some((_, location)) => location.trim_end_matches(':').to_string(),
+        None => tail
+            .iter()
+            .rev()
+            // Log lines start with a bracketed timestamp
+            .find(|line| !line.trim().is_empty() && !line.starts_with('['))
+            .map(|line| message_template(line))
+            .unwrap_or_default(),
+    }
+}
+
+/// Stores the crash as a finding, so it is deduplicated with the rest and its seed kept.
+async fn record_crash(kind: &'static str, report: &ExecutorReport, run_id: u64) {
+    // The signature is taken from the first line, leaving the rest of the output out of it
+    let detail = format!(
+        "{}: {}\n{}",
+        report.exit.describe(),
+        crash_cause(&report.tail),
+        report.tail.join("\n")
+    );
+    let finding = Finding::new(kind, report.seed, "", detail, vec![]);
+    let stored = tokio::task::spawn_blocking(move || {
+        let new = FindingStore::open(Path::new(FINDINGS_DB)).and_then(|store| store.record(&finding));
+        new.map(|new| new.then_some(finding))
+    })
+    .await;
+    match stored {
+        Ok(Ok(Some(finding))) => crate::live::publish_finding(run_id, report.seed.to_string(), finding),
+        Ok(Ok(None)) => {}
+        Ok(Err(e)) => log::warn!("Failed to record {}: {}", kind, e),
+        Err(e) => log::warn!("Failed to record {}: {}", kind, e),
+    }
 }
@@ -0,0 +1,201 @@
+// Live view of runs: executors' progress and new findings are fanned out to the UI as
+// Server-Sent Events, and throughput and error rate are kept as a time series per run.
+use actix_web::web::Bytes;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlsmith_rs_common::findings::Finding;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+/// Samples kept per run, an hour at the most frequent.
+const MAX_SAMPLES: usize = 3600;
+/// Executors submit independently; closer samples would measure rates over a few milliseconds.
+const MIN_SAMPLE_INTERVAL: chrono::TimeDelta = chrono::TimeDelta::seconds(1);
+/// Events a slow subscriber may fall behind before it skips ahead.
+const EVENT_BUFFER: usize = 256;
+
+/// Throughput and error rate since the previous sample of the same run.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    pub total_queries: usize,
+    pub failed_new_queries: usize,
+    pub queries_per_second: f64,
+    pub error_rate: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Event {
+    /// A run's statistics changed
+    Stats {
+        run_id: u64,
+        sample: Sample,
+        summary: serde_json::Value,
+    },
+    /// An executor stored a finding no one had stored before
+    Finding {
+        run_id: u64,
+        executor_id: String,
+        signature: String,
+        finding: Finding,
+    },
+}
+
+impl Event {
+    fn run_id(&self) -> u64 {
+        match self {
+            Event::Stats { run_id, .. } | Event::Finding { run_id, .. } => *run_id,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Event::Stats { .. } => "stats",
+            Event::Finding { .. } => "finding",
+        }
+    }
+}
+
+static SERIES: Mutex<BTreeMap<u64, VecDeque<Sample>>> = Mutex::new(BTreeMap::new());
+
+fn events() -> &'static broadcast::Sender<Event> {
+    static EVENTS: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+    EVENTS.get_or_init(|| broadcast::channel(EVENT_BUFFER).0)
+}
+
+/// Appends a sample for the run's new totals unless the last one is recent, and sends the
+/// summary with the latest sample to subscribers.
+pub fn publish_stats(run_id: u64, total_queries: usize, failed_new_queries: usize, summary: serde_json::Value) {
+    let now = Utc::now();
+    let sample = {
+        let mut series = SERIES.lock().unwrap();
+        let samples = series.entry(run_id).or_default();
+        match samples.back() {
+            Some(last) if now - last.timestamp < MIN_SAMPLE_INTERVAL => last.clone(),
+            last => {
+                let sample = next_sample(last, now, total_queries, failed_new_queries);
+                if samples.len() == MAX_SAMPLES {
+                    samples.pop_front();
+                }
+                samples.push_back(sample.clone());
+                sample
+            }
+        }
+    };
+    // Sending only fails when nobody is subscribed
+    let _ = events().send(Event::Stats {
+        run_id,
+        sample,
+        summary,
+    });
+}
+
+fn next_sample(previous: Option<&Sample>, now: DateTime<Utc>, total_queries: usize, failed_new_queries: usize) -> Sample {
+    let (queries, failed, seconds) = match previous {
+        Some(previous) => (
+            total_queries.saturating_sub(previous.total_queries),
+            failed_new_queries.saturating_sub(previous.failed_new_queries),
+            (now - previous.timestamp).num_milliseconds() as f64 / 1000.0,
+        ),
+        None => (0, 0, 0.0),
+    };
+    Sample {
+        timestamp: now,
+        total_queries,
+        failed_new_queries,
+        queries_per_second: if seconds > 0.0 { queries as f64 / seconds } else { 0.0 },
+        error_rate: if queries > 0 { (failed as f64 / queries as f64) * 100.0 } else { 0.0 },
+    }
+}
+
+/// Forgets a run's time series, when its statistics are reset or archived.
+pub fn clear_series(run_id: u64) {
+    SERIES.lock().unwrap().remove(&run_id);
+}
+
+#[derive(Deserialize)]
+pub struct LiveQuery {
+    run: Option<u64>,
+}
+
+// GET /live: Server-Sent Events for every run, or the one given with ?run=
+pub async fn stream(query: web::Query<LiveQuery>) -> impl Responder {
+    let run = query.run;
+    let events = futures_util::stream::unfold(events().subscribe(), move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if run.is_none_or(|run| run == event.run_id()) => {
+                    let data = serde_json::to_string(&event).unwrap_or_default();
+                    let message = Bytes::from(format!("event: {}\ndata: {}\n\n", event.name(), data));
+                    return Some((Ok::<_, actix_web::Error>(message), receiver));
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Live subscriber fell behind, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events)
+}
+
+// GET /runs/{id}/series
+pub async fn show_series(id: web::Path<u64>) -> impl Responder {
+    let series = SERIES.lock().unwrap();
+    let samples: Vec<&Sample> = series.get(&id).into_iter().flatten().collect();
+    HttpResponse::Ok().json(samples)
+}
+
+#[derive(Deserialize)]
+pub struct FindingSubmission {
+    run_id: u64,
+    executor_id: String,
+    finding: Finding,
+}
+
+// POST /internal/finding/submit: executors announce findings no one had stored before
+pub async fn handle_finding_submission(submission: web::Json<FindingSubmission>) -> impl Responder {
+    let FindingSubmission {
+        run_id,
+        executor_id,
+        finding,
+    } = submission.into_inner();
+    log::info!("New {} finding from executor {}: {}", finding.kind, executor_id, finding.detail);
+    publish_finding(run_id, executor_id, finding);
+    HttpResponse::Ok().body("Finding received")
+}
+
+/// Keeps a finding that was stored for the first time with its run and sends it to subscribers.
+pub fn publish_finding(run_id: u64, executor_id: String, finding: Finding) {
+    let (stored_by, stored) = (executor_id.clone(), finding.clone());
+    crate::results::write("save finding", move |db| db.save_finding(run_id, &stored_by, &stored));
+    let _ = events().send(Event::Finding {
+        run_id,
+        executor_id,
+        signature: finding.signature().key(),
+        finding,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_measure_the_interval_since_the_previous_one() {
+        let start = Utc::now();
+        let first = next_sample(None, start, 100, 5);
+        assert_eq!((first.queries_per_second, first.error_rate), (0.0, 0.0));
+
+        let second = next_sample(Some(&first), start + chrono::Duration::seconds(2), 500, 25);
+        assert_eq!(second.queries_per_second, 200.0);
+        assert_eq!(second.error_rate, 5.0);
+    }
+}
@@ -0,0 +1,139 @@
+// Prometheus metrics: run and executor counters in the text exposition format, so campaigns can
+// be scraped alongside everything else instead of polled as JSON.
+use crate::{runs, stats};
+use actix_web::{HttpResponse, Responder, web};
+use sqlsmith_rs_common::findings::{FINDINGS_DB, FindingStore, StoredFinding};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::path::Path;
+
+/// Reads one counter out of a run's totals or executors.
+type TotalsValue = fn(&stats::Totals) -> usize;
+type RunValue = fn(&runs::RunMetrics) -> usize;
+
+/// Metrics text in the Prometheus exposition format, one family after the other.
+#[derive(Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(label, value)| format!("{}=\"{}\"", label, escape(value)))
+            .collect();
+        let _ = writeln!(self.text, "{}{{{}}} {}", name, labels.join(","), value);
+    }
+}
+
+/// Escapes a label value: backslashes, double quotes and line feeds.
+fn escape(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', r#"\""#).replace('\n', r"\n")
+}
+
+// GET /metrics
+pub async fn metrics() -> impl Responder {
+    let findings = web::block(|| FindingStore::open(Path::new(FINDINGS_DB))?.list()).await;
+    let findings = match findings {
+        Ok(Ok(findings)) => findings,
+        Ok(Err(e)) => {
+            log::warn!("Failed to read findings for metrics: {}", e);
+            vec![]
+        }
+        Err(e) => {
+            log::warn!("Failed to read findings for metrics: {}", e);
+            vec![]
+        }
+    };
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(render(&runs::metrics(), &stats::run_totals(), &findings))
+}
+
+fn render(runs: &[runs::RunMetrics], totals: &BTreeMap<u64, stats::Totals>, findings: &[StoredFinding]) -> String {
+    let mut out = Exposition::default();
+    let drivers: BTreeMap<u64, &str> = runs.iter().map(|run| (run.id, run.driver.as_str())).collect();
+    // Executors started by hand submit under run 0, which has no profile to take the driver from
+    let labelled: Vec<(String, &str, &stats::Totals)> = totals
+        .iter()
+        .map(|(id, totals)| (id.to_string(), drivers.get(id).copied().unwrap_or("unknown"), totals))
+        .collect();
+
+    let query_counters: [(&str, &str, TotalsValue); 3] = [
+        ("sqlsmith_queries_total", "Queries executors ran.", |totals| totals.total_queries),
+        (
+            "sqlsmith_queries_failed_expected_total",
+            "Queries that failed with an error the generator can cause.",
+            |totals| totals.failed_expected_count,
+        ),
+        (
+            "sqlsmith_queries_failed_new_total",
+            "Queries that failed with an error that was not expected.",
+            |totals| totals.failed_new_count,
+        ),
+    ];
+    for (name, help, value) in query_counters {
+        out.family(name, "counter", help);
+        for (run, driver, totals) in &labelled {
+            out.sample(name, &[("run", run), ("driver", driver)], value(totals));
+        }
+    }
+
+    let name = "sqlsmith_statements_total";
+    out.family(name, "counter", "Generated statements by SqlKind.");
+    for (run, driver, totals) in &labelled {
+        let kinds: BTreeMap<&String, &usize> = totals.stmt_type_counts.iter().collect();
+        for (kind, count) in kinds {
+            out.sample(name, &[("run", run), ("driver", driver), ("kind", kind)], count);
+        }
+    }
+
+    let name = "sqlsmith_findings_total";
+    out.family(name, "counter", "Occurrences of each finding in findings.db by signature, across runs.");
+    for finding in findings {
+        out.sample(name, &[("signature", &finding.signature), ("kind", &finding.kind)], finding.count);
+    }
+
+    let executor_metrics: [(&str, &str, &str, RunValue); 4] = [
+        (
+            "sqlsmith_executor_restarts_total",
+            "counter",
+            "Executors started again after crashing or hanging.",
+            |run| run.restarts,
+        ),
+        ("sqlsmith_executor_crashes_total", "counter", "Executors that crashed.", |run| run.crashes),
+        ("sqlsmith_executor_hangs_total", "counter", "Executors killed for running past executor_timeout.", |run| run.hangs),
+        ("sqlsmith_active_executors", "gauge", "Executors running.", |run| run.active),
+    ];
+    for (name, kind, help, value) in executor_metrics {
+        out.family(name, kind, help);
+        for run in runs {
+            out.sample(name, &[("run", &run.id.to_string()), ("driver", &run.driver)], value(run));
+        }
+    }
+    out.text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let mut out = Exposition::default();
+        out.family("sqlsmith_findings_total", "counter", "Findings.");
+        out.sample("sqlsmith_findings_total", &[("signature", "error|1|no such table: \"?\"\n")], 2);
+        assert_eq!(
+            out.text,
+            "# HELP sqlsmith_findings_total Findings.\n\
+             # TYPE sqlsmith_findings_total counter\n\
+             sqlsmith_findings_total{signature=\"error|1|no such table: \\\"?\\\"\\n\"} 2\n"
+        );
+    }
+}
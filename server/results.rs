@@ -0,0 +1,248 @@
+// Results database: runs with the profiles they used, what their executors submitted and the
+// findings they made are written through to SQLite, so history survives server restarts.
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use sqlsmith_rs_common::findings::Finding;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, mpsc};
+use std::time::Duration;
+
+/// Kept next to `findings.db` in the directory the server runs in.
+pub const RESULTS_DB: &str = "results.db";
+
+/// Schema changes in the order they were made; a database's `user_version` counts those applied.
+/// Only ever append to this list.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE runs (
+        id INTEGER PRIMARY KEY,
+        state TEXT NOT NULL,
+        error TEXT,
+        profile TEXT NOT NULL,
+        started TEXT NOT NULL,
+        finished TEXT,
+        executors TEXT NOT NULL,
+        stats TEXT
+    );
+    CREATE TABLE executor_stats (
+        run_id INTEGER NOT NULL,
+        executor_id TEXT NOT NULL,
+        stats TEXT NOT NULL,
+        PRIMARY KEY (run_id, executor_id)
+    );
+    CREATE TABLE run_findings (
+        run_id INTEGER NOT NULL,
+        signature TEXT NOT NULL,
+        executor_id TEXT NOT NULL,
+        finding TEXT NOT NULL,
+        PRIMARY KEY (run_id, signature)
+    );",
+];
+
+static DB: OnceLock<Mutex<ResultsDb>> = OnceLock::new();
+
+/// A change to the results database and what it is, for the log if it fails.
+type Write = (&'static str, Box<dyn FnOnce(&ResultsDb) -> rusqlite::Result<()> + Send>);
+
+/// Queue of the writer thread, which applies changes one at a time in the order they came.
+static WRITES: OnceLock<mpsc::Sender<Write>> = OnceLock::new();
+
+/// A run as stored, with its state, profile, executors and statistics as JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredRun {
+    pub id: u64,
+    pub state: String,
+    pub error: Option<String>,
+    pub profile: String,
+    pub started: String,
+    pub finished: Option<String>,
+    pub executors: String,
+    pub stats: Option<String>,
+}
+
+/// A finding first stored during a run, with everything needed to reproduce it.
+#[derive(Serialize, Debug, Clone)]
+pub struct RunFinding {
+    pub signature: String,
+    pub executor_id: String,
+    pub finding: Finding,
+}
+
+pub struct ResultsDb {
+    conn: Connection,
+}
+
+impl ResultsDb {
+    /// Opens or creates the database at `path` and brings its schema up to date.
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::migrated(conn)
+    }
+
+    fn migrated(mut conn: Connection) -> rusqlite::Result<Self> {
+        let applied: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", version + 1)?;
+            tx.commit()?;
+            log::info!("Results database migrated to version {}", version + 1);
+        }
+        Ok(Self { conn })
+    }
+
+    pub fn save_run(&self, run: &StoredRun) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO runs (id, state, error, profile, started, finished, executors, stats)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                run.id as i64,
+                run.state,
+                run.error,
+                run.profile,
+                run.started,
+                run.finished,
+                run.executors,
+                run.stats
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn runs(&self) -> rusqlite::Result<Vec<StoredRun>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, state, error, profile, started, finished, executors, stats FROM runs ORDER BY id")?;
+        stmt.query_map([], |row| {
+            Ok(StoredRun {
+                id: row.get::<_, i64>(0)? as u64,
+                state: row.get(1)?,
+                error: row.get(2)?,
+                profile: row.get(3)?,
+                started: row.get(4)?,
+                finished: row.get(5)?,
+                executors: row.get(6)?,
+                stats: row.get(7)?,
+            })
+        })?
+        .collect()
+    }
+
+    /// Keeps the latest submission of an executor, replacing its earlier ones.
+    pub fn save_stats(&self, run_id: u64, executor_id: &str, stats: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO executor_stats (run_id, executor_id, stats) VALUES (?1, ?2, ?3)",
+            params![run_id as i64, executor_id, stats],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear_stats(&self, run_id: u64) -> rusqlite::Result<()> {
+        self.conn.execute("DELETE FROM executor_stats WHERE run_id = ?1", [run_id as i64])?;
+        Ok(())
+    }
+
+    /// Every executor's latest submission as `(run id, stats)`.
+    pub fn stats(&self) -> rusqlite::Result<Vec<(u64, String)>> {
+        let mut stmt = self.conn.prepare("SELECT run_id, stats FROM executor_stats ORDER BY run_id, executor_id")?;
+        stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)))?
+            .collect()
+    }
+
+    pub fn save_finding(&self, run_id: u64, executor_id: &str, finding: &Finding) -> rusqlite::Result<()> {
+        let finding_json = serde_json::to_string(finding).unwrap_or_default();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO run_findings (run_id, signature, executor_id, finding) VALUES (?1, ?2, ?3, ?4)",
+            params![run_id as i64, finding.signature().key(), executor_id, finding_json],
+        )?;
+        Ok(())
+    }
+
+    /// Findings first stored during the run, in the order they were made.
+    pub fn findings(&self, run_id: u64) -> rusqlite::Result<Vec<RunFinding>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT signature, executor_id, finding FROM run_findings WHERE run_id = ?1 ORDER BY rowid")?;
+        let rows = stmt.query_map([run_id as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+        let mut findings = Vec::new();
+        for row in rows {
+            let (signature, executor_id, finding) = row?;
+            match serde_json::from_str(&finding) {
+                Ok(finding) => findings.push(RunFinding {
+                    signature,
+                    executor_id,
+                    finding,
+                }),
+                Err(e) => log::warn!("Skipping unreadable finding {}: {}", signature, e),
+            }
+        }
+        Ok(findings)
+    }
+}
+
+/// Opens the server's results database; until then, or if it fails, nothing is persisted.
+pub fn init(path: &Path) {
+    match ResultsDb::open(path) {
+        Ok(db) => {
+            let _ = DB.set(Mutex::new(db));
+            let (writes, queued) = mpsc::channel::<Write>();
+            std::thread::spawn(move || {
+                for (action, f) in queued {
+                    with_db(action, f);
+                }
+            });
+            let _ = WRITES.set(writes);
+        }
+        Err(e) => log::error!("Failed to open results database {}, history will not be kept: {}", path.display(), e),
+    }
+}
+
+/// Queues `f` for the writer thread and returns right away, so request handlers and the
+/// fork server never wait on SQLite or on each other's writes.
+pub fn write(action: &'static str, f: impl FnOnce(&ResultsDb) -> rusqlite::Result<()> + Send + 'static) {
+    if let Some(writes) = WRITES.get() {
+        let _ = writes.send((action, Box::new(f)));
+    }
+}
+
+/// Runs `f` on the results database, logging what failed. Blocks, so async code calls it
+/// through `web::block`; changes go through [`write`].
+pub fn with_db<T>(action: &str, f: impl FnOnce(&ResultsDb) -> rusqlite::Result<T>) -> Option<T> {
+    let db = DB.get()?.lock().unwrap();
+    f(&db).inspect_err(|e| log::warn!("Failed to {}: {}", action, e)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_apply_once_and_runs_round_trip() {
+        let db = ResultsDb::migrated(Connection::open_in_memory().unwrap()).unwrap();
+        let run = StoredRun {
+            id: 3,
+            state: "running".to_string(),
+            error: None,
+            profile: "{}".to_string(),
+            started: "2025-01-01T00:00:00+00:00".to_string(),
+            finished: None,
+            executors: "[]".to_string(),
+            stats: None,
+        };
+        db.save_run(&run).unwrap();
+        let finished = StoredRun {
+            state: "finished".to_string(),
+            ..run
+        };
+        db.save_run(&finished).unwrap();
+
+        // Reopening with every migration applied leaves the data alone
+        let db = ResultsDb::migrated(db.conn).unwrap();
+        let version: usize = db.conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        assert_eq!(db.runs().unwrap(), vec![finished]);
+    }
+}
@@ -0,0 +1,378 @@
+// Runs: one start of the executors for a profile, tracked from start to finish so it can be
+// queried and stopped while it runs and looked up afterwards.
+use crate::fork_server::{self, ExecutorExit};
+use crate::results::{self, StoredRun};
+use actix_web::{HttpResponse, Responder, web};
+use serde::{Deserialize, Serialize};
+use sqlsmith_rs_common::profile::{Profile, read_profile};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+static RUNS: Mutex<BTreeMap<u64, Run>> = Mutex::new(BTreeMap::new());
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunState {
+    Running,
+    Finished,
+    Stopped,
+    /// The executors could not be started
+    Failed,
+    /// The server stopped while the run was going
+    Interrupted,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutorState {
+    Pending,
+    Running,
+    Finished,
+    Crashed,
+    Hung,
+    Stopped,
+    /// Could not be started
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExecutorStatus {
+    pub pid: Option<u32>,
+    /// Seed of the current process, which moves on when a crashed executor is restarted
+    pub seed: u64,
+    pub state: ExecutorState,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub restarts: usize,
+    /// Processes of the slot that crashed or hung, including those restarted since
+    #[serde(default)]
+    pub crashes: usize,
+    #[serde(default)]
+    pub hangs: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Run {
+    pub id: u64,
+    pub state: RunState,
+    pub error: Option<String>,
+    /// The profile the run was started with
+    pub profile: Profile,
+    pub started: String,
+    pub finished: Option<String>,
+    pub executors: Vec<ExecutorStatus>,
+    /// Aggregated statistics once the run is over or its statistics were archived
+    pub stats: Option<serde_json::Value>,
+    #[serde(skip)]
+    stop: watch::Sender<bool>,
+}
+
+impl Run {
+    fn stored(&self) -> StoredRun {
+        StoredRun {
+            id: self.id,
+            // Plain `running`, `finished`, ... rather than a JSON string, so it reads well in SQL
+            state: serde_json::to_value(self.state)
+                .ok()
+                .and_then(|state| state.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            error: self.error.clone(),
+            profile: serde_json::to_string(&self.profile).unwrap_or_default(),
+            started: self.started.clone(),
+            finished: self.finished.clone(),
+            executors: serde_json::to_string(&self.executors).unwrap_or_default(),
+            stats: self.stats.as_ref().map(|stats| stats.to_string()),
+        }
+    }
+
+    /// A run of an earlier server process; one that was still going is marked interrupted.
+    fn restored(stored: StoredRun) -> serde_json::Result<Self> {
+        let mut run = Run {
+            id: stored.id,
+            state: serde_json::from_value(serde_json::Value::String(stored.state))?,
+            error: stored.error,
+            profile: serde_json::from_str(&stored.profile)?,
+            started: stored.started,
+            finished: stored.finished,
+            executors: serde_json::from_str(&stored.executors)?,
+            stats: stored.stats.map(|stats| serde_json::from_str(&stats)).transpose()?,
+            stop: watch::channel(true).0,
+        };
+        if run.state == RunState::Running {
+            run.state = RunState::Interrupted;
+            run.error = Some("The server stopped while the run was going".to_string());
+            for executor in &mut run.executors {
+                if matches!(executor.state, ExecutorState::Pending | ExecutorState::Running) {
+                    executor.state = ExecutorState::Stopped;
+                }
+            }
+        }
+        Ok(run)
+    }
+}
+
+fn save(run: Option<StoredRun>) {
+    if let Some(run) = run {
+        results::write("save run", move |db| db.save_run(&run));
+    }
+}
+
+/// What the fork server needs to report on a run and to notice it being stopped.
+#[derive(Clone)]
+pub struct Tracker {
+    pub run_id: u64,
+    stop: watch::Receiver<bool>,
+}
+
+impl Tracker {
+    /// A receiver that changes to true once the run is asked to stop.
+    pub fn stop_signal(&self) -> watch::Receiver<bool> {
+        self.stop.clone()
+    }
+
+    pub fn executor_started(&self, slot: usize, pid: Option<u32>, seed: u64, restarts: usize) {
+        self.update(slot, |executor| {
+            *executor = ExecutorStatus {
+                pid,
+                seed,
+                state: ExecutorState::Running,
+                exit_code: None,
+                signal: None,
+                restarts,
+                crashes: executor.crashes,
+                hangs: executor.hangs,
+            }
+        });
+    }
+
+    pub fn executor_exited(&self, slot: usize, exit: &ExecutorExit) {
+        self.update(slot, |executor| {
+            executor.state = match exit {
+                ExecutorExit::Finished => ExecutorState::Finished,
+                ExecutorExit::Failed(_) | ExecutorExit::Signaled(_) => ExecutorState::Crashed,
+                ExecutorExit::TimedOut(_) => ExecutorState::Hung,
+                ExecutorExit::Stopped => ExecutorState::Stopped,
+            };
+            match exit {
+                ExecutorExit::Failed(_) | ExecutorExit::Signaled(_) => executor.crashes += 1,
+                ExecutorExit::TimedOut(_) => executor.hangs += 1,
+                ExecutorExit::Finished | ExecutorExit::Stopped => {}
+            }
+            match exit {
+                ExecutorExit::Finished => executor.exit_code = Some(0),
+                ExecutorExit::Failed(code) => executor.exit_code = Some(*code),
+                ExecutorExit::Signaled(signal) => executor.signal = Some(*signal),
+                ExecutorExit::TimedOut(_) | ExecutorExit::Stopped => {}
+            }
+        });
+    }
+
+    pub fn executor_failed(&self, slot: usize) {
+        self.update(slot, |executor| executor.state = ExecutorState::Failed);
+    }
+
+    fn update(&self, slot: usize, f: impl FnOnce(&mut ExecutorStatus)) {
+        let stored = RUNS.lock().unwrap().get_mut(&self.run_id).and_then(|run| {
+            f(run.executors.get_mut(slot)?);
+            Some(run.stored())
+        });
+        save(stored);
+    }
+}
+
+/// Registers a run of `profile` and starts its executors in the background.
+pub fn start(profile: Profile) -> (u64, JoinHandle<()>) {
+    let (stop, stop_signal) = watch::channel(false);
+    let mut runs = RUNS.lock().unwrap();
+    let id = runs.keys().next_back().map_or(1, |last| last + 1);
+    let base_seed = profile.seed.unwrap_or(0);
+    let executors = (0..profile.executor_count.unwrap_or(0))
+        .map(|n| ExecutorStatus {
+            pid: None,
+            seed: fork_server::executor_seed(base_seed, n),
+            state: ExecutorState::Pending,
+            exit_code: None,
+            signal: None,
+            restarts: 0,
+            crashes: 0,
+            hangs: 0,
+        })
+        .collect();
+    let run = Run {
+        id,
+        state: RunState::Running,
+        error: None,
+        profile: profile.clone(),
+        started: chrono::Utc::now().to_rfc3339(),
+        finished: None,
+        executors,
+        stats: None,
+        stop,
+    };
+    let stored = run.stored();
+    runs.insert(id, run);
+    drop(runs);
+    save(Some(stored));
+
+    let tracker = Tracker {
+        run_id: id,
+        stop: stop_signal,
+    };
+    let handle = tokio::spawn(async move {
+        let result = fork_server::fork_server_main(&profile, &tracker).await;
+        let stopped = *tracker.stop.borrow();
+        // Taken before locking RUNS, which it reads to count active executors
+        let stats = crate::stats::summary(id);
+        let stored = RUNS.lock().unwrap().get_mut(&id).map(|run| {
+            run.state = match &result {
+                Err(_) => RunState::Failed,
+                Ok(()) if stopped => RunState::Stopped,
+                Ok(()) => RunState::Finished,
+            };
+            run.error = result.err();
+            run.finished = Some(chrono::Utc::now().to_rfc3339());
+            run.stats = Some(stats);
+            run.stored()
+        });
+        save(stored);
+        log::info!("Run {} is over", id);
+    });
+    (id, handle)
+}
+
+/// Executors of the run still going, for its stats summary.
+pub fn active_executors(id: u64) -> usize {
+    RUNS.lock().unwrap().get(&id).map_or(0, |run| {
+        run.executors
+            .iter()
+            .filter(|executor| executor.state == ExecutorState::Running)
+            .count()
+    })
+}
+
+/// What `/metrics` reports of a run's executors.
+pub struct RunMetrics {
+    pub id: u64,
+    pub driver: String,
+    pub active: usize,
+    pub restarts: usize,
+    pub crashes: usize,
+    pub hangs: usize,
+}
+
+pub fn metrics() -> Vec<RunMetrics> {
+    RUNS.lock()
+        .unwrap()
+        .values()
+        .map(|run| RunMetrics {
+            id: run.id,
+            driver: driver_name(&run.profile),
+            active: run.executors.iter().filter(|executor| executor.state == ExecutorState::Running).count(),
+            restarts: run.executors.iter().map(|executor| executor.restarts).sum(),
+            crashes: run.executors.iter().map(|executor| executor.crashes).sum(),
+            hangs: run.executors.iter().map(|executor| executor.hangs).sum(),
+        })
+        .collect()
+}
+
+/// The driver a run used as named in profiles, e.g. `SQLITE_IN_MEM`.
+fn driver_name(profile: &Profile) -> String {
+    profile.driver.map_or_else(|| "unknown".to_string(), |driver| format!("{:?}", driver))
+}
+
+/// Id of the run started last.
+pub fn latest() -> Option<u64> {
+    RUNS.lock().unwrap().keys().next_back().copied()
+}
+
+/// Replaces the statistics kept with the run; false if there is no such run.
+pub fn archive_stats(id: u64, stats: serde_json::Value) -> bool {
+    let stored = RUNS.lock().unwrap().get_mut(&id).map(|run| {
+        run.stats = Some(stats);
+        run.stored()
+    });
+    let found = stored.is_some();
+    save(stored);
+    found
+}
+
+/// Loads the runs of earlier server processes from the results database, so their history
+/// stays available and new runs continue their ids. Statistics have to be restored first.
+pub fn restore() {
+    let Some(stored) = results::with_db("load runs", |db| db.runs()) else {
+        return;
+    };
+    let mut restored = Vec::new();
+    for stored in stored {
+        let (id, interrupted) = (stored.id, stored.state == "running");
+        match Run::restored(stored) {
+            Ok(mut run) => {
+                if interrupted {
+                    // Taken before RUNS is filled, so no executor counts as active
+                    run.stats = Some(crate::stats::summary(id));
+                    save(Some(run.stored()));
+                }
+                restored.push(run);
+            }
+            Err(e) => log::warn!("Skipping unreadable run {}: {}", id, e),
+        }
+    }
+    log::info!("Restored {} runs from the results database", restored.len());
+    let mut runs = RUNS.lock().unwrap();
+    runs.extend(restored.into_iter().map(|run| (run.id, run)));
+}
+
+// POST /runs: starts a run with the profile in the body, or profile.json when the body is empty
+pub async fn create_run(body: web::Bytes) -> impl Responder {
+    let profile = if body.is_empty() {
+        read_profile()
+    } else {
+        match serde_json::from_slice::<Profile>(&body) {
+            Ok(profile) => profile,
+            Err(e) => return HttpResponse::BadRequest().body(format!("Invalid profile: {}", e)),
+        }
+    };
+    profile.print();
+    let (id, _) = start(profile);
+    HttpResponse::Accepted().json(serde_json::json!({ "id": id }))
+}
+
+// GET /runs
+pub async fn list_runs() -> impl Responder {
+    let runs = RUNS.lock().unwrap();
+    HttpResponse::Ok().json(runs.values().collect::<Vec<_>>())
+}
+
+// GET /runs/{id}
+pub async fn show_run(id: web::Path<u64>) -> impl Responder {
+    match RUNS.lock().unwrap().get(&id) {
+        Some(run) => HttpResponse::Ok().json(run),
+        None => HttpResponse::NotFound().body(format!("No run {}", id)),
+    }
+}
+
+// GET /runs/{id}/findings: findings first stored during the run, with their reproducers
+pub async fn show_run_findings(id: web::Path<u64>) -> impl Responder {
+    let id = id.into_inner();
+    if !RUNS.lock().unwrap().contains_key(&id) {
+        return HttpResponse::NotFound().body(format!("No run {}", id));
+    }
+    match web::block(move || results::with_db("load findings", |db| db.findings(id))).await {
+        Ok(Some(findings)) => HttpResponse::Ok().json(findings),
+        _ => HttpResponse::ServiceUnavailable().body("The results database is not available"),
+    }
+}
+
+// POST /runs/{id}/stop: kills the run's executors; they are not restarted
+pub async fn stop_run(id: web::Path<u64>) -> impl Responder {
+    match RUNS.lock().unwrap().get(&id) {
+        Some(run) if run.state == RunState::Running => {
+            run.stop.send_replace(true);
+            HttpResponse::Accepted().body(format!("Stopping run {}", id))
+        }
+        Some(_) => HttpResponse::Conflict().body(format!("Run {} is not running", id)),
+        None => HttpResponse::NotFound().body(format!("No run {}", id)),
+    }
+}
@@ -0,0 +1,352 @@
+// Statistics submitted by executors, kept per run and per executor so that runs do not mix
+// and a run's totals can be reset or archived with the run.
+use crate::{live, results, runs};
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Run id that submissions from executors not started by a run are kept under.
+const NO_RUN: u64 = 0;
+
+// Define ExecutionStats locally since sqlsmith_rs_executor is not available
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExecutionStats {
+    pub elapsed_ms: u64,
+    pub success_count: usize,
+    pub failed_expected_count: usize,
+    pub failed_new_count: usize,
+    #[serde(default)]
+    pub oracle_mismatch_count: usize,
+    #[serde(default)]
+    pub unique_plan_count: usize,
+    pub total_queries: usize,
+    pub thread_count: usize,
+    pub queries_per_second: f64,
+    pub error_rate: f64,
+    pub stmt_type_counts: HashMap<String, usize>,
+    pub executor_id: String,
+    /// Run that started the executor; None when it was started by hand
+    #[serde(default)]
+    pub run_id: Option<u64>,
+    /// Sent periodically while the executor runs; the final submission has it false
+    #[serde(default)]
+    pub in_progress: bool,
+    pub timestamp: String,
+}
+
+impl ExecutionStats {
+    /// When the executor started and finished, from its submission time and elapsed time.
+    fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let end = DateTime::parse_from_rfc3339(&self.timestamp).ok()?.with_timezone(&Utc);
+        Some((end - chrono::Duration::milliseconds(self.elapsed_ms as i64), end))
+    }
+}
+
+/// Latest submission of every executor, by run id and executor id.
+static RUN_STATS: Mutex<BTreeMap<u64, BTreeMap<String, ExecutionStats>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Totals {
+    executors: usize,
+    completed_executors: usize,
+    success_count: usize,
+    pub(crate) failed_expected_count: usize,
+    pub(crate) failed_new_count: usize,
+    oracle_mismatch_count: usize,
+    // Executors explain plans independently, so their counts overlap and are not summed
+    max_unique_plan_count: usize,
+    pub(crate) total_queries: usize,
+    thread_count: usize,
+    max_elapsed_ms: u64,
+    /// From the first executor starting to the last one finishing
+    wall_clock_ms: u64,
+    pub(crate) stmt_type_counts: HashMap<String, usize>,
+    last_updated: Option<String>,
+}
+
+impl Totals {
+    fn of<'a>(executors: impl IntoIterator<Item = &'a ExecutionStats>) -> Self {
+        let mut totals = Totals::default();
+        let (mut first_start, mut last_end) = (None::<DateTime<Utc>>, None::<DateTime<Utc>>);
+        for stats in executors {
+            totals.executors += 1;
+            totals.completed_executors += usize::from(!stats.in_progress);
+            totals.success_count += stats.success_count;
+            totals.failed_expected_count += stats.failed_expected_count;
+            totals.failed_new_count += stats.failed_new_count;
+            totals.oracle_mismatch_count += stats.oracle_mismatch_count;
+            totals.max_unique_plan_count = totals.max_unique_plan_count.max(stats.unique_plan_count);
+            totals.total_queries += stats.total_queries;
+            totals.thread_count += stats.thread_count;
+            totals.max_elapsed_ms = totals.max_elapsed_ms.max(stats.elapsed_ms);
+            for (stmt_type, count) in &stats.stmt_type_counts {
+                *totals.stmt_type_counts.entry(stmt_type.clone()).or_insert(0) += count;
+            }
+            if let Some((start, end)) = stats.span() {
+                first_start = Some(first_start.map_or(start, |first| first.min(start)));
+                last_end = Some(last_end.map_or(end, |last| last.max(end)));
+            }
+            if totals.last_updated.as_ref().is_none_or(|last| *last < stats.timestamp) {
+                totals.last_updated = Some(stats.timestamp.clone());
+            }
+        }
+        totals.wall_clock_ms = match (first_start, last_end) {
+            (Some(start), Some(end)) => (end - start).num_milliseconds().max(0) as u64,
+            _ => totals.max_elapsed_ms,
+        };
+        totals
+    }
+
+    fn queries_per_second(&self) -> f64 {
+        if self.wall_clock_ms > 0 {
+            (self.total_queries as f64) / (self.wall_clock_ms as f64 / 1000.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.total_queries > 0 {
+            (self.failed_new_count as f64 / self.total_queries as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Totals of every run executors submitted to, by run id.
+pub(crate) fn run_totals() -> BTreeMap<u64, Totals> {
+    let run_stats = RUN_STATS.lock().unwrap();
+    run_stats.iter().map(|(run_id, executors)| (*run_id, Totals::of(executors.values()))).collect()
+}
+
+/// Aggregated statistics of a run with a breakdown per executor.
+pub fn summary(run_id: u64) -> serde_json::Value {
+    let active_executors = runs::active_executors(run_id);
+    let run_stats = RUN_STATS.lock().unwrap();
+    let executors = run_stats.get(&run_id).map(|executors| executors.values().collect::<Vec<_>>()).unwrap_or_default();
+    let totals = Totals::of(executors.iter().copied());
+
+    let mut summary = json!({
+        "run_id": run_id,
+        "timestamp": totals.last_updated.clone().unwrap_or_else(|| Utc::now().to_rfc3339()),
+        "executor_stats": {
+            "total_executors": totals.executors,
+            "active_executors": active_executors,
+            "completed_executors": totals.completed_executors,
+        },
+        "execution_results": {
+            "total_queries": totals.total_queries,
+            "successful_queries": totals.success_count,
+            "failed_expected_queries": totals.failed_expected_count,
+            "failed_new_queries": totals.failed_new_count,
+            "oracle_mismatches": totals.oracle_mismatch_count,
+            "unique_plans": totals.max_unique_plan_count,
+            "error_rate": totals.error_rate(),
+            "stmt_type_counts": totals.stmt_type_counts,
+        },
+        "performance": {
+            "max_execution_time_ms": totals.max_elapsed_ms,
+            "wall_clock_ms": totals.wall_clock_ms,
+            "queries_per_second": totals.queries_per_second(),
+            "total_thread_count": totals.thread_count,
+        },
+        "executors": executors,
+    });
+    if executors.is_empty() {
+        summary["message"] = json!("No executor statistics collected yet");
+    }
+    summary
+}
+
+/// Loads what executors of earlier server processes submitted from the results database.
+pub fn restore() {
+    let Some(stored) = results::with_db("load statistics", |db| db.stats()) else {
+        return;
+    };
+    let mut run_stats = RUN_STATS.lock().unwrap();
+    for (run_id, json) in stored {
+        match serde_json::from_str::<ExecutionStats>(&json) {
+            Ok(stats) => {
+                run_stats.entry(run_id).or_default().insert(stats.executor_id.clone(), stats);
+            }
+            Err(e) => log::warn!("Skipping unreadable statistics of run {}: {}", run_id, e),
+        }
+    }
+}
+
+/// Keeps `stats` as the latest of its executor and publishes the run's new totals.
+fn store(run_id: u64, stats: ExecutionStats) -> Totals {
+    if let Ok(json) = serde_json::to_string(&stats) {
+        let executor_id = stats.executor_id.clone();
+        results::write("save statistics", move |db| db.save_stats(run_id, &executor_id, &json));
+    }
+    let totals = {
+        let mut run_stats = RUN_STATS.lock().unwrap();
+        let executors = run_stats.entry(run_id).or_default();
+        executors.insert(stats.executor_id.clone(), stats);
+        Totals::of(executors.values())
+    };
+    // Summarized after releasing RUN_STATS, which summary() locks again
+    live::publish_stats(run_id, totals.total_queries, totals.failed_new_count, summary(run_id));
+    totals
+}
+
+/// Counts an executor as completed once its process is gone; one that crashed, hung or was
+/// stopped never sends its final submission, so its last progress would stay in progress.
+pub fn executor_exited(run_id: u64, executor_id: &str) {
+    let last = RUN_STATS.lock().unwrap().get(&run_id).and_then(|executors| executors.get(executor_id)).cloned();
+    if let Some(mut stats) = last.filter(|stats| stats.in_progress) {
+        stats.in_progress = false;
+        store(run_id, stats);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CollectQuery {
+    run: Option<u64>,
+}
+
+// 新增处理函数，用于收集执行器结果; the latest run unless ?run= names one
+pub async fn collect_executor_results(query: web::Query<CollectQuery>) -> impl Responder {
+    let run_id = query.run.unwrap_or_else(|| {
+        let submitted = RUN_STATS.lock().unwrap().keys().next_back().copied();
+        runs::latest().max(submitted).unwrap_or(NO_RUN)
+    });
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .json(summary(run_id))
+}
+
+// GET /runs/{id}/stats
+pub async fn show_run_stats(id: web::Path<u64>) -> impl Responder {
+    HttpResponse::Ok().json(summary(*id))
+}
+
+// POST /runs/{id}/stats/reset: forgets what the run's executors submitted
+pub async fn reset_run_stats(id: web::Path<u64>) -> impl Responder {
+    let id = id.into_inner();
+    RUN_STATS.lock().unwrap().remove(&id);
+    live::clear_series(id);
+    results::write("reset statistics", move |db| db.clear_stats(id));
+    HttpResponse::Ok().body(format!("Statistics of run {} reset", id))
+}
+
+// POST /runs/{id}/stats/archive: keeps the current totals with the run and resets them
+pub async fn archive_run_stats(id: web::Path<u64>) -> impl Responder {
+    let id = id.into_inner();
+    let summary = summary(id);
+    if !runs::archive_stats(id, summary) {
+        return HttpResponse::NotFound().body(format!("No run {}", id));
+    }
+    RUN_STATS.lock().unwrap().remove(&id);
+    live::clear_series(id);
+    results::write("reset statistics", move |db| db.clear_stats(id));
+    HttpResponse::Ok().body(format!("Statistics of run {} archived", id))
+}
+
+// 新增处理函数，用于接收执行器统计提交
+pub async fn handle_stat_submission(stats: web::Json<ExecutionStats>) -> impl Responder {
+    if stats.in_progress {
+        log::debug!("Received executor progress from: {}", stats.executor_id);
+    } else {
+        log::info!("Received executor statistics from: {}", stats.executor_id);
+    }
+
+    // Check if executor_id is a valid number
+    if stats.executor_id.parse::<u32>().is_err() {
+        return HttpResponse::BadRequest().body("executor_id must be a valid number");
+    }
+
+    let stats = stats.into_inner();
+    let run_id = stats.run_id.unwrap_or(NO_RUN);
+    let totals = store(run_id, stats);
+
+    let summary = format!(
+        "Statistics updated successfully!\n\nAggregated Results (run {}):\n\
+        Executors: {}\n\
+        Total Queries: {}\n\
+        Success: {}\n\
+        Failed (expected): {}\n\
+        Failed (new): {}\n\
+        Oracle mismatches: {}\n\
+        Unique plans (max per executor): {}\n\
+        Total Threads: {}\n\
+        Overall QPS: {:.2}\n\
+        Overall Error Rate: {:.2}%\n\
+        Wall Clock Time: {}ms\n\
+        Last Updated: {}",
+        run_id,
+        totals.executors,
+        totals.total_queries,
+        totals.success_count,
+        totals.failed_expected_count,
+        totals.failed_new_count,
+        totals.oracle_mismatch_count,
+        totals.max_unique_plan_count,
+        totals.thread_count,
+        totals.queries_per_second(),
+        totals.error_rate(),
+        totals.wall_clock_ms,
+        totals.last_updated.unwrap_or_default()
+    );
+
+    HttpResponse::Ok().body(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(executor_id: &str, elapsed_ms: u64, total_queries: usize, finished: &str) -> ExecutionStats {
+        ExecutionStats {
+            elapsed_ms,
+            success_count: total_queries,
+            failed_expected_count: 0,
+            failed_new_count: 0,
+            oracle_mismatch_count: 0,
+            unique_plan_count: 0,
+            total_queries,
+            thread_count: 1,
+            queries_per_second: 0.0,
+            error_rate: 0.0,
+            stmt_type_counts: HashMap::new(),
+            executor_id: executor_id.to_string(),
+            run_id: Some(1),
+            in_progress: false,
+            timestamp: finished.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_qps_is_over_wall_clock_time() {
+        // 0 runs from 00:00:00 to 00:00:04, 1 from 00:00:02 to 00:00:08
+        let executors = [
+            stats("0", 4000, 400, "2025-01-01T00:00:04+00:00"),
+            stats("1", 6000, 800, "2025-01-01T00:00:08+00:00"),
+        ];
+        let totals = Totals::of(&executors);
+        assert_eq!(totals.executors, 2);
+        assert_eq!(totals.max_elapsed_ms, 6000);
+        assert_eq!(totals.wall_clock_ms, 8000);
+        assert_eq!(totals.queries_per_second(), 150.0);
+        assert_eq!(totals.last_updated.as_deref(), Some("2025-01-01T00:00:08+00:00"));
+    }
+
+    #[test]
+    fn test_exited_executor_counts_as_completed() {
+        let progress = ExecutionStats {
+            in_progress: true,
+            ..stats("7", 2000, 100, "2025-01-01T00:00:02+00:00")
+        };
+        RUN_STATS.lock().unwrap().entry(7).or_default().insert("7".to_string(), progress);
+        executor_exited(7, "7");
+        executor_exited(7, "8");
+        let totals = run_totals().remove(&7).unwrap();
+        assert_eq!((totals.executors, totals.completed_executors), (1, 1));
+        assert_eq!(totals.total_queries, 100);
+    }
+}
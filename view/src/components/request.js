@@ -42,4 +42,13 @@ export const fetchStats = async () => {
     console.error('Failed to fetch stats:', error);
     throw error;
   }
-};
\ No newline at end of file
+};
+// Subscribe to live run statistics and new findings pushed by the server
+export const subscribeLive = ({ onStats, onFinding, onOpen, onError }) => {
+  const source = new EventSource('http://127.0.0.1:8080/live');
+  source.addEventListener('stats', (event) => onStats?.(JSON.parse(event.data)));
+  source.addEventListener('finding', (event) => onFinding?.(JSON.parse(event.data)));
+  source.onopen = () => onOpen?.();
+  source.onerror = (error) => onError?.(error);
+  return source;
+};